@@ -4,23 +4,35 @@ use egui::{
     Align,
     Button,
     CentralPanel,
+    Align2,
     Color32,
+    ComboBox,
     Context,
+    FontId,
     Frame,
+    Grid,
     IconData,
+    Image,
     Id,
+    Key,
     Label,
     Layout,
+    load::SizedTexture,
     Margin,
     PointerButton,
+    Rect,
     Response,
     Rgba,
     RichText,
     ScrollArea,
     Sense,
+    Shape,
     SidePanel,
     Slider,
+    Stroke,
     TextEdit,
+    TextureHandle,
+    TextureOptions,
     TopBottomPanel,
     Ui,
     Vec2,
@@ -28,7 +40,8 @@ use egui::{
     ViewportClass,
     ViewportCommand,
     ViewportId,
-    Visuals
+    Visuals,
+    pos2
 };
 
 use egui_extras::install_image_loaders;
@@ -36,20 +49,49 @@ use egui_file_dialog::FileDialog;
 use image::open;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io::Result,
-    sync::{Arc, Mutex},
+    path::Path,
+    sync::{Arc, Mutex, mpsc::{Receiver, TryRecvError}},
     time::{Duration, Instant}
 };
 
 use comrad::{
     media::{
+        batch::{self, Batch},
+        broadcast::Broadcast,
+        encoder::Format,
+        library::Library,
+        lyrics::Lyrics,
+        mpd::Mpd,
+        organize::{self, Move, Retag},
         playback::Playback,
-        source::Playlist
+        podcast::{Episode, Podcast, Podcasts},
+        remote::{self, Command, PlayerState, Remote},
+        scanner::Scanner,
+        server::{NowPlaying, Server},
+        watcher::{Change, Watcher},
+        source::{self, Mount, Playlist, Root, Station},
+        stream::StreamStatus,
+        subtitles::Subtitles,
+        video::Video,
+        visualizer::{VisualMode, Visualizer}
     },
-    utils::{data, filesys, images, styles}
+    utils::{
+        artwork::Artwork,
+        data::{self, TagField, Tags},
+        filesys,
+        images,
+        styles,
+        web
+    }
 };
 
+#[cfg(target_os = "linux")]
+use comrad::media::mpris::Mpris;
+
+enum ListMode { Playlists, Podcasts, Stations }
+enum OrganizeMode { Rename, Tag }
 enum PlayState { Paused, Playing, Stopped }
 enum RepeatState { All, None, One }
 
@@ -58,60 +100,447 @@ impl Main {
     fn new(cc: &CreationContext<'_>) -> Self {
         images::load(cc);
 
-        let dir = filesys::get_dir();
+        let roots = filesys::get_roots();
+        let playlists = filesys::get_playlists();
+        let mut missing: HashSet<String> = HashSet::new();
+
+        for playlist in &playlists {
+            if let Some(sources) = playlist.get_sources() {
+                for source in sources {
+                    if !filesys::is_url(source.clone()) && !filesys::exists(source.clone()) {
+                        missing.insert(source);
+                    };
+                };
+            };
+        };
+
+        // Mounts are only known once the first probe lands, so the dialogs open at the first root
+        let start = roots.first().map(|root| root.get_path()).unwrap_or(".".to_string());
+
+        let watched = roots.iter().filter(|root| root.is_enabled()).map(|root| root.get_path())
+            .collect::<Vec<_>>();
+
+        let mut podcasts = Podcasts::new();
+
+        podcasts.refresh_all();
+
+        let playback = Playback::new();
+        let visualizer = Visualizer::new(playback.get_samples());
+
+        let (port, format, serving) = filesys::get_server();
+        let format = Format::from_label(&format).unwrap_or(Format::Mp3);
+        let mut server = Server::new(playback.get_outputs(), port, format);
+
+        if serving { server.start(); };
+
+        let library = Library::new();
+        let (answering, mpd_port, mpd_password) = filesys::get_mpd();
+        let mut mpd = Mpd::new(cc.egui_ctx.clone(), library.get_entries(), mpd_port,
+            mpd_password.clone());
+
+        if answering { mpd.start(); };
+
+        let (controlled, remote_port, token) = filesys::get_remote();
+        let mut remote = Remote::new(cc.egui_ctx.clone(), remote_port, token);
+
+        if controlled { remote.start(); };
+
+        let mount = filesys::get_broadcast();
+        let broadcast = Broadcast::new(playback.get_outputs(), mount.clone());
 
         Self {
             // Booleans
             is_shuffled: false,
+            batch_editor_open: false,
             big_player_open: false,
+            broadcast_open: false,
+            lyrics_editing: false,
+            lyrics_open: false,
             mini_player_open: false,
+            mounts_probed: false,
+            mpd_open: false,
+            organizer_open: false,
             playlist_add_open: false,
             playlist_edit_open: false,
+            feed_open: false,
+            remote_open: false,
+            server_open: false,
+            tag_editor_open: false,
+            url_open: false,
+            scan_cancelled: false,
             start_playback: false,
             stop_playback: false,
 
             // Dialogs
-            dir_dialog: FileDialog::new().initial_directory(dir.clone().into()),
-            playlist_dialog: FileDialog::new().initial_directory(dir.clone().into()),
+            dir_dialog: FileDialog::new().initial_directory(start.clone().into()),
+            playlist_dialog: FileDialog::new().initial_directory(start.clone().into()),
 
             // Directories
-            dir: dir.clone(),
-            path: dir.clone(),
+            dir: String::new(),
+            path: String::new(),
             folders: Vec::new(),
+            mounted: HashSet::new(),
+            mount_probe: Some(source::probe_mounts(roots.clone())),
+            pseudo_roots: Vec::new(),
+            roots,
 
             // Files
-            playback: Arc::new(Mutex::new(Playback::new())),
+            artwork: Artwork::new(),
+            broadcast,
+            library,
+            mpd,
+            #[cfg(target_os = "linux")]
+            mpris: Mpris::new(cc.egui_ctx.clone()),
+            playback: Arc::new(Mutex::new(playback)),
+            podcasts,
+            remote,
+            merge_scanners: Vec::new(),
+            scanner: None,
+            server,
+            subtitles: None,
+            video: None,
+            video_texture: None,
+            visualizer,
+            watcher: Watcher::new(watched, filesys::get_playlists_path()),
 
             // Misc.
             volume: filesys::get_volume(),
+            batch: None,
+            batch_message: String::new(),
+            batch_undo: Vec::new(),
+            broadcast_host: mount.get_host(),
+            broadcast_mount: mount.get_mount(),
+            broadcast_password: mount.get_password(),
+            broadcast_port: mount.get_port().to_string(),
+            broadcast_user: mount.get_user(),
+            feed_input: String::new(),
+            lyrics: None,
+            lyrics_draft: String::new(),
+            lyrics_line: None,
+            lyrics_message: String::new(),
+            lyrics_stamp: 0,
+            mpd_password,
+            mpd_port: mpd_port.to_string(),
+            organize_message: String::new(),
+            organize_moves: Vec::new(),
+            organize_pattern: "%artist%/%album%/%track% - %title%".to_string(),
+            organize_retags: Vec::new(),
+            remote_port: remote_port.to_string(),
+            server_port: port.to_string(),
+            subtitle_offset: 0,
+            subtitle_size: 28.0,
             temp_playlist_name: String::new(),
+            tag_edit: Tags::new(),
+            tag_message: String::new(),
+            tag_original: Tags::new(),
+            url_input: String::new(),
+            url_name: String::new(),
 
             // Playlists
             active_playlist: None,
+            active_podcast: None,
             edited_playlist: None,
             now_playinglist: None,
             pseudo_playlist: None,
             sorted_playlist: None,
-            playlists: filesys::get_playlists(),
+            playlists,
+            stations: filesys::get_stations(),
 
             // Sources
+            missing,
+            lyrics_source: String::new(),
             now_playing: String::new(),
             selected: String::new(),
+            tag_source: String::new(),
+            video_source: String::new(),
 
             // States
+            broadcast_format: mount.get_format(),
+            list_mode: ListMode::Playlists,
+            organize_mode: OrganizeMode::Rename,
             play_state: PlayState::Stopped,
             repeat_state: RepeatState::None,
     
             // Time
             elapsed_time: Duration::ZERO,
             start_time: Duration::ZERO,
-            total_time: Duration::ZERO,
+            total_time: None,
+            seek_preview: None,
+            podcast_saved: Instant::now(),
+            roots_checked: Instant::now(),
             stopwatch_instant: None
         }
     }
 
+    fn get_display_artist(&self, source: &str, title: &str) -> Option<String> {
+        self.library.get_metadata(source).get_artist().or(self.get_station_name(source))
+            .or(self.podcasts.get_title(source)).filter(|artist| artist != title)
+    }
+
+    fn get_display_title(&self, source: &str) -> String {
+        if let Some(episode) = self.podcasts.get_episode(source) { return episode.get_title(); }
+
+        if filesys::is_url(source.to_string()) {
+            // Radio announces the current song in-band, which beats any name for the station
+            let title = match source == self.now_playing {
+                true => self.playback.try_lock().ok()
+                    .and_then(|player| player.get_metadata().get_title()),
+                false => None
+            };
+
+            if let Some(title) = title.or(self.get_station_name(source)) { return title; }
+        };
+
+        match self.library.get_metadata(source).get_title() {
+            Some(title) => title,
+            None => filesys::create_from_path(source.to_string())
+        }
+    }
+
+    fn get_now_playing(&self) -> NowPlaying {
+        let source = self.now_playing.clone();
+        let metadata = self.library.get_metadata(&source);
+
+        let (title, artist) = match source.is_empty() {
+            true => (String::new(), String::new()),
+
+            false => {
+                let title = self.get_display_title(&source);
+                let artist = self.get_display_artist(&source, &title).unwrap_or_default();
+
+                (title, artist)
+            }
+        };
+
+        let state = match self.play_state {
+            PlayState::Paused => "paused",
+            PlayState::Playing => "playing",
+            PlayState::Stopped => "stopped"
+        };
+
+        NowPlaying::new(source, title, artist, metadata.get_album().unwrap_or_default(),
+            state.to_string(), self.get_elapsed_time(), self.total_time)
+    }
+
+    fn is_visualizing(&self) -> bool {
+        // Mirrors what the central panel ends up drawing
+        let covered = self.playlist_edit_open || self.batch_editor_open || self.organizer_open
+            || self.tag_editor_open || self.lyrics_open || self.video.is_some();
+
+        !covered && self.visualizer.get_mode() != VisualMode::Off
+    }
+
+    //** Broadcast **//
+    fn publish_title(&mut self) {
+        if !self.broadcast.is_running() { return; }
+
+        let title = match self.now_playing.is_empty() {
+            true => String::new(),
+
+            false => {
+                let title = self.get_display_title(&self.now_playing);
+
+                match self.get_display_artist(&self.now_playing, &title) {
+                    Some(artist) => format!("{} - {}", artist, title),
+                    None => title
+                }
+            }
+        };
+
+        self.broadcast.set_title(title);
+    }
+
+    fn toggle_broadcast(&mut self) {
+        if self.broadcast.is_running() {
+            self.broadcast.stop();
+
+            return;
+        };
+
+        let port = self.broadcast_port.trim().parse::<u16>()
+            .unwrap_or(self.broadcast.get_mount().get_port());
+
+        let mount = Mount::new(self.broadcast_host.clone(), port, self.broadcast_mount.clone(),
+            self.broadcast_user.clone(), self.broadcast_password.clone(), self.broadcast_format);
+
+        self.broadcast_mount = mount.get_mount();
+        self.broadcast_port = port.to_string();
+
+        let _ = filesys::edit_broadcast(mount.clone());
+
+        self.broadcast.set_mount(mount);
+        self.broadcast.start();
+    }
+
+    //** Lyrics **//
+    fn apply_lyrics(&mut self) {
+        let offset = self.lyrics.as_ref().map(|lyrics| lyrics.get_offset()).unwrap_or(0);
+        let mut lyrics = Lyrics::parse(&self.lyrics_draft);
+
+        if lyrics.get_offset() == 0 { lyrics.set_offset(offset); };
+
+        self.lyrics_stamp = lyrics.get_lines().iter().position(|line| line.get_time().is_none())
+            .unwrap_or(0);
+
+        self.lyrics = Some(lyrics);
+        self.lyrics_line = None;
+    }
+
+    fn load_lyrics(&mut self) {
+        if self.lyrics_source == self.now_playing { return; }
+
+        self.lyrics = if self.now_playing.is_empty() { None }
+            else { Lyrics::load(&self.now_playing) };
+        self.lyrics_source = self.now_playing.clone();
+        self.lyrics_editing = false;
+        self.lyrics_line = None;
+        self.lyrics_message = String::new();
+    }
+
+    fn open_lyrics_editor(&mut self) {
+        self.lyrics_draft = self.lyrics.as_ref().map(|lyrics| lyrics.to_lrc()).unwrap_or_default();
+        self.lyrics_editing = true;
+        self.lyrics_message = String::new();
+
+        self.apply_lyrics();
+    }
+
+    fn save_lyrics(&mut self) {
+        if let Some(lyrics) = &self.lyrics {
+            self.lyrics_message = match lyrics.save(&self.lyrics_source) {
+                Ok(()) => "Saved lyrics.".to_string(),
+                Err(error) => error.to_string()
+            };
+
+            self.lyrics_draft = lyrics.to_lrc();
+        };
+    }
+
+    fn stamp_lyrics(&mut self, forward: bool) {
+        let elapsed = self.get_elapsed_time();
+
+        if let Some(lyrics) = &mut self.lyrics {
+            let count = lyrics.get_lines().len();
+
+            if forward && self.lyrics_stamp < count {
+                lyrics.set_time(self.lyrics_stamp, Some(elapsed));
+
+                self.lyrics_stamp += 1;
+            } else if !forward && self.lyrics_stamp > 0 {
+                self.lyrics_stamp -= 1;
+
+                lyrics.set_time(self.lyrics_stamp, None);
+            };
+
+            self.lyrics_draft = lyrics.to_lrc();
+        };
+    }
+
+    //** Organize **//
+    fn apply_organize(&mut self) {
+        match self.organize_mode {
+            OrganizeMode::Rename => {
+                let (moved, errors) = organize::apply_renames(&self.organize_moves);
+
+                for (from, to) in &moved {
+                    self.remove_source(from.clone());
+                    self.library.refresh(to);
+
+                    if self.now_playing == *from { self.now_playing = to.clone(); };
+                    if self.selected == *from { self.selected = to.clone(); };
+                };
+
+                self.insert_sources(moved.iter().map(|(_from, to)| to.clone()).collect());
+                self.rewrite_playlists(&moved.iter().cloned().collect());
+
+                self.organize_message = format!("Moved {} file(s).", moved.len());
+
+                if !errors.is_empty() {
+                    self.organize_message += &format!(" Failed: {}", errors.join("; "));
+                };
+
+                self.organize_moves.clear();
+            },
+
+            OrganizeMode::Tag => {
+                let (written, errors) = organize::apply_retags(&self.organize_retags);
+
+                for source in &written { self.library.refresh(source); };
+
+                self.organize_message = format!("Tagged {} file(s).", written.len());
+
+                if !errors.is_empty() {
+                    self.organize_message += &format!(" Failed: {}", errors.join("; "));
+                };
+
+                self.organize_retags.clear();
+            }
+        };
+    }
+
+    fn get_folder_sources(&self) -> Vec<String> {
+        self.pseudo_playlist.clone().and_then(|list| list.get_sources()).unwrap_or_default()
+            .into_iter().filter(|source| Path::new(source).starts_with(&self.path)).collect()
+    }
+
+    fn preview_organize(&mut self) {
+        let sources = self.get_folder_sources();
+
+        self.organize_message = String::new();
+
+        match self.organize_mode {
+            OrganizeMode::Rename => {
+                self.organize_moves = organize::plan_renames(&self.organize_pattern, &self.dir,
+                    &sources).into_iter().filter(|entry| entry.get_from() != entry.get_to())
+                    .collect();
+
+                let conflicts = self.organize_moves.iter()
+                    .filter(|entry| entry.get_conflict().is_some()).count();
+
+                if conflicts > 0 {
+                    self.organize_message = format!("{} conflict(s) will be skipped.", conflicts);
+                };
+            },
+
+            OrganizeMode::Tag => {
+                self.organize_retags = organize::plan_retags(&self.organize_pattern, &self.dir,
+                    &sources);
+            }
+        };
+    }
+
+    fn rewrite_playlists(&mut self, moved: &HashMap<String, String>) {
+        if moved.is_empty() { return; }
+
+        let mut changed = false;
+
+        for playlist in &mut self.playlists {
+            for (from, to) in moved {
+                if playlist.replace_source(from, to) { changed = true; };
+            };
+        };
+
+        let lists = [
+            &mut self.active_playlist,
+            &mut self.now_playinglist,
+            &mut self.sorted_playlist
+        ];
+
+        for list in lists {
+            if let Some(playlist) = list {
+                for (from, to) in moved { playlist.replace_source(from, to); };
+            };
+        };
+
+        if changed { let _ = filesys::edit_playlists(self.playlists.clone()); };
+
+        self.flag_missing();
+    }
+
     //** Playback **//
     fn pause(&mut self) {
+        self.save_episode();
+
         self.play_state = PlayState::Paused;
         self.start_playback = false;
         self.stop_playback = true;
@@ -130,6 +559,11 @@ impl Main {
                     self.stop_playback = false;
 
                     let _ = player.play(self.now_playing.clone());
+
+                    // Resuming picks up where the stopwatch left off instead of the start
+                    if !self.get_elapsed_time().is_zero() {
+                        player.try_seek(self.get_elapsed_time());
+                    };
                 },
 
                 PlayState::Playing => {
@@ -144,6 +578,44 @@ impl Main {
         };
     }
 
+    fn play_playlist(&mut self, playlist: Playlist) {
+        self.stop();
+
+        self.active_playlist = Some(playlist);
+        self.now_playinglist = self.active_playlist.clone();
+        self.sorted_playlist = self.active_playlist.clone();
+
+        if let Some(playlist) = &self.now_playinglist {
+            if let Some(sources) = playlist.get_sources() {
+                for source in &sources {
+                    if *source == self.selected { self.now_playing = source.to_string(); }
+                    else {
+                        if let Some(first) = playlist.get_source(0) {
+                            self.now_playing = first;
+                        };
+                    };
+                };
+            };
+        };
+
+        self.play();
+    }
+
+    fn play_url(&mut self, url: String) {
+        if !filesys::is_url(url.clone()) { return; }
+
+        self.stop();
+
+        // A one-off URL or station has no playlist to skip through
+        self.now_playinglist = None;
+        self.now_playing = url;
+        self.url_input = String::new();
+        self.url_name = String::new();
+        self.url_open = false;
+
+        self.play();
+    }
+
     fn repeat(&mut self) {
         match self.repeat_state {
             RepeatState::All => self.repeat_state = RepeatState::One,
@@ -152,6 +624,23 @@ impl Main {
         };
     }
     
+    fn seek(&mut self, position: Duration) {
+        self.elapsed_time = position;
+        self.start_time = position;
+
+        if let PlayState::Playing = self.play_state {
+            self.stopwatch_instant = Some(Instant::now());
+
+            if let Ok(mut player) = self.playback.try_lock() {
+                player.pause();
+
+                let _ = player.play(self.now_playing.clone());
+
+                player.try_seek(position);
+            };
+        } else { self.stopwatch_instant = None; };
+    }
+
     fn shuffle(&mut self) {
         match self.is_shuffled {
             false => {
@@ -218,7 +707,9 @@ impl Main {
         self.stop();
 
         if let Some(playlist) = &self.now_playinglist {
-            if let Some(last) = &playlist.get_source(playlist.len() - 1) {
+            let last = playlist.len().checked_sub(1).and_then(|index| playlist.get_source(index));
+
+            if let Some(last) = &last {
                 if last != &self.now_playing {
                     if let Some(sources) = &playlist.get_sources() {
                         let mut index = 0;
@@ -253,67 +744,172 @@ impl Main {
     }
     
     fn stop(&mut self) {
+        self.save_episode();
+
         self.play_state = PlayState::Stopped;
         self.start_playback = false;
         self.stop_playback = true;
 
         self.start_time = Duration::ZERO;
         self.elapsed_time = Duration::ZERO;
-        self.total_time = Duration::ZERO;
+        self.total_time = None;
 
         if let Ok(mut player) = self.playback.try_lock() { player.pause(); };
     }
     
     //** Playlist **//
-    fn load_sources(&mut self, dir: String) -> Result<()> {
-        let mut sources = Playlist::new(String::new());
+    fn add_source(&mut self, path: String) {
+        if Path::new(&path).is_dir() {
+            self.merge_scanners.push(Scanner::start(path));
 
-        let mut entries = filesys::read_dir(dir.clone())?.map(|res| res.map(|e| e.path()))
-            .collect::<Result<Vec<_>>>()?;
+            return;
+        };
 
-        entries.sort();
+        if !filesys::is_media(path.clone()) { return; }
 
-        for entry in entries {
-            let file_path = entry.as_path().display().to_string();
+        self.library.refresh(&path);
+        self.insert_sources(vec![path]);
+    }
 
-            if entry.as_path().is_file() { sources.add_source(Some(file_path.clone())); }
-            else {
-                let mut entries1 = filesys::read_dir(file_path.clone())?
-                    .map(|res| res.map(|e| e.path())).collect::<Result<Vec<_>>>()?;
+    fn cancel_scan(&mut self) {
+        // A cancelled scan stays cancelled until a folder is entered again
+        if self.scanner.take().is_some() { self.scan_cancelled = true; };
+    }
+
+    fn flag_missing(&mut self) {
+        self.missing.clear();
+
+        for playlist in &self.playlists {
+            if let Some(sources) = playlist.get_sources() {
+                for source in sources {
+                    if !filesys::is_url(source.clone()) && !filesys::exists(source.clone()) {
+                        self.missing.insert(source);
+                    };
+                };
+            };
+        };
+    }
 
-                entries1.sort();
+    fn insert_sources(&mut self, sources: Vec<String>) {
+        if let Some(mut pseudo) = self.pseudo_playlist.clone() {
+            for source in sources {
+                if self.pseudo_roots.iter().any(|root| Path::new(&source).starts_with(root)) {
+                    pseudo.add_source(Some(source));
+                };
+            };
 
-                for entry1 in entries1 {
-                    let file_path1 = entry1.as_path().display().to_string();
+            pseudo.sort_sources();
 
-                    if entry1.as_path().is_file() { sources.add_source(Some(file_path1.clone())); }
-                    else {
-                        let mut entries2 = filesys::read_dir(file_path1.clone())?
-                            .map(|res| res.map(|e| e.path())).collect::<Result<Vec<_>>>()?;
+            self.sync_pseudo(pseudo);
+        };
+    }
 
-                        entries2.sort();
+    fn load_sources(&mut self, dir: String) -> Result<()> {
+        let roots = if dir.is_empty() { self.get_enabled_roots() } else { vec![dir] };
 
-                        for entry2 in entries2 {
-                            let file_path2 = entry2.as_path().display().to_string();
+        self.scan_cancelled = false;
 
-                            if entry2.as_path().is_file() {
-                                sources.add_source(Some(file_path2.clone()));
-                            };
-                        };
-                    };
-                };
-            };
+        if let Some(scanner) = &self.scanner {
+            if scanner.get_roots() == roots && !scanner.is_finished() { return Ok(()); };
+
+            scanner.cancel();
         };
 
-        self.pseudo_playlist = Some(sources);
+        self.scanner = Some(Scanner::start_all(roots));
+
+        Ok(())
+    }
+
+    fn poll_scanner(&mut self) {
+        let (roots, sources) = match &self.scanner {
+            Some(scanner) => match scanner.take_sources() {
+                Some(sources) => (scanner.get_roots(), sources),
+                None => return
+            },
+
+            None => return
+        };
+
+        self.scanner = None;
+        self.pseudo_roots = roots;
+
+        let mut playlist = Playlist::new(String::new());
+
+        for source in &sources { playlist.add_source(Some(source.clone())); };
+
+        self.library.scan(sources);
+
+        self.pseudo_playlist = Some(playlist);
         self.sorted_playlist = self.pseudo_playlist.clone();
         self.now_playinglist = self.pseudo_playlist.clone();
 
         if let Some(list) = self.pseudo_playlist.clone() {
-            self.selected = list.get_source(0).unwrap();
+            if let Some(first) = list.get_source(0) { self.selected = first; };
         };
+    }
 
-        Ok(())
+    fn poll_watcher(&mut self) {
+        let mut merged: Vec<String> = Vec::new();
+
+        for scanner in &self.merge_scanners {
+            if let Some(sources) = scanner.take_sources() { merged.extend(sources); };
+        };
+
+        self.merge_scanners.retain(|scanner| !scanner.is_finished());
+
+        if !merged.is_empty() {
+            self.library.scan(merged.clone());
+            self.insert_sources(merged);
+        };
+
+        let changes = match &self.watcher {
+            Some(watcher) => watcher.poll(),
+            None => return
+        };
+
+        if changes.is_empty() { return; }
+
+        for change in changes {
+            match change {
+                Change::Created(path) => {
+                    self.artwork.refresh(&path);
+                    self.add_source(path);
+                },
+
+                Change::Modified(path) => {
+                    self.artwork.refresh(&path);
+                    self.library.refresh(&path);
+                },
+                Change::Playlists => self.playlists = filesys::get_playlists(),
+                Change::Removed(path) => self.remove_source(path),
+
+                Change::Renamed(from, to) => {
+                    self.remove_source(from);
+                    self.add_source(to);
+                }
+            };
+        };
+
+        self.flag_missing();
+    }
+
+    fn remove_source(&mut self, path: String) {
+        self.library.remove(&path);
+
+        if let Some(pseudo) = self.pseudo_playlist.clone() {
+            let mut list = Playlist::new(String::new());
+
+            list.set_id(pseudo.get_id().unwrap());
+
+            if let Some(sources) = pseudo.get_sources() {
+                for source in sources {
+                    if Path::new(&source).starts_with(&path) { self.library.remove(&source); }
+                    else { list.add_source(Some(source)); };
+                };
+            };
+
+            self.sync_pseudo(list);
+        };
     }
 
     fn save_playlists(&mut self, playlist: Option<Playlist>) {
@@ -329,815 +925,2774 @@ impl Main {
         self.playlists = playlists.clone();
 
         let _ = filesys::edit_playlists(self.playlists.clone());
-    }
 
-    //** Time **//
-    fn get_elapsed_time(&self) -> Duration {
-        match self.stopwatch_instant {
-            None => self.elapsed_time,
-            Some(instance) => instance.elapsed() + self.start_time
-        }
+        self.flag_missing();
     }
 
-    fn setup_stopwatch(&mut self) {
-        self.elapsed_time = self.get_elapsed_time();
+    fn sync_pseudo(&mut self, pseudo: Playlist) {
+        let id = pseudo.get_id();
 
-        if self.start_playback {
-            self.start_time = self.elapsed_time;
-            self.stopwatch_instant = Some(Instant::now());
+        if self.sorted_playlist.as_ref().and_then(|list| list.get_id()) == id {
+            self.sorted_playlist = Some(pseudo.clone());
         };
 
-        if self.stop_playback { self.stopwatch_instant = None; };
+        if self.now_playinglist.as_ref().and_then(|list| list.get_id()) == id {
+            self.now_playinglist = Some(pseudo.clone());
+        };
+
+        self.pseudo_playlist = Some(pseudo);
     }
 
-    fn update_playback(&mut self) {
-        if self.now_playing != String::new() {
-            self.total_time = data::get_total_time(&self.now_playing);
+    //** Podcasts **//
+    fn play_episode(&mut self, episode: Episode) {
+        // Stopping also keeps the position of the episode being switched away from
+        self.stop();
 
-            if self.get_elapsed_time() >= self.total_time && self.total_time != Duration::ZERO {
-                self.elapsed_time = Duration::ZERO;
+        self.now_playinglist = None;
+        self.now_playing = episode.get_source();
 
-                self.stop();
+        // Unfinished episodes pick up where they were left; play() seeks to the elapsed time
+        if !episode.is_played() {
+            self.elapsed_time = episode.get_position();
+            self.start_time = episode.get_position();
+        };
 
-                self.play_state = PlayState::Stopped;
+        self.play();
+    }
 
-                match self.repeat_state {
-                    RepeatState::All | RepeatState::None => self.skip_forward(),
-                    RepeatState::One => self.play()
-                };
-            };
-        } else { self.total_time = Duration::ZERO; };
+    fn save_episode(&mut self) {
+        if let PlayState::Stopped = self.play_state { return; }
+        if self.podcasts.get_episode(&self.now_playing).is_none() { return; }
+
+        self.podcast_saved = Instant::now();
+        self.podcasts.set_position(&self.now_playing.clone(), self.get_elapsed_time());
     }
 
-    //** UI - Buttons **//
-    fn big_player_button(&mut self, ui: &mut Ui) {
-        let icon = images::get_big_player();
-        let button = Button::new(icon);
-        let component = ui.add_sized([30.0, 30.0], button);
+    fn subscribe_podcast(&mut self) {
+        self.podcasts.subscribe(&self.feed_input);
 
-        if component.clicked() {
-            match self.big_player_open {
-                false => self.big_player_open = true,
-                true => self.big_player_open = false
-            };
-        };
+        self.feed_input = String::new();
+        self.feed_open = false;
+        self.list_mode = ListMode::Podcasts;
     }
-    
-    fn cancel_button(&mut self, ui: &mut Ui) {
-        let button = Button::new("Cancel");
-        let component = ui.add_sized([70.0, 30.0], button);
 
-        if component.clicked() {
-            self.temp_playlist_name = String::new();
-            self.playlist_add_open = false;
-            self.playlist_edit_open = false;
-        };
-    }
+    fn track_episode(&mut self) {
+        // Progress is written every few seconds rather than every frame
+        if self.podcast_saved.elapsed() < Duration::from_secs(5) { return; }
 
-    fn catalog_back_button(&mut self, ui: &mut Ui) {
-        let button = Button::new("Back");
-        let component = ui.add_sized([ui.available_width(), 30.0], button);
-        let dir = self.dir.clone();
+        if let PlayState::Playing = self.play_state { self.save_episode(); };
+    }
 
-        if component.clicked() {
-            self.selected = String::new();
+    //** Remote **//
+    fn can_queue(&self, source: &str) -> bool {
+        // Remote clients name files by path, so only existing media makes it into a list
+        filesys::is_url(source.to_string()) || (filesys::exists(source.to_string())
+            && filesys::is_media(source.to_string()))
+    }
 
-            if self.folders.len() == 0 { self.path = dir.clone(); }
-            else {
-                let mut folder = self.folders.pop();
+    fn edit_saved_playlist(&mut self, id: String, source: String, add: bool) {
+        let Some(mut playlist) = self.playlists.iter()
+            .find(|playlist| playlist.get_id() == Some(id.clone())).cloned() else { return };
 
-                if Some(self.path.clone()) == folder && self.folders.len() > 0 {
-                    folder = self.folders.last().cloned();
-                } else { folder = Some(dir.clone()); }
-                
-                self.path = folder.clone().unwrap();
-            };
-        };
-    }
+        if add {
+            if !self.can_queue(&source) { return; }
 
-    fn change_dir_button(&mut self, ui: &mut Ui) {
-        let button = Button::new("Change Directory");
-        let component = ui.add_sized([30.0, 30.0], button);
+            playlist.add_source(Some(source));
+        } else { playlist.remove_source(source); };
 
-        if component.clicked() {
-            self.selected = String::new();
-            
-            self.dir_dialog.pick_directory();
+        if self.active_playlist.as_ref().and_then(|list| list.get_id()) == Some(id) {
+            self.active_playlist = Some(playlist.clone());
         };
+
+        self.save_playlists(Some(playlist));
     }
 
-    fn dir_button(&mut self, ui: &mut Ui) {
-        let button = Button::new("Directory");
-        let component = ui.add_sized([ui.available_width(), 30.0], button);
-        let dir = self.dir.clone();
+    fn handle_remote(&mut self) {
+        let mut commands = self.remote.poll();
 
-        if component.clicked() && dir != "/" {
-            self.selected = String::new();
-            self.path = dir.clone();
+        commands.extend(self.mpd.poll());
 
-            self.folders.clear();
-        };
-    }
+        #[cfg(target_os = "linux")]
+        commands.extend(self.mpris.poll());
 
-    fn mini_player_button(&mut self, ui: &mut Ui) {
-        let icon = images::get_mini_player();
-        let button = Button::new(icon);
-        let component = ui.add_sized([30.0, 30.0], button);
+        for command in commands {
+            match command {
+                Command::AddToPlaylist(id, source) => self.edit_saved_playlist(id, source, true),
+                Command::ListPlaylists(reply) => { let _ = reply.send(self.list_playlists()); },
+                Command::ListQueue(reply) => { let _ = reply.send(self.list_queue()); },
+                Command::Next => self.skip_forward(),
 
-        if component.clicked() {
-            match self.mini_player_open {
-                false => self.mini_player_open = true,
-                true => self.mini_player_open = false
-            };
-        };
-    }
+                Command::Open(source) => {
+                    if !self.can_queue(&source) { continue; }
 
-    fn play_button(&mut self, ui: &mut Ui, big: bool) {
-        let icon = match self.play_state {
-            PlayState::Paused | PlayState::Stopped => images::get_play(),
-            PlayState::Playing => images::get_pause()
-        };
+                    self.queue_source(source.clone());
+                    self.stop();
 
-        let size = if big { 60.0 } else { 30.0 };
+                    self.selected = source.clone();
+                    self.now_playing = source;
 
-        let button = Button::new(icon).corner_radius(90);
-        let component = ui.add_sized([size, size], button);
+                    self.play();
+                },
 
-        if component.clicked() { self.play(); };
-    }
+                Command::Pause => {
+                    if let PlayState::Playing = self.play_state { self.pause(); };
+                },
 
-    fn playlist_add_button(&mut self, ui: &mut Ui) {
-        let icon = images::get_playlist_add();
-        let button = Button::new(icon);
-        let component = ui.add_sized([ui.available_width(), 30.0], button);
+                Command::Play => {
+                    if !matches!(self.play_state, PlayState::Playing) { self.play(); };
+                },
 
-        if component.clicked() {
-            self.selected = String::new();
-            self.playlist_add_open = true;
-            self.playlist_edit_open = false;
-        };
-    }
+                Command::PlayIndex(index) => {
+                    let source = self.now_playinglist.as_ref()
+                        .and_then(|playlist| playlist.get_source(index));
 
-    fn playlist_add_save_button(&mut self, ui: &mut Ui) {
-        let button = Button::new("Save");
-        let component = ui.add_sized([55.0, 30.0], button);
+                    if let Some(source) = source {
+                        self.stop();
 
-        if component.clicked() {
-            self.playlists.push(Playlist::new(self.temp_playlist_name.clone()));
+                        self.selected = source.clone();
+                        self.now_playing = source;
 
-            let _ = filesys::edit_playlists(self.playlists.clone());
+                        self.play();
+                    };
+                },
 
-            self.temp_playlist_name = String::new();
-            self.selected = String::new();
-            self.playlist_add_open = false;
-        };
-    }
+                Command::PlayPlaylist(id) => {
+                    let playlist = self.playlists.iter()
+                        .find(|playlist| playlist.get_id() == Some(id.clone())).cloned();
 
-    fn playlist_back_button(&mut self, ui: &mut Ui) {
-        let button = Button::new("Back");
-        let component = ui.add_sized([ui.available_width(), 30.0], button);
+                    if let Some(playlist) = playlist { self.play_playlist(playlist); };
+                },
 
-        if component.clicked() {
-            self.active_playlist = None;
-            self.selected = String::new();
-        };
-    }
+                Command::Previous => self.skip_backward(),
+                Command::Queue(source) => self.queue_source(source),
 
-    fn playlist_edit_button(&mut self, ui: &mut Ui, playlist: Playlist) {
-        let icon = images::get_playlist();
-        let button = Button::new(icon);
-        let component = ui.add_sized([30.0, 30.0], button);
+                Command::RemoveFromPlaylist(id, source) => {
+                    self.edit_saved_playlist(id, source, false);
+                },
 
-        if component.clicked() {
-            self.edited_playlist = Some(playlist.clone());
-            self.temp_playlist_name = playlist.get_name().unwrap();
-            self.selected = String::new();
-            self.playlist_add_open = false;
-            self.playlist_edit_open = true;
-        };
-    }
+                Command::Repeat => self.repeat(),
 
-    fn playlist_edit_save_button(&mut self, ui: &mut Ui) {
-        let button = Button::new("Save");
-        let component = ui.add_sized([55.0, 30.0], button);
+                Command::Seek(position) => {
+                    let position = match self.total_time {
+                        Some(total) => position.min(total),
+                        None => position
+                    };
 
-        if component.clicked() {
-            let mut playlist = Playlist::new(self.temp_playlist_name.clone());
+                    self.seek(position);
+                },
 
-            if let Some(list) = self.edited_playlist.clone() {
-                playlist.set_id(list.get_id().unwrap());
+                Command::Shuffle => {
+                    self.is_shuffled = !self.is_shuffled;
 
-                if let Some(sources) = list.get_sources() {
-                    for source in sources { playlist.add_source(Some(source)); };
-                };
-            };
+                    self.shuffle();
+                },
 
-            self.save_playlists(Some(playlist));
+                Command::Stop => self.stop(),
 
-            self.temp_playlist_name = String::new();
-            self.selected = String::new();
-            self.playlist_edit_open = false;
-        };
-    }
-    
-    fn playlist_play_button(&mut self, ui: &mut Ui, playlist: Playlist) {
-        let icon = images::get_play();
-        let button = Button::new(icon).corner_radius(90);
-        let component = ui.add_sized([30.0, 30.0], button);
+                Command::Unqueue(index) => {
+                    // Only the entry at that position goes, duplicates of it stay queued
+                    if let Some(playlist) = &mut self.now_playinglist {
+                        playlist.remove_index(index);
 
-        if component.clicked() {
-            self.stop();
-            
-            self.active_playlist = Some(playlist);
-            self.now_playinglist = self.active_playlist.clone();
-            self.sorted_playlist = self.active_playlist.clone();
+                        if playlist.is_empty() {
+                            self.now_playing = String::new();
+                            self.now_playinglist = None;
 
-            if let Some(playlist) = &self.now_playinglist {
-                if let Some(sources) = playlist.get_sources() {
-                    for source in &sources {
-                        if *source == self.selected { self.now_playing = source.to_string(); }
-                        else {
-                            if let Some(first) = playlist.get_source(0) {
-                                self.now_playing = first;
-                            };
+                            self.stop();
                         };
                     };
-                };
-            };
+                },
 
-            self.play();
+                Command::Volume(volume) => {
+                    self.volume = volume;
+
+                    let _ = filesys::edit_config(self.dir.clone(), volume);
+                }
+            };
         };
     }
 
-    fn playlist_remove_button(&mut self, ui: &mut Ui) {
-        let button = Button::new("Delete");
-        let component = ui.add_sized([55.0, 30.0], button);
+    fn list_playlists(&self) -> String {
+        let entries = self.playlists.iter().map(|playlist| {
+            format!("{{\"id\":{},\"name\":{},\"count\":{}}}",
+                web::json_string(&playlist.get_id().unwrap_or_default()),
+                web::json_string(&playlist.get_name().unwrap_or_default()), playlist.len())
+        }).collect::<Vec<_>>();
 
-        if component.clicked() {
-            let mut playlists: Vec<Playlist> = Vec::new();
+        format!("[{}]", entries.join(","))
+    }
 
-            for entry in &self.playlists.clone() {
-                if let Some(playlist) = self.edited_playlist.clone() {
-                    if entry.get_id() != playlist.get_id() { playlists.push(entry.clone()); };
-                };
-            };
+    fn list_queue(&self) -> String {
+        let sources = self.now_playinglist.as_ref().and_then(|playlist| playlist.get_sources())
+            .unwrap_or_default();
 
-            self.playlists = playlists.clone();
+        let index = sources.iter().position(|source| *source == self.now_playing)
+            .map(|index| index.to_string()).unwrap_or("null".to_string());
 
-            let _ = filesys::edit_playlists(self.playlists.clone());
+        let entries = sources.iter().map(|source| {
+            format!("{{\"source\":{},\"title\":{}}}", web::json_string(source),
+                web::json_string(&self.get_display_title(source)))
+        }).collect::<Vec<_>>();
 
-            self.temp_playlist_name = String::new();
-            self.selected = String::new();
-            self.playlist_edit_open = false;
-        };
+        format!("{{\"index\":{},\"sources\":[{}]}}", index, entries.join(","))
     }
 
-    fn repeat_button(&mut self, ui: &mut Ui, big: bool) {
-        let color = match self.repeat_state {
-            RepeatState::All | RepeatState::One => styles::get_button_fill(),
-            RepeatState::None => Color32::TRANSPARENT
+    fn publish_state(&mut self) {
+        let repeat = match self.repeat_state {
+            RepeatState::All => "all",
+            RepeatState::None => "none",
+            RepeatState::One => "one"
         };
 
-        let icon = match self.repeat_state {
-            RepeatState::All => images::get_repeat(),
-            RepeatState::None => images::get_repeat_off(),
-            RepeatState::One => images::get_repeat_one()
+        let state = PlayerState::new(self.get_now_playing(), repeat.to_string(), self.is_shuffled,
+            self.volume);
+
+        #[cfg(target_os = "linux")]
+        self.mpris.set_state(state.clone(), self.artwork.get_path(&self.now_playing));
+
+        if self.mpd.is_running() {
+            let queue = self.now_playinglist.as_ref().and_then(|playlist| playlist.get_sources())
+                .unwrap_or_default();
+
+            self.mpd.set_state(state.clone(), queue);
         };
 
-        let size = if big { 60.0 } else { 30.0 };
+        if self.remote.is_running() { self.remote.set_state(state); };
+    }
 
-        let button = Button::new(icon).corner_radius(90).fill(color);
-        let component = ui.add_sized([size, size], button);
+    fn queue_source(&mut self, source: String) {
+        if !self.can_queue(&source) { return; }
 
-        if component.clicked() { self.repeat(); };
+        let mut playlist = self.now_playinglist.clone()
+            .unwrap_or(Playlist::new("Queue".to_string()));
+
+        playlist.add_source(Some(source));
+
+        self.now_playinglist = Some(playlist);
     }
-    
-    fn shuffle_button(&mut self, ui: &mut Ui, big: bool) {
-        let color = match self.is_shuffled {
-            false => Color32::TRANSPARENT,
-            true => styles::get_button_fill()
-        };
 
-        let icon = match self.is_shuffled {
-            false => images::get_shuffle_off(),
-            true => images::get_shuffle()
+    fn save_mpd(&self) {
+        let _ = filesys::edit_mpd(self.mpd.is_running(), self.mpd.get_port(),
+            self.mpd.get_password());
+    }
+
+    fn save_remote(&self) {
+        let _ = filesys::edit_remote(self.remote.is_running(), self.remote.get_port(),
+            self.remote.get_token());
+    }
+
+    fn toggle_mpd(&mut self) {
+        if self.mpd.is_running() { self.mpd.stop(); }
+        else {
+            match self.mpd_port.trim().parse::<u16>() {
+                Ok(port) => self.mpd.set_port(port),
+                Err(_) => self.mpd_port = self.mpd.get_port().to_string()
+            };
+
+            self.mpd.set_password(self.mpd_password.clone());
+            self.mpd.start();
         };
 
-        let size = if big { 60.0 } else { 30.0 };
+        self.save_mpd();
+    }
 
-        let button = Button::new(icon).corner_radius(90).fill(color);
-        let component = ui.add_sized([size, size], button);
+    fn toggle_remote(&mut self) {
+        if self.remote.is_running() { self.remote.stop(); }
+        else {
+            match self.remote_port.trim().parse::<u16>() {
+                Ok(port) => self.remote.set_port(port),
+                Err(_) => self.remote_port = self.remote.get_port().to_string()
+            };
 
-        if component.clicked() {
-            self.is_shuffled = !self.is_shuffled;
-            
-            self.shuffle();
+            self.remote.start();
         };
+
+        self.save_remote();
     }
-    
-    fn skip_backward_button(&mut self, ui: &mut Ui, big: bool) {
-        let icon = images::get_skip_backward();
 
-        let size = if big { 60.0 } else { 30.0 };
+    //** Roots **//
+    fn add_root(&mut self, path: String) {
+        if self.roots.iter().any(|root| root.get_path() == path) { return; }
 
-        let button = Button::new(icon).corner_radius(90);
-        let component = ui.add_sized([size, size], button);
+        self.roots.push(Root::new(path.clone()));
 
-        if component.clicked() { self.skip_backward(); };
+        if filesys::exists(path.clone()) { self.mounted.insert(path); };
+
+        // A probe started before this root existed would report it as unmounted
+        self.mount_probe = None;
+        self.save_roots();
     }
-    
-    fn skip_forward_button(&mut self, ui: &mut Ui, big: bool) {
-        let icon = images::get_skip_forward();
 
-        let size = if big { 60.0 } else { 30.0 };
+    fn get_enabled_roots(&self) -> Vec<String> {
+        self.roots.iter().filter(|root| root.is_enabled()).map(|root| root.get_path()).collect()
+    }
 
-        let button = Button::new(icon).corner_radius(90);
-        let component = ui.add_sized([size, size], button);
+    fn open_root(&mut self, path: String) {
+        self.selected = String::new();
+        self.cancel_scan();
+        self.dir = path.clone();
+        self.path = path.clone();
 
-        if component.clicked() { self.skip_forward(); };
+        self.folders.clear();
+
+        let _ = self.load_sources(path);
     }
 
-    fn source_add_button(&mut self, ui: &mut Ui) {
-        let icon = images::get_add();
-        let button = Button::new(icon);
-        let component = ui.add_sized([ui.available_width(), 30.0], button);
+    fn open_roots(&mut self) {
+        self.selected = String::new();
+        self.cancel_scan();
+        self.dir = String::new();
+        self.path = String::new();
 
-        if component.clicked() {
-            self.selected = String::new();
-            
-            self.playlist_dialog.pick_file();
-        };
+        self.folders.clear();
     }
-    
-    fn source_play_button(&mut self, ui: &mut Ui, source: String) {
-        let icon = images::get_play();
-        let button = Button::new(icon).corner_radius(90);
-        let component = ui.add_sized([30.0, 30.0], button);
+
+    fn poll_roots(&mut self) {
+        // Mounts are probed off the UI thread so a hung share can't freeze the window
+        let probed = match &self.mount_probe {
+            Some(probe) => match probe.try_recv() {
+                Ok(probed) => probed,
+                Err(TryRecvError::Empty) => return,
+
+                Err(TryRecvError::Disconnected) => {
+                    self.mount_probe = None;
+
+                    return;
+                }
+            },
+
+            None => {
+                if self.roots_checked.elapsed() >= Duration::from_secs(5) {
+                    self.mount_probe = Some(source::probe_mounts(self.roots.clone()));
+                };
+
+                return;
+            }
+        };
+
+        self.mount_probe = None;
+        self.roots_checked = Instant::now();
+
+        // The first answer only fills in what startup skipped, nothing was shown as missing yet
+        if !self.mounts_probed {
+            self.mounted = probed;
+            self.mounts_probed = true;
+
+            return;
+        };
+
+        let mut changed = false;
+
+        for root in self.roots.clone() {
+            let path = root.get_path();
+            let mounted = probed.contains(&path);
+
+            if mounted == self.mounted.contains(&path) { continue; };
+
+            changed = true;
+
+            if mounted {
+                self.mounted.insert(path.clone());
+
+                if root.is_enabled() && self.pseudo_roots.contains(&path) {
+                    self.add_source(path);
+                };
+            } else {
+                self.mounted.remove(&path);
+                self.remove_source(path.clone());
+
+                if Path::new(&self.path).starts_with(&path) { self.open_roots(); };
+            };
+        };
+
+        if changed {
+            self.reset_watcher();
+            self.flag_missing();
+        };
+    }
+
+    fn remove_root(&mut self, index: usize) {
+        if index >= self.roots.len() { return; }
+
+        let path = self.roots.remove(index).get_path();
+
+        self.mounted.remove(&path);
+        self.remove_source(path.clone());
+
+        if self.dir == path { self.open_roots(); };
+
+        self.save_roots();
+    }
+
+    fn reset_watcher(&mut self) {
+        self.watcher = Watcher::new(self.get_enabled_roots(), filesys::get_playlists_path());
+    }
+
+    fn save_roots(&mut self) {
+        let _ = filesys::edit_roots(self.roots.clone());
+
+        self.reset_watcher();
+
+        if self.dir.is_empty() { let _ = self.load_sources(String::new()); };
+    }
+
+    fn toggle_root(&mut self, index: usize, enabled: bool) {
+        if let Some(root) = self.roots.get_mut(index) {
+            root.set_enabled(enabled);
+
+            let path = root.get_path();
+
+            if !enabled {
+                self.remove_source(path.clone());
+
+                if self.dir == path { self.open_roots(); };
+            };
+
+            self.save_roots();
+        };
+    }
+
+    //** Server **//
+    fn publish_now_playing(&mut self) {
+        if !self.server.is_running() { return; }
+
+        let now_playing = self.get_now_playing();
+
+        self.server.set_now_playing(now_playing);
+    }
+
+    fn save_server(&self) {
+        let _ = filesys::edit_server(self.server.get_port(),
+            self.server.get_format().get_label().to_string(), self.server.is_running());
+    }
+
+    fn toggle_server(&mut self) {
+        if self.server.is_running() { self.server.stop(); }
+        else {
+            match self.server_port.trim().parse::<u16>() {
+                Ok(port) => self.server.set_port(port),
+                Err(_) => self.server_port = self.server.get_port().to_string()
+            };
+
+            self.server.start();
+        };
+
+        self.save_server();
+    }
+
+    //** Stations **//
+    fn get_station_name(&self, source: &str) -> Option<String> {
+        if let Some(station) = self.stations.iter().find(|station| station.get_url() == source) {
+            return Some(station.get_name());
+        };
+
+        // Unsaved streams fall back to the name the server announces
+        if source != self.now_playing { return None; }
+
+        self.playback.try_lock().ok().and_then(|player| player.get_metadata().get_name())
+    }
+
+    fn remove_station(&mut self, index: usize) {
+        if index < self.stations.len() { self.stations.remove(index); };
+
+        let _ = filesys::edit_stations(self.stations.clone());
+    }
+
+    fn save_station(&mut self) {
+        let url = self.url_input.trim().to_string();
+
+        if !filesys::is_url(url.clone()) { return; }
+
+        let name = match self.url_name.trim() {
+            "" => self.get_station_name(&url).unwrap_or(url.clone()),
+            name => name.to_string()
+        };
+
+        self.stations.retain(|station| station.get_url() != url);
+        self.stations.push(Station::new(name, url));
+
+        let _ = filesys::edit_stations(self.stations.clone());
+
+        self.url_input = String::new();
+        self.url_name = String::new();
+        self.url_open = false;
+        self.list_mode = ListMode::Stations;
+    }
+
+    //** Tags **//
+    fn apply_batch(&mut self) {
+        let result = match &mut self.batch {
+            Some(batch) => batch.apply(),
+            None => return
+        };
+
+        match result {
+            Ok((step, errors)) => {
+                for (source, _tags) in &step { self.library.refresh(source); };
+
+                self.batch_message = format!("Updated {} file(s).", step.len());
+
+                if !errors.is_empty() {
+                    self.batch_message += &format!(" {} failed: {}", errors.len(),
+                        errors.join("; "));
+                };
+
+                if !step.is_empty() { self.batch_undo.push(step); };
+            },
+
+            Err(error) => self.batch_message = error.to_string()
+        };
+    }
+
+    fn open_batch_editor(&mut self, name: String, sources: Vec<String>) {
+        self.playlist_edit_open = false;
+        self.tag_editor_open = false;
+        self.organizer_open = false;
+        self.batch_editor_open = true;
+        self.batch_message = String::new();
+        self.batch = Some(Batch::new(name, sources));
+    }
+
+    fn undo_batch(&mut self) {
+        if let Some(step) = self.batch_undo.pop() {
+            let sources = step.iter().map(|(source, _tags)| source.clone()).collect::<Vec<_>>();
+            let errors = batch::undo(step);
+
+            for source in &sources { self.library.refresh(source); };
+
+            self.batch_message = format!("Reverted {} file(s).", sources.len() - errors.len());
+
+            if !errors.is_empty() {
+                self.batch_message += &format!(" Failed: {}", errors.join("; "));
+            };
+
+            if let Some(batch) = &mut self.batch { batch.reload(); };
+        };
+    }
+
+    fn open_tag_editor(&mut self, source: String) {
+        self.playlist_edit_open = false;
+        self.batch_editor_open = false;
+        self.organizer_open = false;
+        self.tag_editor_open = true;
+        self.tag_source = source.clone();
+
+        match data::read_tags(&source) {
+            Ok(tags) => {
+                self.tag_edit = tags.clone();
+                self.tag_original = tags;
+                self.tag_message = String::new();
+            },
+
+            Err(error) => {
+                self.tag_edit = Tags::new();
+                self.tag_original = Tags::new();
+                self.tag_message = error.to_string();
+            }
+        };
+    }
+
+    fn save_tags(&mut self) {
+        match data::write_tags(&self.tag_source, &self.tag_edit) {
+            Ok(()) => {
+                self.tag_original = self.tag_edit.clone();
+                self.tag_message = "Saved.".to_string();
+
+                self.library.refresh(&self.tag_source);
+            },
+
+            Err(error) => self.tag_message = error.to_string()
+        };
+    }
+
+    //** Time **//
+    fn get_clock(&self) -> Duration {
+        // The sink position is the audio clock; while paused the stopwatch holds it still
+        match self.play_state {
+            PlayState::Playing => self.playback.try_lock().map(|player| player.get_position())
+                .unwrap_or(self.get_elapsed_time()),
+            _ => self.get_elapsed_time()
+        }
+    }
+
+    fn get_elapsed_time(&self) -> Duration {
+        match self.stopwatch_instant {
+            None => self.elapsed_time,
+            Some(instance) => instance.elapsed() + self.start_time
+        }
+    }
+
+    fn setup_stopwatch(&mut self) {
+        self.elapsed_time = self.get_elapsed_time();
+
+        if self.start_playback {
+            self.start_time = self.elapsed_time;
+            self.stopwatch_instant = Some(Instant::now());
+        };
+
+        if self.stop_playback { self.stopwatch_instant = None; };
+    }
+
+    fn sync_stream(&mut self) {
+        if !matches!(self.play_state, PlayState::Playing) { return; }
+
+        let (status, position) = match self.playback.try_lock() {
+            Ok(player) => (player.get_status(), player.get_position()),
+            Err(_) => return
+        };
+
+        // Network stalls would otherwise run the stopwatch ahead of the audio
+        match status {
+            StreamStatus::Streaming => self.start_time = position,
+            StreamStatus::Idle => return,
+            _ => {}
+        };
+
+        self.stopwatch_instant = Some(Instant::now());
+    }
+
+    fn update_playback(&mut self) {
+        if self.now_playing != String::new() {
+            let is_url = filesys::is_url(self.now_playing.clone());
+
+            self.total_time = match is_url {
+                true => self.playback.try_lock().ok().and_then(|player| player.get_duration())
+                    .or(self.podcasts.get_episode(&self.now_playing)
+                        .and_then(|episode| episode.get_duration())),
+                false => self.library.get_total_time(&self.now_playing)
+            };
+
+            if is_url { self.sync_stream(); };
+
+            self.track_episode();
+
+            let ended = match self.total_time {
+                Some(total_time) if !is_url => self.get_elapsed_time() >= total_time,
+
+                // Without a known length the end is when the sink runs out of samples
+                _ => matches!(self.play_state, PlayState::Playing) && self.playback.try_lock()
+                    .map(|player| player.is_finished()).unwrap_or(false)
+            };
+
+            if ended {
+                if self.podcasts.get_episode(&self.now_playing).is_some() {
+                    self.podcasts.set_played(&self.now_playing.clone(), true);
+                };
+
+                self.elapsed_time = Duration::ZERO;
+
+                self.stop();
+
+                self.play_state = PlayState::Stopped;
+
+                match self.repeat_state {
+                    RepeatState::All | RepeatState::None => self.skip_forward(),
+                    RepeatState::One => self.play()
+                };
+            };
+        } else { self.total_time = None; };
+    }
+
+    //** Video **//
+    fn sync_video(&mut self, ctx: &Context) -> Option<TextureHandle> {
+        if self.video_source != self.now_playing {
+            self.video_source = self.now_playing.clone();
+            self.video_texture = None;
+
+            // Opening a remote container would block the UI on the network
+            self.video = if filesys::is_video(self.now_playing.clone())
+                && !filesys::is_url(self.now_playing.clone()) {
+                Video::open(&self.now_playing)
+            } else { None };
+
+            self.subtitles = self.video.as_ref().map(|_video| Subtitles::load(&self.now_playing));
+        };
+
+        if let Some(subtitles) = &mut self.subtitles { subtitles.poll(); };
+
+        let clock = self.get_clock();
+
+        if let Some(image) = self.video.as_mut()?.get_frame(clock) {
+            match &mut self.video_texture {
+                Some(texture) => texture.set(image, TextureOptions::LINEAR),
+                None => self.video_texture = Some(ctx.load_texture("video", image,
+                    TextureOptions::LINEAR))
+            };
+        };
+
+        self.video_texture.clone()
+    }
+
+    //** UI - Buttons **//
+    fn batch_apply_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Apply");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() { self.apply_batch(); };
+    }
+
+    fn batch_button(&mut self, ui: &mut Ui) {
+        ui.menu_button("Batch", |ui| {
+            let folder = self.pseudo_playlist.clone().and_then(|list| list.get_sources())
+                .unwrap_or_default().into_iter()
+                .filter(|source| Path::new(source).starts_with(&self.path))
+                .collect::<Vec<_>>();
+
+            let album = self.library.get_album(&self.selected);
+
+            let album_sources = self.now_playinglist.clone().or(self.pseudo_playlist.clone())
+                .and_then(|list| list.get_sources()).unwrap_or_default().into_iter()
+                .filter(|source| album != String::new() && self.library.get_album(source) == album)
+                .collect::<Vec<_>>();
+
+            let playlist = self.active_playlist.clone();
+
+            if ui.add_enabled(!folder.is_empty(), Button::new("Folder")).clicked() {
+                let name = filesys::create_from_path(self.path.clone());
+
+                self.open_batch_editor(name, folder);
+                ui.close_menu();
+            };
+
+            if ui.add_enabled(!album_sources.is_empty(), Button::new("Album")).clicked() {
+                self.open_batch_editor(album.clone(), album_sources);
+                ui.close_menu();
+            };
+
+            if ui.add_enabled(playlist.is_some(), Button::new("Playlist")).clicked() {
+                if let Some(list) = playlist {
+                    self.open_batch_editor(list.get_name().unwrap(),
+                        list.get_sources().unwrap_or_default());
+                };
+
+                ui.close_menu();
+            };
+        });
+    }
+
+    fn batch_close_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Close");
+        let component = ui.add_sized([55.0, 30.0], button);
 
         if component.clicked() {
-            self.stop();
-            
-            self.selected = source;
-            self.now_playing = self.selected.clone();
-            self.now_playinglist = self.active_playlist.clone();
-            self.sorted_playlist = self.active_playlist.clone();
-            
-            self.play();
+            self.batch_editor_open = false;
+            self.batch = None;
+            self.batch_message = String::new();
         };
     }
 
-    fn source_remove_button(&mut self, ui: &mut Ui, source: String) {
-        let button = Button::new("-");
+    fn batch_undo_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Undo");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() { self.undo_batch(); };
+    }
+
+    fn big_player_button(&mut self, ui: &mut Ui) {
+        let icon = images::get_big_player();
+        let button = Button::new(icon);
         let component = ui.add_sized([30.0, 30.0], button);
 
-        if component.clicked() {
-            self.selected = String::new();
-            
-            if let Some(playlist) = self.active_playlist.clone() {
-                let mut list = Playlist::new(playlist.get_name().unwrap());
-                
-                list.set_id(playlist.get_id().unwrap());
+        if component.clicked() {
+            match self.big_player_open {
+                false => self.big_player_open = true,
+                true => self.big_player_open = false
+            };
+        };
+    }
+    
+    fn broadcast_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Broadcast").selected(self.broadcast.is_running());
+        let component = ui.add(button);
+
+        if component.clicked() {
+            self.feed_open = false;
+            self.mpd_open = false;
+            self.playlist_add_open = false;
+            self.remote_open = false;
+            self.server_open = false;
+            self.url_open = false;
+            self.broadcast_open = !self.broadcast_open;
+        };
+    }
+
+    fn broadcast_start_button(&mut self, ui: &mut Ui) {
+        let running = self.broadcast.is_running();
+        let enabled = running || !self.broadcast_host.trim().is_empty();
+        let button = Button::new(if running { "Stop" } else { "Go Live" });
+        let component = ui.add_enabled_ui(enabled, |ui| ui.add_sized([70.0, 30.0], button)).inner;
+
+        if component.clicked() { self.toggle_broadcast(); };
+    }
+
+    fn cancel_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Cancel");
+        let component = ui.add_sized([70.0, 30.0], button);
+
+        if component.clicked() {
+            self.temp_playlist_name = String::new();
+            self.feed_input = String::new();
+            self.url_input = String::new();
+            self.url_name = String::new();
+            self.broadcast_open = false;
+            self.feed_open = false;
+            self.mpd_open = false;
+            self.playlist_add_open = false;
+            self.playlist_edit_open = false;
+            self.remote_open = false;
+            self.server_open = false;
+            self.url_open = false;
+        };
+    }
+
+    fn catalog_back_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Back");
+        let component = ui.add_sized([ui.available_width(), 30.0], button);
+        let dir = self.dir.clone();
+
+        if component.clicked() {
+            self.selected = String::new();
+            self.cancel_scan();
+
+            if self.folders.len() == 0 { self.open_roots(); }
+            else {
+                let mut folder = self.folders.pop();
+
+                if Some(self.path.clone()) == folder && self.folders.len() > 0 {
+                    folder = self.folders.last().cloned();
+                } else { folder = Some(dir.clone()); }
+                
+                self.path = folder.clone().unwrap();
+            };
+        };
+    }
+
+    fn change_dir_button(&mut self, ui: &mut Ui) {
+        let icon = images::get_add();
+        let button = Button::new(icon);
+        let component = ui.add_sized([30.0, 30.0], button).on_hover_text("Add a root folder");
+
+        if component.clicked() {
+            self.selected = String::new();
+            
+            self.dir_dialog.pick_directory();
+        };
+    }
+
+    fn dir_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Directory");
+        let component = ui.add_sized([ui.available_width(), 30.0], button);
+        let dir = self.dir.clone();
+
+        if component.clicked() && !dir.is_empty() {
+            self.selected = String::new();
+            self.cancel_scan();
+            self.path = dir.clone();
+
+            self.folders.clear();
+        };
+    }
+
+    fn episode_download_button(&mut self, ui: &mut Ui, episode: Episode) {
+        let url = episode.get_url();
+        let downloading = self.podcasts.is_downloading(&url);
+        let downloaded = episode.get_path().is_some();
+
+        let (label, hint) = match (downloading, downloaded) {
+            (true, _) => ("…", "Downloading"),
+            (false, true) => ("✕", "Delete download"),
+            (false, false) => ("⬇", "Download")
+        };
+
+        let button = Button::new(label);
+        let component = ui.add_enabled_ui(!downloading, |ui| ui.add_sized([30.0, 30.0], button))
+            .inner.on_hover_text(hint);
+
+        if component.clicked() {
+            if downloaded { self.podcasts.remove_download(&url); }
+            else { self.podcasts.download(&url); };
+        };
+    }
+
+    fn episode_play_button(&mut self, ui: &mut Ui, episode: Episode) {
+        let icon = images::get_play();
+        let button = Button::new(icon).corner_radius(90);
+        let component = ui.add_sized([30.0, 30.0], button);
+
+        if component.clicked() { self.play_episode(episode); };
+    }
+
+    fn episode_played_button(&mut self, ui: &mut Ui, episode: Episode) {
+        let played = episode.is_played();
+        let hint = if played { "Mark as unplayed" } else { "Mark as played" };
+        let button = Button::new("✔").selected(played);
+        let component = ui.add_sized([30.0, 30.0], button).on_hover_text(hint);
+
+        if component.clicked() { self.podcasts.set_played(&episode.get_url(), !played); };
+    }
+
+    fn list_mode_button(&mut self, ui: &mut Ui) {
+        let label = match self.list_mode {
+            ListMode::Playlists => "Playlists",
+            ListMode::Podcasts => "Podcasts",
+            ListMode::Stations => "Stations"
+        };
+
+        let button = Button::new(label).frame(false).fill(Color32::TRANSPARENT);
+        let component = ui.add_sized([ui.available_width(), 30.0], button)
+            .on_hover_text("Switch between playlists, radio stations and podcasts");
+
+        if component.clicked() {
+            self.list_mode = match self.list_mode {
+                ListMode::Playlists => ListMode::Stations,
+                ListMode::Stations => ListMode::Podcasts,
+                ListMode::Podcasts => ListMode::Playlists
+            };
+        };
+    }
+
+    fn lyrics_apply_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Apply");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() { self.apply_lyrics(); };
+    }
+
+    fn lyrics_back_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Back");
+        let component = ui.add_enabled(self.lyrics_stamp > 0, button);
+
+        if component.clicked() { self.stamp_lyrics(false); };
+    }
+
+    fn lyrics_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Lyrics");
+        let component = ui.add_enabled(self.now_playing != String::new(), button);
+
+        if component.clicked() {
+            self.playlist_edit_open = false;
+            self.batch_editor_open = false;
+            self.organizer_open = false;
+            self.tag_editor_open = false;
+            self.lyrics_open = !self.lyrics_open;
+        };
+    }
+
+    fn lyrics_close_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Close");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() {
+            self.lyrics_open = false;
+            self.lyrics_editing = false;
+            self.lyrics_message = String::new();
+        };
+    }
+
+    fn lyrics_edit_button(&mut self, ui: &mut Ui) {
+        let label = if self.lyrics_editing { "Done" } else { "Edit" };
+        let component = ui.add_sized([55.0, 30.0], Button::new(label));
+
+        if component.clicked() {
+            match self.lyrics_editing {
+                false => self.open_lyrics_editor(),
+                true => self.lyrics_editing = false
+            };
+        };
+    }
+
+    fn lyrics_offset_button(&mut self, ui: &mut Ui, delta: i64) {
+        let label = if delta < 0 { "-" } else { "+" };
+        let component = ui.add_sized([30.0, 30.0], Button::new(label));
+
+        if component.clicked() {
+            if let Some(lyrics) = &mut self.lyrics {
+                lyrics.set_offset(lyrics.get_offset() + delta);
+
+                self.lyrics_draft = lyrics.to_lrc();
+            };
+        };
+    }
+
+    fn lyrics_save_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Save");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() { self.save_lyrics(); };
+    }
+
+    fn lyrics_stamp_button(&mut self, ui: &mut Ui) {
+        let count = self.lyrics.as_ref().map(|lyrics| lyrics.get_lines().len()).unwrap_or(0);
+        let enabled = self.lyrics_stamp < count && self.now_playing == self.lyrics_source;
+        let component = ui.add_enabled(enabled, Button::new("Stamp"));
+
+        if component.clicked() { self.stamp_lyrics(true); };
+    }
+
+    fn mini_player_button(&mut self, ui: &mut Ui) {
+        let icon = images::get_mini_player();
+        let button = Button::new(icon);
+        let component = ui.add_sized([30.0, 30.0], button);
+
+        if component.clicked() {
+            match self.mini_player_open {
+                false => self.mini_player_open = true,
+                true => self.mini_player_open = false
+            };
+        };
+    }
+
+    fn mpd_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("MPD").selected(self.mpd.is_running());
+        let component = ui.add(button);
+
+        if component.clicked() {
+            self.broadcast_open = false;
+            self.feed_open = false;
+            self.playlist_add_open = false;
+            self.remote_open = false;
+            self.server_open = false;
+            self.url_open = false;
+            self.mpd_open = !self.mpd_open;
+        };
+    }
+
+    fn mpd_start_button(&mut self, ui: &mut Ui) {
+        let button = Button::new(if self.mpd.is_running() { "Stop" } else { "Start" });
+        let component = ui.add_sized([70.0, 30.0], button);
+
+        if component.clicked() { self.toggle_mpd(); };
+    }
+
+    fn organize_apply_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Apply");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() { self.apply_organize(); };
+    }
+
+    fn organize_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Organize");
+        let component = ui.add_enabled(!self.dir.is_empty(), button);
+
+        if component.clicked() {
+            self.playlist_edit_open = false;
+            self.batch_editor_open = false;
+            self.tag_editor_open = false;
+            self.organizer_open = true;
+            self.organize_message = String::new();
+
+            self.organize_moves.clear();
+            self.organize_retags.clear();
+        };
+    }
+
+    fn organize_close_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Close");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() {
+            self.organizer_open = false;
+            self.organize_message = String::new();
+
+            self.organize_moves.clear();
+            self.organize_retags.clear();
+        };
+    }
+
+    fn organize_preview_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Preview");
+        let component = ui.add_sized([70.0, 30.0], button);
+
+        if component.clicked() { self.preview_organize(); };
+    }
+
+    fn play_button(&mut self, ui: &mut Ui, big: bool) {
+        let icon = match self.play_state {
+            PlayState::Paused | PlayState::Stopped => images::get_play(),
+            PlayState::Playing => images::get_pause()
+        };
+
+        let size = if big { 60.0 } else { 30.0 };
+
+        let button = Button::new(icon).corner_radius(90);
+        let component = ui.add_sized([size, size], button);
+
+        if component.clicked() { self.play(); };
+    }
+
+    fn playlist_add_button(&mut self, ui: &mut Ui) {
+        let icon = images::get_playlist_add();
+        let button = Button::new(icon);
+        let component = ui.add_sized([ui.available_width(), 30.0], button);
+
+        if component.clicked() {
+            self.selected = String::new();
+            self.broadcast_open = false;
+            self.mpd_open = false;
+            self.remote_open = false;
+            self.server_open = false;
+            self.playlist_add_open = true;
+            self.playlist_edit_open = false;
+        };
+    }
+
+    fn playlist_add_save_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Save");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() {
+            self.playlists.push(Playlist::new(self.temp_playlist_name.clone()));
+
+            let _ = filesys::edit_playlists(self.playlists.clone());
+
+            self.temp_playlist_name = String::new();
+            self.selected = String::new();
+            self.playlist_add_open = false;
+        };
+    }
+
+    fn playlist_back_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Back");
+        let component = ui.add_sized([ui.available_width(), 30.0], button);
+
+        if component.clicked() {
+            self.active_playlist = None;
+            self.active_podcast = None;
+            self.selected = String::new();
+        };
+    }
+
+    fn playlist_edit_button(&mut self, ui: &mut Ui, playlist: Playlist) {
+        let icon = images::get_playlist();
+        let button = Button::new(icon);
+        let component = ui.add_sized([30.0, 30.0], button);
+
+        if component.clicked() {
+            self.edited_playlist = Some(playlist.clone());
+            self.temp_playlist_name = playlist.get_name().unwrap();
+            self.selected = String::new();
+            self.playlist_add_open = false;
+            self.playlist_edit_open = true;
+        };
+    }
+
+    fn playlist_edit_save_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Save");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() {
+            let mut playlist = Playlist::new(self.temp_playlist_name.clone());
+
+            if let Some(list) = self.edited_playlist.clone() {
+                playlist.set_id(list.get_id().unwrap());
+
+                if let Some(sources) = list.get_sources() {
+                    for source in sources { playlist.add_source(Some(source)); };
+                };
+            };
+
+            self.save_playlists(Some(playlist));
+
+            self.temp_playlist_name = String::new();
+            self.selected = String::new();
+            self.playlist_edit_open = false;
+        };
+    }
+    
+    fn playlist_play_button(&mut self, ui: &mut Ui, playlist: Playlist) {
+        let icon = images::get_play();
+        let button = Button::new(icon).corner_radius(90);
+        let component = ui.add_sized([30.0, 30.0], button);
+
+        if component.clicked() { self.play_playlist(playlist); };
+    }
+
+    fn playlist_remove_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Delete");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() {
+            let mut playlists: Vec<Playlist> = Vec::new();
+
+            for entry in &self.playlists.clone() {
+                if let Some(playlist) = self.edited_playlist.clone() {
+                    if entry.get_id() != playlist.get_id() { playlists.push(entry.clone()); };
+                };
+            };
+
+            self.playlists = playlists.clone();
+
+            let _ = filesys::edit_playlists(self.playlists.clone());
+
+            self.temp_playlist_name = String::new();
+            self.selected = String::new();
+            self.playlist_edit_open = false;
+        };
+    }
+
+    fn podcast_add_button(&mut self, ui: &mut Ui) {
+        let icon = images::get_playlist_add();
+        let button = Button::new(icon);
+        let component = ui.add_sized([ui.available_width(), 30.0], button);
+
+        if component.clicked() {
+            self.broadcast_open = false;
+            self.mpd_open = false;
+            self.playlist_add_open = false;
+            self.remote_open = false;
+            self.server_open = false;
+            self.url_open = false;
+            self.feed_open = true;
+        };
+    }
+
+    fn podcast_refresh_button(&mut self, ui: &mut Ui, url: String) {
+        let refreshing = self.podcasts.is_refreshing(&url);
+        let button = Button::new(if refreshing { "Refreshing" } else { "Refresh" });
+        let component = ui.add_enabled_ui(!refreshing,
+            |ui| ui.add_sized([ui.available_width(), 30.0], button)).inner;
+
+        if component.clicked() { self.podcasts.refresh(&url); };
+    }
+
+    fn podcast_remove_button(&mut self, ui: &mut Ui, url: String) {
+        let button = Button::new("-");
+        let component = ui.add_sized([30.0, 30.0], button).on_hover_text("Unsubscribe");
+
+        if component.clicked() { self.podcasts.unsubscribe(&url); };
+    }
+
+    fn podcast_subscribe_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Subscribe");
+        let enabled = filesys::is_url(self.feed_input.trim().to_string());
+        let component = ui.add_enabled_ui(enabled, |ui| ui.add_sized([80.0, 30.0], button)).inner;
+
+        if component.clicked() { self.subscribe_podcast(); };
+    }
+
+    fn remote_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Remote").selected(self.remote.is_running());
+        let component = ui.add(button);
+
+        if component.clicked() {
+            self.broadcast_open = false;
+            self.feed_open = false;
+            self.mpd_open = false;
+            self.playlist_add_open = false;
+            self.server_open = false;
+            self.url_open = false;
+            self.remote_open = !self.remote_open;
+        };
+    }
+
+    fn remote_start_button(&mut self, ui: &mut Ui) {
+        let button = Button::new(if self.remote.is_running() { "Disable" } else { "Enable" });
+        let component = ui.add_sized([70.0, 30.0], button);
+
+        if component.clicked() { self.toggle_remote(); };
+    }
+
+    fn remote_token_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("New Token");
+        let component = ui.add_sized([80.0, 30.0], button)
+            .on_hover_text("Replace the token, signing out every connected client");
+
+        if component.clicked() {
+            self.remote.set_token(remote::new_token());
+
+            // The listener thread keeps a copy of the old token until it restarts
+            if self.remote.is_running() { self.remote.start(); };
+
+            self.save_remote();
+        };
+    }
+
+    fn repeat_button(&mut self, ui: &mut Ui, big: bool) {
+        let color = match self.repeat_state {
+            RepeatState::All | RepeatState::One => styles::get_button_fill(),
+            RepeatState::None => Color32::TRANSPARENT
+        };
+
+        let icon = match self.repeat_state {
+            RepeatState::All => images::get_repeat(),
+            RepeatState::None => images::get_repeat_off(),
+            RepeatState::One => images::get_repeat_one()
+        };
+
+        let size = if big { 60.0 } else { 30.0 };
+
+        let button = Button::new(icon).corner_radius(90).fill(color);
+        let component = ui.add_sized([size, size], button);
+
+        if component.clicked() { self.repeat(); };
+    }
+    
+    fn root_remove_button(&mut self, ui: &mut Ui, index: usize) {
+        let button = Button::new("-");
+        let component = ui.add_sized([30.0, 30.0], button);
+
+        if component.clicked() { self.remove_root(index); };
+    }
+
+    fn server_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Server").selected(self.server.is_running());
+        let component = ui.add(button);
+
+        if component.clicked() {
+            self.broadcast_open = false;
+            self.feed_open = false;
+            self.mpd_open = false;
+            self.playlist_add_open = false;
+            self.remote_open = false;
+            self.url_open = false;
+            self.server_open = !self.server_open;
+        };
+    }
+
+    fn server_share_button(&mut self, ui: &mut Ui) {
+        let sharing = self.server.get_track().is_some();
+        let track = if self.selected.is_empty() { self.now_playing.clone() }
+            else { self.selected.clone() };
+
+        let enabled = sharing || (!track.is_empty() && !filesys::is_url(track.clone()));
+        let button = Button::new(if sharing { "Unshare" } else { "Share" });
+        let component = ui.add_enabled_ui(enabled, |ui| ui.add_sized([70.0, 30.0], button))
+            .inner.on_hover_text("Serve the selected track on its own");
+
+        if component.clicked() { self.server.set_track(if sharing { None } else { Some(track) }); };
+    }
+
+    fn server_start_button(&mut self, ui: &mut Ui) {
+        let button = Button::new(if self.server.is_running() { "Stop" } else { "Start" });
+        let component = ui.add_sized([70.0, 30.0], button);
+
+        if component.clicked() { self.toggle_server(); };
+    }
+
+    fn shuffle_button(&mut self, ui: &mut Ui, big: bool) {
+        let color = match self.is_shuffled {
+            false => Color32::TRANSPARENT,
+            true => styles::get_button_fill()
+        };
+
+        let icon = match self.is_shuffled {
+            false => images::get_shuffle_off(),
+            true => images::get_shuffle()
+        };
+
+        let size = if big { 60.0 } else { 30.0 };
+
+        let button = Button::new(icon).corner_radius(90).fill(color);
+        let component = ui.add_sized([size, size], button);
+
+        if component.clicked() {
+            self.is_shuffled = !self.is_shuffled;
+            
+            self.shuffle();
+        };
+    }
+    
+    fn skip_backward_button(&mut self, ui: &mut Ui, big: bool) {
+        let icon = images::get_skip_backward();
+
+        let size = if big { 60.0 } else { 30.0 };
+
+        let button = Button::new(icon).corner_radius(90);
+        let component = ui.add_sized([size, size], button);
+
+        if component.clicked() { self.skip_backward(); };
+    }
+    
+    fn skip_forward_button(&mut self, ui: &mut Ui, big: bool) {
+        let icon = images::get_skip_forward();
+
+        let size = if big { 60.0 } else { 30.0 };
+
+        let button = Button::new(icon).corner_radius(90);
+        let component = ui.add_sized([size, size], button);
+
+        if component.clicked() { self.skip_forward(); };
+    }
+
+    fn source_add_button(&mut self, ui: &mut Ui) {
+        let icon = images::get_add();
+        let button = Button::new(icon);
+        let component = ui.add_sized([ui.available_width(), 30.0], button);
+
+        if component.clicked() {
+            self.selected = String::new();
+            
+            self.playlist_dialog.pick_file();
+        };
+    }
+    
+    fn source_play_button(&mut self, ui: &mut Ui, source: String) {
+        let icon = images::get_play();
+        let button = Button::new(icon).corner_radius(90);
+        let component = ui.add_sized([30.0, 30.0], button);
+
+        if component.clicked() {
+            self.stop();
+            
+            self.selected = source;
+            self.now_playing = self.selected.clone();
+            self.now_playinglist = self.active_playlist.clone();
+            self.sorted_playlist = self.active_playlist.clone();
+            
+            self.play();
+        };
+    }
+
+    fn source_remove_button(&mut self, ui: &mut Ui, source: String) {
+        let button = Button::new("-");
+        let component = ui.add_sized([30.0, 30.0], button);
+
+        if component.clicked() {
+            self.selected = String::new();
+            
+            if let Some(playlist) = self.active_playlist.clone() {
+                let mut list = Playlist::new(playlist.get_name().unwrap());
+                
+                list.set_id(playlist.get_id().unwrap());
+
+                if let Some(sources) = playlist.get_sources() {
+                    for entry in sources {
+                        if entry != source { list.add_source(Some(entry)); };
+                    };
+                };
+
+                self.active_playlist = Some(list.clone());
+
+                self.save_playlists(self.active_playlist.clone());
+            };
+        };
+    }
+    
+    fn station_add_button(&mut self, ui: &mut Ui) {
+        let icon = images::get_playlist_add();
+        let button = Button::new(icon);
+        let component = ui.add_sized([ui.available_width(), 30.0], button);
+
+        if component.clicked() {
+            self.broadcast_open = false;
+            self.feed_open = false;
+            self.mpd_open = false;
+            self.playlist_add_open = false;
+            self.remote_open = false;
+            self.server_open = false;
+            self.url_open = true;
+        };
+    }
+
+    fn station_play_button(&mut self, ui: &mut Ui, station: Station) {
+        let icon = images::get_play();
+        let button = Button::new(icon).corner_radius(90);
+        let component = ui.add_sized([30.0, 30.0], button);
+
+        if component.clicked() { self.play_url(station.get_url()); };
+    }
+
+    fn station_remove_button(&mut self, ui: &mut Ui, index: usize) {
+        let button = Button::new("-");
+        let component = ui.add_sized([30.0, 30.0], button);
+
+        if component.clicked() { self.remove_station(index); };
+    }
+
+    fn stop_button(&mut self, ui: &mut Ui, big: bool) {
+        let icon = images::get_stop();
+
+        let size = if big { 60.0 } else { 30.0 };
+
+        let button = Button::new(icon).corner_radius(90);
+        let component = ui.add_sized([size, size], button);
+
+        if component.clicked() {
+            self.now_playing = String::new();
+            self.selected = String::new();
+            self.now_playinglist = None;
+            self.sorted_playlist = None;
+        
+            self.stop();
+        };
+    }
+
+    fn subtitle_offset_button(&mut self, ui: &mut Ui, delta: i64) {
+        let label = if delta < 0 { "-" } else { "+" };
+        let component = ui.add_sized([30.0, 30.0], Button::new(label));
+
+        if component.clicked() { self.subtitle_offset += delta; };
+    }
+
+    fn subtitle_size_button(&mut self, ui: &mut Ui, delta: f32) {
+        let label = if delta < 0.0 { "A-" } else { "A+" };
+        let component = ui.add_sized([40.0, 30.0], Button::new(label));
+
+        if component.clicked() {
+            self.subtitle_size = (self.subtitle_size + delta).clamp(12.0, 72.0);
+        };
+    }
+
+    fn tag_close_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Close");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() {
+            self.tag_editor_open = false;
+            self.tag_source = String::new();
+            self.tag_message = String::new();
+        };
+    }
+
+    fn tag_revert_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Revert");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() {
+            self.tag_edit = self.tag_original.clone();
+            self.tag_message = String::new();
+        };
+    }
+
+    fn tag_save_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Save");
+        let component = ui.add_sized([55.0, 30.0], button);
+
+        if component.clicked() { self.save_tags(); };
+    }
+
+    fn tags_button(&mut self, ui: &mut Ui) {
+        let source = if self.selected != String::new() { self.selected.clone() }
+            else { self.now_playing.clone() };
+
+        let button = Button::new("Tags");
+        let component = ui.add_enabled(source != String::new(), button);
+
+        if component.clicked() { self.open_tag_editor(source); };
+    }
+
+    fn url_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("URL");
+        let component = ui.add(button);
+
+        if component.clicked() {
+            self.broadcast_open = false;
+            self.feed_open = false;
+            self.mpd_open = false;
+            self.playlist_add_open = false;
+            self.remote_open = false;
+            self.server_open = false;
+            self.url_open = !self.url_open;
+        };
+    }
+
+    fn url_play_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Play");
+        let enabled = filesys::is_url(self.url_input.trim().to_string());
+        let component = ui.add_enabled_ui(enabled, |ui| ui.add_sized([55.0, 30.0], button)).inner;
+
+        if component.clicked() { self.play_url(self.url_input.trim().to_string()); };
+    }
+
+    fn url_save_button(&mut self, ui: &mut Ui) {
+        let button = Button::new("Save");
+        let enabled = filesys::is_url(self.url_input.trim().to_string());
+        let component = ui.add_enabled_ui(enabled, |ui| ui.add_sized([55.0, 30.0], button)).inner;
+
+        if component.clicked() { self.save_station(); };
+    }
+
+    //** UI - Panels **//
+    fn add_playlist(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
+
+        ui.horizontal(|ui| {
+            ui.add(Label::new("Name:"));
+            ui.add_space(5.5);
+
+            ui.add_sized([ui.available_width(), 30.0],
+                TextEdit::singleline(&mut self.temp_playlist_name));
+        });
+
+        ui.add_space(5.5);
+
+        ui.columns(9, |columns| {
+            self.playlist_add_save_button(&mut columns[3]);
+            self.cancel_button(&mut columns[5]);
+        });
+    }
+
+    fn add_podcast(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
+
+        ui.horizontal(|ui| {
+            ui.add(Label::new("Feed:"));
+            ui.add_space(5.5);
+
+            let input = ui.add_sized([ui.available_width(), 30.0],
+                TextEdit::singleline(&mut self.feed_input).hint_text("https://"));
+
+            if input.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter)) {
+                self.subscribe_podcast();
+            };
+        });
+
+        ui.add_space(5.5);
+
+        ui.columns(9, |columns| {
+            self.podcast_subscribe_button(&mut columns[3]);
+            self.cancel_button(&mut columns[5]);
+        });
+    }
+
+    fn batch_editor(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
+
+        let (name, count) = match &self.batch {
+            Some(batch) => (batch.get_name(), batch.len()),
+            None => return
+        };
+
+        ui.add_sized([ui.available_width(), 30.0],
+            Label::new(format!("Batch:  {}  ({} files)", name, count)).truncate());
+
+        ui.add_space(5.5);
+
+        if let Some(batch) = &mut self.batch {
+            Grid::new("batch_editor").num_columns(3).spacing([10.0, 6.0]).show(ui, |ui| {
+                for field in TagField::ALL {
+                    ui.label(field.get_label());
+                    ui.checkbox(batch.keep_mut(field), "Keep");
+
+                    let width = if field.is_numeric() { 80.0 } else { ui.available_width() };
+                    let enabled = !batch.is_kept(field);
+
+                    ui.add_enabled_ui(enabled, |ui| {
+                        let value = batch.get_edit_mut().get_mut(field);
+
+                        ui.add_sized([width, 30.0], TextEdit::singleline(value));
+                    });
+
+                    ui.end_row();
+                };
+            });
+        };
+
+        ui.add_space(5.5);
+
+        let edits = self.batch.as_ref().map(|batch| batch.preview()).unwrap_or_default();
+        let can_undo = !self.batch_undo.is_empty();
+
+        ui.columns(9, |columns| {
+            columns[2].add_enabled_ui(!edits.is_empty(), |ui| { self.batch_apply_button(ui); });
+            columns[4].add_enabled_ui(can_undo, |ui| { self.batch_undo_button(ui); });
+            self.batch_close_button(&mut columns[6]);
+        });
+
+        if self.batch_message != String::new() {
+            ui.add_space(5.5);
+            ui.label(self.batch_message.clone());
+        };
+
+        ui.add_space(5.5);
+        ui.separator();
+
+        ScrollArea::vertical().auto_shrink(false).id_salt("batch_preview").show(ui, |ui| {
+            for edit in edits {
+                let file = filesys::create_from_path(edit.get_source());
+
+                ui.label(RichText::new(format!("{}  —  {}:  {}  →  {}", file,
+                    edit.get_field().get_label(), edit.get_before(), edit.get_after())).size(16.0));
+            };
+        });
+    }
+
+    fn big_player(&mut self, ctx: &Context) {
+        let id = ViewportId::from_hash_of("big_player");
+
+        let viewport = ViewportBuilder::default().with_decorations(false)
+            .with_inner_size(Vec2::new(700.0, 241.0));
+
+        if self.big_player_open {
+            ctx.show_viewport_immediate(id, viewport, |ctx, class| {
+                assert!(class == ViewportClass::Immediate, "Fatal error.");
+
+                CentralPanel::default().frame(Frame::NONE).show(ctx, |ui| {
+                    let rect = {
+                        let mut rect = ui.max_rect();
+
+                        rect.max.y = rect.min.y + ui.available_height();
+
+                        rect
+                    };
+
+                    let panel = ui.interact(rect, Id::new("big_player"), Sense::click_and_drag());
+
+                    if panel.drag_started_by(PointerButton::Primary) {
+                        ctx.send_viewport_cmd(ViewportCommand::StartDrag);
+                    };
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(12.0);
+
+                        self.playing_view(ui, false);
+                    });
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(155.0);
+
+                        self.controls(ui, true);
+                    });
+                });
+            });
+        };
+    }
+
+    fn broadcast_settings(&mut self, ui: &mut Ui) {
+        let running = self.broadcast.is_running();
+
+        ui.add_space(8.5);
+
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!running, |ui| {
+                ui.add(Label::new("Host:"));
+                ui.add(TextEdit::singleline(&mut self.broadcast_host).desired_width(140.0));
+                ui.add(Label::new("Port:"));
+                ui.add(TextEdit::singleline(&mut self.broadcast_port).desired_width(50.0));
+                ui.add(Label::new("Mount:"));
+                ui.add(TextEdit::singleline(&mut self.broadcast_mount).desired_width(90.0));
+                ui.add(Label::new("User:"));
+                ui.add(TextEdit::singleline(&mut self.broadcast_user).desired_width(80.0));
+                ui.add(Label::new("Password:"));
+
+                ui.add(TextEdit::singleline(&mut self.broadcast_password).password(true)
+                    .desired_width(100.0));
+
+                ComboBox::from_id_salt("broadcast_format")
+                    .selected_text(self.broadcast_format.get_label()).show_ui(ui, |ui| {
+                    for option in Format::all() {
+                        ui.selectable_value(&mut self.broadcast_format, option,
+                            option.get_label());
+                    };
+                });
+
+                if !self.broadcast_format.has_titles() {
+                    ui.label(RichText::new("No titles").color(styles::get_missing_color()))
+                        .on_hover_text("Icecast cannot update titles on Opus streams");
+                };
+            });
+
+            ui.add_space(10.0);
+
+            let status = self.broadcast.get_status();
+
+            match status {
+                StreamStatus::Failed(_) => {
+                    let label = status.get_label().unwrap_or_default();
+
+                    ui.label(RichText::new(label).color(styles::get_missing_color()));
+                },
+
+                StreamStatus::Streaming => {
+                    let mount = self.broadcast.get_mount();
+
+                    ui.label(format!("Live on {}:{}{}", mount.get_host(), mount.get_port(),
+                        mount.get_mount()));
+                },
+
+                _ => { ui.label(status.get_label().unwrap_or("Off air".to_string())); }
+            };
+        });
+
+        ui.add_space(5.5);
+
+        ui.columns(9, |columns| {
+            self.broadcast_start_button(&mut columns[3]);
+            self.cancel_button(&mut columns[5]);
+        });
+    }
+
+    fn buffer(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
+        ui.add_space(3.0);
+    }
+
+    fn catalog(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
+
+        ui.columns(3, |columns| {
+            let mut can_go_back = false;
+            let mut can_go_dir = false;
+
+            if self.folders.len() > 0 || !self.dir.is_empty() { can_go_back = true; };
+            if self.path.clone() != self.dir.clone() { can_go_dir = true; };
+
+            columns[0].add_enabled_ui(can_go_dir, |ui| { self.dir_button(ui); });
+            let label = match &self.scanner {
+                Some(scanner) => format!("Scanning ({})", scanner.get_found()),
+                None => "Catalog".to_string()
+            };
+
+            columns[1].add_sized([columns[1].available_width(), 30.0], Label::new(label));
+            columns[2].add_enabled_ui(can_go_back, |ui| { self.catalog_back_button(ui); });
+        });
+
+        ui.add_space(3.0);
+        ui.separator();
+        ui.vertical(|ui| { let _ = self.catalog_list(ui); });
+    }
+
+    fn controls(&mut self, ui: &mut Ui, big: bool) {
+        ui.horizontal(|ui| {
+            self.repeat_button(ui, big);
+
+            let mut enabled = if self.selected != String::new() { true }
+            else { false };
+
+            match self.play_state {
+                PlayState::Paused | PlayState::Stopped => {},
+                PlayState::Playing => enabled = true
+            };
+
+            ui.add_enabled_ui(enabled, |ui| {
+                self.skip_backward_button(ui, big);
+                self.stop_button(ui, big);
+                self.play_button(ui, big);
+                self.skip_forward_button(ui, big);
+            });
+
+            self.shuffle_button(ui, big);
+        });
+    }
+
+    fn edit_playlist(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
+
+        ui.horizontal(|ui| {
+            ui.add(Label::new("Name:"));
+            ui.add_space(5.5);
+
+            if let Some(playlist) = &self.edited_playlist {
+                ui.add_sized([ui.available_width(), 30.0],
+                    TextEdit::singleline(&mut playlist.get_name().unwrap()));
+            };
+        });
+
+        ui.add_space(5.5);
+
+        ui.columns(9, |columns| {
+            self.playlist_edit_save_button(&mut columns[2]);
+            self.playlist_remove_button(&mut columns[4]);
+            self.cancel_button(&mut columns[6]);
+        });
+
+        ui.add_space(5.5);
+
+        let salt = self.edited_playlist.clone().unwrap().get_id();
+
+        ScrollArea::vertical().id_salt(salt).show(ui, |ui| {
+            if let Some(playlist) = &self.edited_playlist {
+                if let Some(sources) = playlist.get_sources() {
+                    for source in sources {
+                        ui.set_width(ui.available_width());
+
+                        ui.horizontal(|ui| {
+                            self.source_remove_button(ui, source.clone());
+
+                            ui.add_space(5.5);
+
+                            let title = self.get_display_title(&source);
+
+                            let mut text = RichText::new(title);
+
+                            if self.missing.contains(&source) {
+                                text = text.color(styles::get_missing_color()).strikethrough();
+                            };
+
+                            let button = ui.add(Button::new(text).frame(false)
+                                .fill(Color32::TRANSPARENT));
+
+                            if button.clicked() { self.selected = source; };
+                        });
+                    };
+                };
+            };
+        });
+    }
+
+    fn mini_player(&mut self, ctx: &Context) {
+        let id = ViewportId::from_hash_of("mini_player");
+
+        let viewport = ViewportBuilder::default().with_always_on_top().with_decorations(false)
+            .with_inner_size(Vec2::new(223.0, 115.0));
+
+        if self.mini_player_open {
+            ctx.show_viewport_immediate(id, viewport, |ctx, class| {
+                assert!(class == ViewportClass::Immediate, "Fatal error.");
+
+                CentralPanel::default().frame(Frame::NONE).show(ctx, |ui| {
+                    let rect = {
+                        let mut rect = ui.max_rect();
+
+                        rect.max.y = rect.min.y + ui.available_height();
+
+                        rect
+                    };
+
+                    let panel = ui.interact(rect, Id::new("mini_player"), Sense::click_and_drag());
+
+                    if panel.drag_started_by(PointerButton::Primary) {
+                        ctx.send_viewport_cmd(ViewportCommand::StartDrag);
+                    };
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(12.0);
+
+                        self.playing_view(ui, true);
+                    });
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(12.0);
+
+                        self.controls(ui, false);
+                    });
+                });
+            });
+        };
+    }
+
+    fn lyrics_view(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
+
+        self.load_lyrics();
+
+        let title = "Lyrics:  ".to_owned() + &self.get_display_title(&self.lyrics_source);
+
+        ui.add_sized([ui.available_width(), 30.0], Label::new(title).truncate());
+
+        ui.add_space(5.5);
+
+        ui.horizontal(|ui| {
+            let offset = self.lyrics.as_ref().map(|lyrics| lyrics.get_offset()).unwrap_or(0);
+
+            ui.label("Offset");
+
+            self.lyrics_offset_button(ui, -100);
+
+            ui.add_sized([90.0, 30.0], Label::new(format!("{:+} ms", offset)));
+
+            self.lyrics_offset_button(ui, 100);
+
+            ui.add_space(10.0);
+
+            self.lyrics_edit_button(ui);
+            ui.add_enabled_ui(self.lyrics.is_some(), |ui| { self.lyrics_save_button(ui); });
+            self.lyrics_close_button(ui);
+        });
+
+        if self.lyrics_message != String::new() {
+            ui.add_space(5.5);
+            ui.label(self.lyrics_message.clone());
+        };
+
+        ui.add_space(5.5);
+
+        if self.lyrics_editing {
+            ui.add_sized([ui.available_width(), ui.available_height() / 3.0],
+                TextEdit::multiline(&mut self.lyrics_draft));
+
+            ui.add_space(5.5);
+
+            ui.horizontal(|ui| {
+                self.lyrics_apply_button(ui);
+                self.lyrics_back_button(ui);
+                self.lyrics_stamp_button(ui);
+            });
+
+            ui.add_space(5.5);
+        };
+
+        self.lyrics_list(ui);
+    }
+
+    fn mpd_settings(&mut self, ui: &mut Ui) {
+        let running = self.mpd.is_running();
+
+        ui.add_space(8.5);
+
+        ui.horizontal(|ui| {
+            ui.add(Label::new("Port:"));
+            ui.add_space(5.5);
+
+            ui.add_enabled(!running, TextEdit::singleline(&mut self.mpd_port)
+                .desired_width(60.0));
+
+            ui.add_space(5.5);
+            ui.add(Label::new("Password:"));
+            ui.add_space(5.5);
+
+            ui.add_enabled(!running, TextEdit::singleline(&mut self.mpd_password).password(true)
+                .desired_width(100.0))
+                .on_hover_text("Without a password only this computer can connect");
+
+            ui.add_space(10.0);
+
+            let message = self.mpd.get_message();
+
+            if running {
+                // Without a password the server only listens on the loopback address
+                let reach = if self.mpd.get_password().is_empty() { "local" } else { "LAN" };
+
+                ui.add(Label::new(format!("Answering {} MPD clients on port {}  ·  {} connected",
+                    reach, self.mpd.get_port(), self.mpd.get_clients())).truncate());
+            } else if !message.is_empty() {
+                ui.label(RichText::new(message).color(styles::get_missing_color()));
+            } else { ui.label("Stopped"); };
+        });
+
+        ui.add_space(5.5);
+
+        ui.columns(9, |columns| {
+            self.mpd_start_button(&mut columns[3]);
+            self.cancel_button(&mut columns[5]);
+        });
+    }
+
+    fn open_url(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
+
+        ui.horizontal(|ui| {
+            ui.add(Label::new("Name:"));
+            ui.add_space(5.5);
+
+            ui.add_sized([180.0, 30.0],
+                TextEdit::singleline(&mut self.url_name).hint_text("Station name"));
+
+            ui.add_space(5.5);
+            ui.add(Label::new("URL:"));
+            ui.add_space(5.5);
+
+            let input = ui.add_sized([ui.available_width(), 30.0],
+                TextEdit::singleline(&mut self.url_input).hint_text("https://"));
+
+            if input.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter)) {
+                self.play_url(self.url_input.trim().to_string());
+            };
+        });
+
+        ui.add_space(5.5);
+
+        ui.columns(9, |columns| {
+            self.url_play_button(&mut columns[3]);
+            self.url_save_button(&mut columns[4]);
+            self.cancel_button(&mut columns[5]);
+        });
+    }
+
+    fn organizer(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
+
+        ui.horizontal(|ui| {
+            let rename = matches!(self.organize_mode, OrganizeMode::Rename);
+
+            if ui.selectable_label(rename, "Rename from tags").clicked() && !rename {
+                self.organize_mode = OrganizeMode::Rename;
+
+                self.organize_retags.clear();
+            };
+
+            if ui.selectable_label(!rename, "Tag from filename").clicked() && rename {
+                self.organize_mode = OrganizeMode::Tag;
+
+                self.organize_moves.clear();
+            };
+        });
+
+        ui.add_space(5.5);
+
+        ui.horizontal(|ui| {
+            ui.add(Label::new("Pattern:"));
+            ui.add_space(5.5);
+
+            ui.add_sized([ui.available_width(), 30.0],
+                TextEdit::singleline(&mut self.organize_pattern));
+        });
+
+        ui.add_space(5.5);
+
+        let ready = match self.organize_mode {
+            OrganizeMode::Rename => self.organize_moves.iter()
+                .any(|entry| entry.get_conflict().is_none()),
+            OrganizeMode::Tag => !self.organize_retags.is_empty()
+        };
+
+        ui.columns(9, |columns| {
+            self.organize_preview_button(&mut columns[2]);
+            columns[4].add_enabled_ui(ready, |ui| { self.organize_apply_button(ui); });
+            self.organize_close_button(&mut columns[6]);
+        });
+
+        if self.organize_message != String::new() {
+            ui.add_space(5.5);
+            ui.label(self.organize_message.clone());
+        };
+
+        ui.add_space(5.5);
+        ui.separator();
+
+        ScrollArea::vertical().auto_shrink(false).id_salt("organize_preview").show(ui, |ui| {
+            for entry in &self.organize_moves {
+                let relative = |source: String| Path::new(&source).strip_prefix(&self.dir)
+                    .map(|path| path.display().to_string()).unwrap_or(source.clone());
+
+                let from = relative(entry.get_from());
+                let to = relative(entry.get_to());
+                let line = format!("{}  →  {}", from, to);
+
+                let text = match entry.get_conflict() {
+                    Some(conflict) => RichText::new(line + "  (" + &conflict + ")").size(16.0)
+                        .color(styles::get_missing_color()),
 
-                if let Some(sources) = playlist.get_sources() {
-                    for entry in sources {
-                        if entry != source { list.add_source(Some(entry)); };
-                    };
+                    None => RichText::new(line).size(16.0)
                 };
 
-                self.active_playlist = Some(list.clone());
+                ui.label(text);
+            };
 
-                self.save_playlists(self.active_playlist.clone());
+            for retag in &self.organize_retags {
+                let file = filesys::create_from_path(retag.get_source());
+                let (before, after) = (retag.get_before(), retag.get_after());
+
+                for field in TagField::ALL {
+                    if before.get(field) != after.get(field) {
+                        ui.label(RichText::new(format!("{}  —  {}:  {}  →  {}", file,
+                            field.get_label(), before.get(field), after.get(field))).size(16.0));
+                    };
+                };
             };
-        };
+        });
     }
-    
-    fn stop_button(&mut self, ui: &mut Ui, big: bool) {
-        let icon = images::get_stop();
 
-        let size = if big { 60.0 } else { 30.0 };
+    fn playing_view(&mut self, ui: &mut Ui, mini: bool) {
+        let size = if mini { 12.0 } else { 40.0 };
+        let cover = if mini { 36.0 } else { 120.0 };
+        let cover_art = self.artwork.get(ui.ctx(), &self.now_playing);
+        let width = match (mini, cover_art.is_some()) {
+            (true, true) => 151.0,
+            (true, false) => 193.0,
+            (false, true) => 550.0,
+            (false, false) => 680.0
+        };
 
-        let button = Button::new(icon).corner_radius(90);
-        let component = ui.add_sized([size, size], button);
+        let metadata = self.library.get_metadata(&self.now_playing);
+        let title = self.get_display_title(&self.now_playing);
+        let artist = self.get_display_artist(&self.now_playing, &title).unwrap_or_default();
 
-        if component.clicked() {
-            self.now_playing = String::new();
-            self.selected = String::new();
-            self.now_playinglist = None;
-            self.sorted_playlist = None;
-        
-            self.stop();
+        let album = match (metadata.get_album(), metadata.get_year()) {
+            (Some(album), Some(year)) => format!("{} ({})", album, year),
+            (Some(album), None) => album,
+            (None, _) => String::new()
         };
-    }
 
-    //** UI - Panels **//
-    fn add_playlist(&mut self, ui: &mut Ui) {
-        ui.add_space(8.5);
+        if let Some(texture) = cover_art {
+            ui.add(Image::from_texture(SizedTexture::from_handle(&texture))
+                .fit_to_exact_size(Vec2::splat(cover)).maintain_aspect_ratio(true));
 
-        ui.horizontal(|ui| {
-            ui.add(Label::new("Name:"));
             ui.add_space(5.5);
+        };
 
-            ui.add_sized([ui.available_width(), 30.0],
-                TextEdit::singleline(&mut self.temp_playlist_name));
-        });
+        ui.vertical(|ui| {
+            ui.add_sized([width, size], Label::new(
+                RichText::new(title.clone()).size(size)).truncate());
 
-        ui.add_space(5.5);
+            ui.add_sized([width, size], Label::new(
+                RichText::new(artist.clone()).size(size)).truncate());
 
-        ui.columns(9, |columns| {
-            self.playlist_add_save_button(&mut columns[3]);
-            self.cancel_button(&mut columns[5]);
+            ui.add_sized([width, size], Label::new(
+                RichText::new(album.clone()).size(size)).truncate());
         });
     }
+    
+    fn player(&mut self, ctx: &Context, ui: &mut Ui) -> Response {
+        let (rect, response) = ui.allocate_exact_size(Vec2::default(), Sense::click());
 
-    fn big_player(&mut self, ctx: &Context) {
-        let id = ViewportId::from_hash_of("big_player");
+        if ui.is_rect_visible(rect) {
+            ui.add_space(8.5);
 
-        let viewport = ViewportBuilder::default().with_decorations(false)
-            .with_inner_size(Vec2::new(700.0, 241.0));
+            self.setup_stopwatch();
 
-        if self.big_player_open {
-            ctx.show_viewport_immediate(id, viewport, |ctx, class| {
-                assert!(class == ViewportClass::Immediate, "Fatal error.");
+            if let Ok(mut player) = self.playback.try_lock() {
+                player.set_volume(self.volume as f32 / 100.0);
+            };
 
-                CentralPanel::default().frame(Frame::NONE).show(ctx, |ui| {
-                    let rect = {
-                        let mut rect = ui.max_rect();
+            ui.columns(2, |columns| {
+                columns[0].horizontal(|ui| {
+                    self.change_dir_button(ui);
+                    self.dir_dialog.update(ctx);
 
-                        rect.max.y = rect.min.y + ui.available_height();
+                    if let Some(path) = self.dir_dialog.take_picked() {
+                        let path = path.to_path_buf().to_string_lossy().to_string();
 
-                        rect
+                        self.add_root(path);
                     };
 
-                    let panel = ui.interact(rect, Id::new("big_player"), Sense::click_and_drag());
+                    ui.add_space(5.5);
 
-                    if panel.drag_started_by(PointerButton::Primary) {
-                        ctx.send_viewport_cmd(ViewportCommand::StartDrag);
-                    };
+                    let mut dir = if self.dir.is_empty() { "All roots".to_string() }
+                        else { self.dir.clone() };
 
-                    ui.add_space(10.0);
+                    ui.add_sized([ui.available_width(), 30.0],
+                        TextEdit::singleline(&mut dir).interactive(false));
+                });
 
-                    ui.horizontal(|ui| {
-                        ui.add_space(12.0);
+                columns[1].horizontal(|ui| {
+                    ui.columns(3, |columns| {
+                        columns[0].horizontal(|ui| {
+                            ui.add_space(10.0);
 
-                        let album = data::get_album(Some(self.now_playing.clone()));
-                        let artist = data::get_artist(Some(self.now_playing.clone()));
-                        let mut title = data::get_title(Some(self.now_playing.clone()));
+                            self.controls(ui, false);
+                        });
 
-                        if title == "" {
-                            title = filesys::create_from_path(self.now_playing.clone());
-                        };
+                        columns[1].add_space(5.0);
+
+                        columns[1].horizontal(|ui| {
+                            self.tracking(ui);
+                                    
+                            ui.add_space(10.0);
+                            
+                            self.volume(ui);
+                        });
 
-                        self.playing_view(ui, album, artist, title, false);
+                        columns[2].allocate_ui_with_layout(Vec2::ZERO,
+                            Layout::right_to_left(Align::RIGHT),
+                            |ui| {
+                            self.mini_player_button(ui);
+                            self.big_player_button(ui);
+                            self.tags_button(ui);
+                            self.batch_button(ui);
+                            self.organize_button(ui);
+                            self.lyrics_button(ui);
+                            self.url_button(ui);
+                            self.server_button(ui);
+                            self.broadcast_button(ui);
+                            self.remote_button(ui);
+                            self.mpd_button(ui);
+                        });
                     });
+                });
+            });
 
-                    ui.add_space(10.0);
+            ui.add_space(6.0);
 
-                    ui.horizontal(|ui| {
-                        ui.add_space(155.0);
+            ui.horizontal(|ui| {
+                let metadata = self.library.get_metadata(&self.now_playing);
+                let title = self.get_display_title(&self.now_playing);
 
-                        self.controls(ui, true);
-                    });
+                ui.vertical(|ui| {
+                    let playing = "Playing:  ".to_owned();
+                    let mut alb = String::new();
+                    let mut art = String::new();
+                    let mut info = String::new();
+
+                    if let Some(album) = metadata.get_album() {
+                        alb = "  —  ".to_owned() + &album;
+                    };
+
+                    if let Some(artist) = self.get_display_artist(&self.now_playing, &title) {
+                        art = "  —  ".to_owned() + &artist;
+                    };
+
+                    if let Some(codec) = metadata.get_codec() {
+                        info = match metadata.get_bitrate() {
+                            Some(bitrate) => format!("  [{} {} kbps]", codec, bitrate),
+                            None => format!("  [{}]", codec)
+                        };
+                    };
+
+                    let line = playing.clone() + &title.clone() + &art + &alb + &info;
+
+                    let status = self.playback.try_lock().map(|player| player.get_status())
+                        .unwrap_or(StreamStatus::Idle);
+
+                    if self.now_playing != String::new() {
+                        ui.horizontal(|ui| {
+                            ui.label(line);
+
+                            if let Some(label) = status.get_label() {
+                                let mut text = RichText::new(label).italics();
+
+                                if let StreamStatus::Failed(_) = status {
+                                    text = text.color(styles::get_missing_color());
+                                };
+
+                                ui.label(text);
+                            };
+                        });
+                    } else { ui.label(playing); };
                 });
+                
+                self.update_playback();
             });
+
+            ui.add_space(1.5);
+
+            // The visualizer's frame limit would be pointless if playback repainted faster
+            let delay = match self.is_visualizing() {
+                true => self.visualizer.get_interval(),
+                false => Duration::from_millis(10)
+            };
+
+            ui.ctx().request_repaint_after(delay);
         };
-    }
 
-    fn buffer(&mut self, ui: &mut Ui) {
-        ui.add_space(8.5);
-        ui.add_space(3.0);
+        response
     }
-
-    fn catalog(&mut self, ui: &mut Ui) {
+    
+    fn playlists(&mut self, ctx: &Context, ui: &mut Ui) {
         ui.add_space(8.5);
 
+        let back = self.active_playlist.is_some() || self.active_podcast.is_some();
+
+        let podcast = self.active_podcast.as_ref()
+            .and_then(|url| self.podcasts.get_podcast(url));
+
         ui.columns(3, |columns| {
-            let mut can_go_back = false;
-            let mut can_go_dir = false;
+            if self.active_playlist.is_some() { self.source_add_button(&mut columns[0]); }
+            else if let Some(podcast) = &podcast {
+                self.podcast_refresh_button(&mut columns[0], podcast.get_url());
+            } else {
+                match self.list_mode {
+                    ListMode::Playlists => self.playlist_add_button(&mut columns[0]),
+                    ListMode::Podcasts => self.podcast_add_button(&mut columns[0]),
+                    ListMode::Stations => self.station_add_button(&mut columns[0])
+                };
+            };
 
-            if self.folders.len() > 0 { can_go_back = true; };
-            if self.path.clone() != self.dir.clone() { can_go_dir = true; };
+            if self.active_playlist.is_some() {
+                columns[1].add_sized([columns[1].available_width(), 30.0], Label::new("Sources"));
+            } else if let Some(podcast) = &podcast {
+                columns[1].add_sized([columns[1].available_width(), 30.0],
+                    Label::new(podcast.get_title()).truncate());
+            } else { self.list_mode_button(&mut columns[1]); };
 
-            columns[0].add_enabled_ui(can_go_dir, |ui| { self.dir_button(ui); });
-            columns[1].add_sized([columns[1].available_width(), 30.0], Label::new("Catalog"));
-            columns[2].add_enabled_ui(can_go_back, |ui| { self.catalog_back_button(ui); });
+            columns[2].add_enabled_ui(back, |ui| { self.playlist_back_button(ui); });
         });
 
-        ui.add_space(3.0);
         ui.separator();
-        ui.vertical(|ui| { let _ = self.catalog_list(ui); });
+
+        let message = self.podcasts.get_message();
+
+        if matches!(self.list_mode, ListMode::Podcasts) && !message.is_empty() {
+            ui.label(RichText::new(message).color(styles::get_missing_color()));
+        };
+
+        if let Some(podcast) = podcast { ui.vertical(|ui| { self.episodes_list(ui, podcast); }); }
+        else if back { ui.vertical(|ui| { let _ = self.playlists_list(ctx, ui); }); }
+        else {
+            match self.list_mode {
+                ListMode::Playlists => ui.vertical(|ui| { let _ = self.playlists_list(ctx, ui); }),
+                ListMode::Podcasts => ui.vertical(|ui| { self.podcasts_list(ui); }),
+                ListMode::Stations => ui.vertical(|ui| { self.stations_list(ui); })
+            };
+        };
     }
+    
+    fn remote_settings(&mut self, ui: &mut Ui) {
+        let running = self.remote.is_running();
+
+        ui.add_space(8.5);
 
-    fn controls(&mut self, ui: &mut Ui, big: bool) {
         ui.horizontal(|ui| {
-            self.repeat_button(ui, big);
+            ui.add(Label::new("Port:"));
+            ui.add_space(5.5);
 
-            let mut enabled = if self.selected != String::new() { true }
-            else { false };
+            ui.add_enabled(!running, TextEdit::singleline(&mut self.remote_port)
+                .desired_width(60.0));
 
-            match self.play_state {
-                PlayState::Paused | PlayState::Stopped => {},
-                PlayState::Playing => enabled = true
-            };
+            ui.add_space(5.5);
+            ui.add(Label::new("Token:"));
+            ui.add_space(5.5);
 
-            ui.add_enabled_ui(enabled, |ui| {
-                self.skip_backward_button(ui, big);
-                self.stop_button(ui, big);
-                self.play_button(ui, big);
-                self.skip_forward_button(ui, big);
-            });
+            let mut token = self.remote.get_token();
 
-            self.shuffle_button(ui, big);
+            ui.add(TextEdit::singleline(&mut token).interactive(false).desired_width(280.0));
+
+            ui.add_space(10.0);
+
+            let message = self.remote.get_message();
+
+            if running {
+                let port = self.remote.get_port();
+
+                ui.add(Label::new(format!("Listening on port {} at /api, events at /api/events",
+                    port)).truncate());
+            } else if !message.is_empty() {
+                ui.label(RichText::new(message).color(styles::get_missing_color()));
+            } else { ui.label("Disabled"); };
+        });
+
+        ui.add_space(5.5);
+
+        ui.columns(9, |columns| {
+            self.remote_start_button(&mut columns[3]);
+            self.remote_token_button(&mut columns[4]);
+            self.cancel_button(&mut columns[5]);
         });
     }
 
-    fn edit_playlist(&mut self, ui: &mut Ui) {
+    fn server_settings(&mut self, ui: &mut Ui) {
+        let running = self.server.is_running();
+
         ui.add_space(8.5);
 
         ui.horizontal(|ui| {
-            ui.add(Label::new("Name:"));
+            ui.add(Label::new("Port:"));
             ui.add_space(5.5);
 
-            if let Some(playlist) = &self.edited_playlist {
-                ui.add_sized([ui.available_width(), 30.0],
-                    TextEdit::singleline(&mut playlist.get_name().unwrap()));
+            ui.add_enabled(!running, TextEdit::singleline(&mut self.server_port)
+                .desired_width(60.0));
+
+            ui.add_space(5.5);
+            ui.add(Label::new("Format:"));
+            ui.add_space(5.5);
+
+            let mut format = self.server.get_format();
+
+            ui.add_enabled_ui(!running, |ui| {
+                ComboBox::from_id_salt("server_format").selected_text(format.get_label())
+                    .show_ui(ui, |ui| {
+                    for option in Format::all() {
+                        ui.selectable_value(&mut format, option, option.get_label());
+                    };
+                });
+            });
+
+            if format != self.server.get_format() {
+                self.server.set_format(format);
+                self.save_server();
+            };
+
+            ui.add_space(10.0);
+
+            match self.server.get_address() {
+                Some(address) => {
+                    let mut status = format!("{}/stream  ·  {}/now-playing  ·  {} listening",
+                        address, address, self.server.get_listeners());
+
+                    if let Some(track) = self.server.get_track() {
+                        status += &format!("  ·  {} at {}/track",
+                            self.get_display_title(&track), address);
+                    };
+
+                    ui.add(Label::new(status).truncate());
+                },
+
+                None => {
+                    let message = self.server.get_message();
+
+                    if message.is_empty() { ui.label("Stopped"); }
+                    else { ui.label(RichText::new(message).color(styles::get_missing_color())); };
+                }
             };
         });
 
         ui.add_space(5.5);
 
         ui.columns(9, |columns| {
-            self.playlist_edit_save_button(&mut columns[2]);
-            self.playlist_remove_button(&mut columns[4]);
-            self.cancel_button(&mut columns[6]);
+            self.server_start_button(&mut columns[3]);
+            self.server_share_button(&mut columns[4]);
+            self.cancel_button(&mut columns[5]);
         });
+    }
 
-        ui.add_space(5.5);
+    fn subtitle_controls(&mut self, ui: &mut Ui) {
+        let (names, mut selected) = match &self.subtitles {
+            Some(subtitles) => (subtitles.get_names(), subtitles.get_selected()),
+            None => return
+        };
 
-        let salt = self.edited_playlist.clone().unwrap().get_id();
+        let current = selected.and_then(|index| names.get(index).cloned())
+            .unwrap_or("Off".to_string());
 
-        ScrollArea::vertical().id_salt(salt).show(ui, |ui| {
-            if let Some(playlist) = &self.edited_playlist {
-                if let Some(sources) = playlist.get_sources() {
-                    for source in sources {
-                        ui.set_width(ui.available_width());
+        ui.horizontal(|ui| {
+            ui.label("Subtitles");
 
-                        ui.horizontal(|ui| {
-                            self.source_remove_button(ui, source.clone());
+            ComboBox::from_id_salt("subtitle_track").selected_text(current).show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected, None, "Off");
 
-                            ui.add_space(5.5);
+                for (index, name) in names.iter().enumerate() {
+                    ui.selectable_value(&mut selected, Some(index), name);
+                };
+            });
 
-                            let mut title = data::get_title(Some(source.clone()));
+            ui.add_space(10.0);
 
-                            if title == String::new() {
-                                title = filesys::create_from_path(source.clone());
-                            };
+            self.subtitle_size_button(ui, -2.0);
+            self.subtitle_size_button(ui, 2.0);
+
+            ui.add_space(10.0);
+            ui.label("Offset");
+
+            self.subtitle_offset_button(ui, -100);
 
-                            let button = ui.add(Button::new(title).frame(false)
-                                .fill(Color32::TRANSPARENT));
+            ui.add_sized([90.0, 30.0], Label::new(format!("{:+} ms", self.subtitle_offset)));
 
-                            if button.clicked() { self.selected = source; };
-                        });
-                    };
-                };
-            };
+            self.subtitle_offset_button(ui, 100);
         });
+
+        if let Some(subtitles) = &mut self.subtitles { subtitles.set_selected(selected); };
     }
 
-    fn mini_player(&mut self, ctx: &Context) {
-        let id = ViewportId::from_hash_of("mini_player");
+    fn subtitle_overlay(&mut self, ui: &mut Ui, frame: Rect) {
+        let clock = self.get_clock();
 
-        let viewport = ViewportBuilder::default().with_always_on_top().with_decorations(false)
-            .with_inner_size(Vec2::new(223.0, 115.0));
+        let text = match self.subtitles.as_ref()
+            .and_then(|subtitles| subtitles.get_text(clock, self.subtitle_offset)) {
+            Some(text) => text,
+            None => return
+        };
 
-        if self.mini_player_open {
-            ctx.show_viewport_immediate(id, viewport, |ctx, class| {
-                assert!(class == ViewportClass::Immediate, "Fatal error.");
+        let painter = ui.painter_at(frame);
+        let font = FontId::proportional(self.subtitle_size);
+        let galley = painter.layout(text, font, Color32::WHITE, frame.width() * 0.9);
 
-                CentralPanel::default().frame(Frame::NONE).show(ctx, |ui| {
-                    let rect = {
-                        let mut rect = ui.max_rect();
+        let anchor = pos2(frame.center().x, frame.bottom() - frame.height() * 0.06);
+        let area = Align2::CENTER_BOTTOM.anchor_size(anchor, galley.size()).expand(6.0);
 
-                        rect.max.y = rect.min.y + ui.available_height();
+        // A translucent backing keeps captions readable over bright frames
+        painter.rect_filled(area, 4.0, Color32::from_black_alpha(170));
+        painter.galley(area.min + Vec2::splat(6.0), galley, Color32::WHITE);
+    }
 
-                        rect
-                    };
+    fn tag_editor(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
 
-                    let panel = ui.interact(rect, Id::new("mini_player"), Sense::click_and_drag());
+        let title = "Tags:  ".to_owned() + &filesys::create_from_path(self.tag_source.clone());
 
-                    if panel.drag_started_by(PointerButton::Primary) {
-                        ctx.send_viewport_cmd(ViewportCommand::StartDrag);
-                    };
+        ui.add_sized([ui.available_width(), 30.0], Label::new(title).truncate());
 
-                    ui.add_space(10.0);
+        ui.add_space(5.5);
 
-                    ui.horizontal(|ui| {
-                        ui.add_space(12.0);
+        Grid::new("tag_editor").num_columns(2).spacing([10.0, 6.0]).show(ui, |ui| {
+            for field in TagField::ALL {
+                ui.label(field.get_label());
 
-                        let album = data::get_album(Some(self.now_playing.clone()));
-                        let artist = data::get_artist(Some(self.now_playing.clone()));
-                        let mut title = data::get_title(Some(self.now_playing.clone()));
+                let width = if field.is_numeric() { 80.0 } else { ui.available_width() };
 
-                        if title == "" {
-                            title = filesys::create_from_path(self.now_playing.clone());
-                        };
+                ui.add_sized([width, 30.0], TextEdit::singleline(self.tag_edit.get_mut(field)));
+                ui.end_row();
+            };
+        });
 
-                        self.playing_view(ui, album, artist, title, true);
-                    });
+        ui.add_space(5.5);
 
-                    ui.add_space(10.0);
+        let changed = self.tag_edit != self.tag_original;
 
-                    ui.horizontal(|ui| {
-                        ui.add_space(12.0);
+        ui.columns(9, |columns| {
+            columns[2].add_enabled_ui(changed, |ui| { self.tag_save_button(ui); });
+            columns[4].add_enabled_ui(changed, |ui| { self.tag_revert_button(ui); });
+            self.tag_close_button(&mut columns[6]);
+        });
 
-                        self.controls(ui, false);
-                    });
-                });
-            });
+        if self.tag_message != String::new() {
+            ui.add_space(5.5);
+            ui.label(self.tag_message.clone());
         };
     }
 
-    fn playing_view(&mut self, ui: &mut Ui, album: String, artist: String, title: String, mini: bool) {
-        let size = if mini { 12.0 } else { 40.0 };
-        let width = if mini { 193.0 } else { 680.0 };
+    fn viewer(&mut self, ui: &mut Ui) {
+        ui.add_space(8.5);
 
-        ui.vertical(|ui| {
-            ui.add_sized([width, size], Label::new(
-                RichText::new(title.clone()).size(size)).truncate());
+        if let Some(texture) = self.sync_video(ui.ctx()) {
+            if self.subtitles.as_ref().is_some_and(|subtitles| !subtitles.get_names().is_empty()) {
+                self.subtitle_controls(ui);
 
-            ui.add_sized([width, size], Label::new(
-                RichText::new(artist.clone()).size(size)).truncate());
+                ui.add_space(5.5);
+            };
 
-            ui.add_sized([width, size], Label::new(
-                RichText::new(album.clone()).size(size)).truncate());
-        });
-    }
-    
-    fn player(&mut self, ctx: &Context, ui: &mut Ui) -> Response {
-        let (rect, response) = ui.allocate_exact_size(Vec2::default(), Sense::click());
+            let size = ui.available_size() - Vec2::new(0.0, 3.0);
 
-        if ui.is_rect_visible(rect) {
-            ui.add_space(8.5);
+            let response = ui.centered_and_justified(|ui| {
+                ui.add(Image::from_texture(SizedTexture::from_handle(&texture))
+                    .fit_to_exact_size(size).maintain_aspect_ratio(true))
+            }).inner;
 
-            self.setup_stopwatch();
+            self.subtitle_overlay(ui, response.rect);
 
-            if let Ok(mut player) = self.playback.try_lock() {
-                player.set_volume(self.volume as f32 / 100.0);
-            };
+            ui.add_space(3.0);
 
-            ui.columns(2, |columns| {
-                columns[0].horizontal(|ui| {
-                    self.change_dir_button(ui);
-                    self.dir_dialog.update(ctx);
+            return;
+        };
 
-                    if let Some(path) = self.dir_dialog.take_picked() {
-                        let path = path.to_path_buf().to_string_lossy().to_string();
+        ui.horizontal(|ui| {
+            let current = self.visualizer.get_mode();
 
-                        self.dir = path.clone();
-                        self.path = path.clone();
+            for mode in VisualMode::ALL {
+                if ui.selectable_label(current == mode, mode.get_label()).clicked() {
+                    self.visualizer.set_mode(mode);
+                };
+            };
 
-                        let _ = filesys::edit_config(path, self.volume);
-                    };
+            if current != VisualMode::Off {
+                ui.add_space(10.0);
+                ui.label("FPS");
 
-                    ui.add_space(5.5);
+                for fps in [15, 30, 60] {
+                    let selected = self.visualizer.get_fps() == fps;
 
-                    ui.add_sized([ui.available_width(), 30.0],
-                        TextEdit::singleline(&mut self.dir.clone()).interactive(false));
-                });
+                    if ui.selectable_label(selected, fps.to_string()).clicked() {
+                        self.visualizer.set_fps(fps);
+                    };
+                };
+            };
+        });
 
-                columns[1].horizontal(|ui| {
-                    ui.columns(3, |columns| {
-                        columns[0].horizontal(|ui| {
-                            ui.add_space(10.0);
+        ui.add_space(5.5);
 
-                            self.controls(ui, false);
-                        });
+        if self.visualizer.get_mode() != VisualMode::Off { self.visualizer(ui); }
+        else if let Some(texture) = self.artwork.get(ui.ctx(), &self.now_playing) {
+            let size = ui.available_size() - Vec2::new(0.0, 3.0);
 
-                        columns[1].add_space(5.0);
+            ui.centered_and_justified(|ui| {
+                ui.add(Image::from_texture(SizedTexture::from_handle(&texture))
+                    .fit_to_exact_size(size).maintain_aspect_ratio(true));
+            });
+        };
 
-                        columns[1].horizontal(|ui| {
-                            self.tracking(ui);
-                                    
-                            ui.add_space(10.0);
-                            
-                            self.volume(ui);
-                        });
+        ui.add_space(3.0);
+    }
 
-                        columns[2].allocate_ui_with_layout(Vec2::ZERO,
-                            Layout::right_to_left(Align::RIGHT),
-                            |ui| {
-                            self.mini_player_button(ui);
-                            self.big_player_button(ui);
-                        });
-                    });
-                });
-            });
+    fn visualizer(&mut self, ui: &mut Ui) {
+        self.visualizer.update();
 
-            ui.add_space(6.0);
+        let size = ui.available_size() - Vec2::new(0.0, 3.0);
+        let (rect, _response) = ui.allocate_exact_size(size, Sense::hover());
 
-            ui.horizontal(|ui| {
-                let album = data::get_album(Some(self.now_playing.clone()));
-                let artist = data::get_artist(Some(self.now_playing.clone()));
-                let mut title = data::get_title(Some(self.now_playing.clone()));
+        if !ui.is_rect_visible(rect) { return; }
 
-                if title == "" {
-                    title = filesys::create_from_path(self.now_playing.clone());
-                };
+        let painter = ui.painter_at(rect);
+        let fill = styles::get_button_fill();
+        let accent = styles::get_lyric_color();
 
-                ui.vertical(|ui| {
-                    let playing = "Playing:  ".to_owned();
-                    let mut alb = "  —  ".to_owned() + &album.clone();
-                    let mut art = "  —  ".to_owned() + &artist.clone();
+        painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
 
-                    if album == String::new() { alb = "".to_string(); };
-                    if artist == String::new() { art = "".to_string(); };
+        match self.visualizer.get_mode() {
+            VisualMode::Meters => {
+                let levels = self.visualizer.get_levels();
+                let peaks = self.visualizer.get_peaks();
+                let width = rect.width() / (levels.len().max(1) as f32 * 2.0 + 1.0);
 
-                    let line = playing.clone() + &title.clone() + &art.clone() + &alb.clone();
+                for (channel, level) in levels.iter().enumerate() {
+                    let left = rect.left() + width * (channel as f32 * 2.0 + 1.0);
+                    let top = rect.bottom() - rect.height() * level;
+                    let peak = rect.bottom() - rect.height() * peaks.get(channel).copied()
+                        .unwrap_or(*level);
 
-                    if self.now_playing != String::new() { ui.label(line); }
-                    else { ui.label(playing); };
-                });
-                
-                self.update_playback();
-            });
+                    painter.rect_filled(Rect::from_min_max(pos2(left, top),
+                        pos2(left + width, rect.bottom())), 2.0, fill);
 
-            ui.add_space(1.5);
+                    painter.line_segment([pos2(left, peak), pos2(left + width, peak)],
+                        Stroke::new(2.0, accent));
+                };
+            },
 
-            ui.ctx().request_repaint_after(Duration::from_millis(10));
-        };
+            VisualMode::Oscilloscope => {
+                let wave = self.visualizer.get_wave();
+                let step = rect.width() / wave.len().max(2) as f32;
+                let (middle, half) = (rect.center().y, rect.height() / 2.0);
 
-        response
-    }
-    
-    fn playlists(&mut self, ctx: &Context, ui: &mut Ui) {
-        ui.add_space(8.5);
+                let points = wave.iter().enumerate()
+                    .map(|(index, sample)| pos2(rect.left() + index as f32 * step,
+                        middle - sample.clamp(-1.0, 1.0) * half))
+                    .collect::<Vec<_>>();
 
-        let back: bool;
+                painter.line_segment([pos2(rect.left(), middle), pos2(rect.right(), middle)],
+                    Stroke::new(1.0, Color32::DARK_GRAY));
 
-        if self.active_playlist.is_some() { back = true; }
-        else { back = false; };
+                painter.add(Shape::line(points, Stroke::new(1.5, accent)));
+            },
 
-        ui.columns(3, |columns| {
-            if !back { self.playlist_add_button(&mut columns[0]); }
-            else { self.source_add_button(&mut columns[0]); };
+            VisualMode::Spectrum => {
+                let bands = self.visualizer.get_bands();
+                let width = rect.width() / bands.len().max(1) as f32;
 
-            let mut label = "Playlists".to_string();
+                for (band, level) in bands.iter().enumerate() {
+                    let left = rect.left() + band as f32 * width;
+                    let top = rect.bottom() - rect.height() * level;
 
-            if back { label = "Sources".to_string(); };
-            
-            columns[1].add_sized([columns[1].available_width(), 30.0], Label::new(label));
-            columns[2].add_enabled_ui(back, |ui| { self.playlist_back_button(ui); });
-        });
+                    painter.rect_filled(Rect::from_min_max(pos2(left + 1.0, top),
+                        pos2(left + width - 1.0, rect.bottom())), 1.0, fill);
 
-        ui.separator();
-        ui.vertical(|ui| { let _ = self.playlists_list(ctx, ui); });
-    }
-    
-    fn viewer(&mut self, ui: &mut Ui) { //TODO video playback
-        ui.add_space(8.5);
+                    painter.line_segment([pos2(left + 1.0, top), pos2(left + width - 1.0, top)],
+                        Stroke::new(2.0, accent));
+                };
+            },
 
-        ui.add_space(3.0);
+            VisualMode::Off => {}
+        };
     }
 
     //** UI - Scroll Areas **//
     fn catalog_list(&mut self, ui: &mut Ui) -> Result<()> {
+        if self.scanner.is_none() && !self.scan_cancelled {
+            if !self.pseudo_playlist.is_some() { let _ = self.load_sources(self.dir.clone()); };
+            if !self.sorted_playlist.is_some() { let _ = self.load_sources(self.dir.clone()); };
+        };
+
+        if self.dir.is_empty() {
+            self.roots_list(ui);
+
+            return Ok(());
+        };
+
+        if !filesys::exists(self.path.clone()) {
+            self.open_roots();
+
+            return Ok(());
+        };
+
         let mut entries = filesys::read_dir(self.path.clone())?.map(|res| res.map(|e| e.path()))
             .collect::<Result<Vec<_>>>()?;
 
         entries.sort();
 
-        if !self.pseudo_playlist.is_some() { let _ = self.load_sources(self.dir.clone()); };
-        if !self.sorted_playlist.is_some() { let _ = self.load_sources(self.dir.clone()); };
-
         ScrollArea::vertical().auto_shrink(false).id_salt("catalog").show(ui, |ui| {
-            if self.dir != "/" {
-                for entry in &entries {
-                    let file = entry.as_path().file_name().unwrap().display().to_string();
-
-                    let button = ui.add(Button::new(&file).frame(false).fill(Color32::TRANSPARENT)
-                        .truncate());
-                    
-                    if button.clicked() {
-                        if entry.as_path().is_file() {
-                            self.selected = entry.as_path().display().to_string();
-
-                            match self.play_state {
-                                PlayState::Paused | PlayState::Stopped => {
-                                    self.now_playing = self.selected.clone();
-                                    self.now_playinglist = self.pseudo_playlist.clone();
-                                    self.sorted_playlist = self.pseudo_playlist.clone();
-                                },
-
-                                PlayState::Playing => {}
-                            };
-                        } else {
-                            let new_path = entry.to_path_buf().display().to_string();
+            for entry in &entries {
+                let file = entry.as_path().file_name().unwrap().display().to_string();
+
+                let button = ui.add(Button::new(&file).frame(false).fill(Color32::TRANSPARENT)
+                    .truncate());
+                
+                if button.clicked() {
+                    if entry.as_path().is_file() {
+                        self.selected = entry.as_path().display().to_string();
+
+                        match self.play_state {
+                            PlayState::Paused | PlayState::Stopped => {
+                                self.now_playing = self.selected.clone();
+                                self.now_playinglist = self.pseudo_playlist.clone();
+                                self.sorted_playlist = self.pseudo_playlist.clone();
+                            },
+
+                            PlayState::Playing => {}
+                        };
+                    } else {
+                        let new_path = entry.to_path_buf().display().to_string();
 
-                            self.path = new_path.clone();
+                        self.path = new_path.clone();
 
-                            if new_path != self.dir.clone() { self.folders.push(new_path.clone()); };
+                        if new_path != self.dir.clone() { self.folders.push(new_path.clone()); };
 
-                            let _ = self.load_sources(new_path);
-                        };
+                        let _ = self.load_sources(new_path);
                     };
                 };
             };
@@ -1146,6 +3701,80 @@ impl Main {
         Ok(())
     }
 
+    fn episodes_list(&mut self, ui: &mut Ui, podcast: Podcast) {
+        ScrollArea::vertical().auto_shrink(false).id_salt("episodes").show(ui, |ui| {
+            for episode in podcast.get_episodes() {
+                ui.horizontal(|ui| {
+                    self.episode_play_button(ui, episode.clone());
+                    self.episode_download_button(ui, episode.clone());
+                    self.episode_played_button(ui, episode.clone());
+
+                    ui.add_space(5.5);
+
+                    ui.vertical(|ui| {
+                        let mut title = RichText::new(episode.get_title());
+
+                        if episode.is_played() { title = title.weak(); }
+                        else if self.podcasts.get_episode(&self.now_playing)
+                            .is_some_and(|playing| playing.get_url() == episode.get_url()) {
+                            title = title.color(styles::get_lyric_color());
+                        };
+
+                        ui.add(Label::new(title).truncate()).on_hover_text(episode.get_published());
+
+                        // Started episodes show how far in they are
+                        let position = episode.get_position();
+
+                        let progress = match episode.get_duration() {
+                            Some(total) if !position.is_zero() => format!("{} / {}",
+                                data::format_duration(position), data::format_duration(total)),
+                            None if !position.is_zero() => data::format_duration(position),
+                            Some(total) => data::format_duration(total),
+                            None => String::new()
+                        };
+
+                        if !progress.is_empty() {
+                            ui.label(RichText::new(progress).small().weak());
+                        };
+                    });
+                });
+            };
+        });
+    }
+
+    fn lyrics_list(&mut self, ui: &mut Ui) {
+        let lyrics = match &self.lyrics {
+            Some(lyrics) => lyrics.clone(),
+
+            None => {
+                ui.label("No lyrics found. Use Edit to paste and time-stamp them.");
+
+                return;
+            }
+        };
+
+        let current = if self.lyrics_editing { Some(self.lyrics_stamp) }
+            else { lyrics.get_current(self.get_elapsed_time()) };
+
+        let follow = current != self.lyrics_line;
+
+        self.lyrics_line = current;
+
+        ScrollArea::vertical().auto_shrink(false).id_salt("lyrics").show(ui, |ui| {
+            for (index, line) in lyrics.get_lines().iter().enumerate() {
+                let mut text = RichText::new(line.get_text()).size(24.0);
+
+                if Some(index) == current {
+                    text = text.color(styles::get_lyric_color()).strong();
+                } else if lyrics.is_synced() { text = text.weak(); };
+
+                let response = ui.vertical_centered(|ui| ui.label(text)).inner;
+
+                if follow && Some(index) == current { response.scroll_to_me(Some(Align::Center)); };
+            };
+        });
+    }
+
     fn playlists_list(&mut self, ctx: &Context, ui: &mut Ui) -> Result<()> {
         let entries = self.playlists.clone();
 
@@ -1177,13 +3806,22 @@ impl Main {
 
                                 ui.add_space(5.5);
 
-                                let mut title = data::get_title(Some(source.clone()));
+                                if let Some(texture) = self.artwork.get_thumbnail(ctx, &source) {
+                                    ui.add(Image::from_texture(SizedTexture::from_handle(&texture))
+                                        .fit_to_exact_size(Vec2::splat(24.0)));
+
+                                    ui.add_space(5.5);
+                                };
+
+                                let title = self.get_display_title(&source);
 
-                                if title == String::new() {
-                                    title = filesys::create_from_path(source.clone());
+                                let mut text = RichText::new(title);
+
+                                if self.missing.contains(&source) {
+                                    text = text.color(styles::get_missing_color()).strikethrough();
                                 };
 
-                                let button = ui.add(Button::new(title).frame(false)
+                                let button = ui.add(Button::new(text).frame(false)
                                     .fill(Color32::TRANSPARENT).truncate());
 
                                 if button.clicked() { self.selected = source; };
@@ -1217,30 +3855,163 @@ impl Main {
         Ok(())
     }
 
+    fn podcasts_list(&mut self, ui: &mut Ui) {
+        let podcasts = self.podcasts.get_podcasts();
+
+        ScrollArea::vertical().auto_shrink(false).id_salt("podcasts").show(ui, |ui| {
+            for podcast in podcasts {
+                ui.horizontal(|ui| {
+                    self.podcast_remove_button(ui, podcast.get_url());
+
+                    ui.add_space(5.5);
+
+                    let unplayed = podcast.get_unplayed();
+                    let mut text = RichText::new(podcast.get_title());
+
+                    if unplayed > 0 { text = text.strong(); };
+
+                    let button = ui.add(Button::new(text).frame(false)
+                        .fill(Color32::TRANSPARENT).truncate())
+                        .on_hover_text(format!("{} unplayed", unplayed));
+
+                    if button.clicked() { self.active_podcast = Some(podcast.get_url()); };
+                });
+            };
+        });
+    }
+
+    fn roots_list(&mut self, ui: &mut Ui) {
+        let roots = self.roots.clone();
+
+        ScrollArea::vertical().auto_shrink(false).id_salt("roots").show(ui, |ui| {
+            for (index, root) in roots.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    self.root_remove_button(ui, index);
+
+                    ui.add_space(5.5);
+
+                    let mut enabled = root.is_enabled();
+
+                    if ui.checkbox(&mut enabled, "").changed() {
+                        self.toggle_root(index, enabled);
+                    };
+
+                    let mounted = self.mounted.contains(&root.get_path());
+                    let mut text = RichText::new(root.get_name());
+
+                    if !mounted { text = text.color(styles::get_missing_color()).italics(); };
+
+                    let button = ui.add_enabled(enabled && mounted, Button::new(text).frame(false)
+                        .fill(Color32::TRANSPARENT).truncate()).on_hover_text(root.get_path());
+
+                    if button.clicked() { self.open_root(root.get_path()); };
+                });
+            };
+        });
+    }
+
+    fn stations_list(&mut self, ui: &mut Ui) {
+        let stations = self.stations.clone();
+
+        ScrollArea::vertical().auto_shrink(false).id_salt("stations").show(ui, |ui| {
+            for (index, station) in stations.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    self.station_remove_button(ui, index);
+
+                    ui.add_space(5.5);
+
+                    self.station_play_button(ui, station.clone());
+
+                    ui.add_space(5.5);
+
+                    let mut text = RichText::new(station.get_name());
+
+                    if self.now_playing == station.get_url() {
+                        text = text.color(styles::get_lyric_color());
+                    };
+
+                    ui.add(Label::new(text).truncate()).on_hover_text(station.get_url());
+                });
+            };
+        });
+    }
+
     //** UI - Sliders **//
     fn tracking(&mut self, ui: &mut Ui) {
-        ui.label(RichText::new(data::format_duration(self.get_elapsed_time())).size(16.0));
+        let elapsed = self.seek_preview.unwrap_or(self.get_elapsed_time());
+
+        ui.label(RichText::new(data::format_duration(elapsed)).size(16.0));
 
-        let mut slider_value = self.elapsed_time.as_secs_f32();
+        // Seeking needs a known length, so unknown durations leave the bar read-only
+        let sense = if self.total_time.is_some() { Sense::click_and_drag() }
+            else { Sense::hover() };
+        let size = Vec2::new(ui.spacing().slider_width, 24.0);
+        let (rect, response) = ui.allocate_exact_size(size, sense);
 
-        let slider = Slider::new(&mut slider_value, 0.0..=self.total_time.as_secs_f32())
-            .show_value(false);
+        let total = self.total_time.unwrap_or_default().as_secs_f32();
+        let pointed = response.interact_pointer_pos().map(|pointer| {
+            let fraction = ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
 
-        let slider_response = ui.add(slider);
+            Duration::from_secs_f32(fraction * total)
+        });
 
-        if slider_response.drag_started() {
-            self.play_state = PlayState::Paused;
+        if response.dragged() { self.seek_preview = pointed; };
 
-            self.pause();
+        if response.drag_stopped() {
+            if let Some(position) = self.seek_preview.take().or(pointed) { self.seek(position); };
+        } else if response.clicked() {
+            if let Some(position) = pointed { self.seek(position); };
         };
 
-        if slider_response.dragged() {
-            self.elapsed_time = Duration::from_secs_f32(slider_value);
+        if ui.is_rect_visible(rect) {
+            let painter = ui.painter_at(rect);
+            let progress = if total > 0.0 { (elapsed.as_secs_f32() / total).min(1.0) } else { 0.0 };
+            let playhead = rect.left() + rect.width() * progress;
+            let (middle, half) = (rect.center().y, rect.height() / 2.0);
+
+            painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+            match self.library.get_waveform(&self.now_playing) {
+                Some(waveform) => {
+                    for step in 0..(rect.width() / 2.0) as i32 {
+                        let x = rect.left() + step as f32 * 2.0 + 1.0;
+                        let position = (x - rect.left()) / rect.width();
+
+                        let color = if x <= playhead { styles::get_button_fill() }
+                            else { Color32::GRAY };
+
+                        let peak = waveform.get_peak(position).max(0.02) * half;
+                        let rms = waveform.get_rms(position) * half;
+
+                        painter.line_segment([pos2(x, middle - peak), pos2(x, middle + peak)],
+                            Stroke::new(1.0, color.gamma_multiply(0.5)));
+
+                        painter.line_segment([pos2(x, middle - rms), pos2(x, middle + rms)],
+                            Stroke::new(1.5, color));
+                    };
+                },
+
+                None => {
+                    painter.line_segment([pos2(rect.left(), middle), pos2(rect.right(), middle)],
+                        Stroke::new(2.0, Color32::GRAY));
+
+                    painter.line_segment([pos2(rect.left(), middle), pos2(playhead, middle)],
+                        Stroke::new(2.0, styles::get_button_fill()));
+                }
+            };
+
+            if total > 0.0 {
+                painter.line_segment([pos2(playhead, rect.top()), pos2(playhead, rect.bottom())],
+                    Stroke::new(1.5, Color32::WHITE));
+            };
+        };
 
-            if self.now_playing != String::new() { self.play(); };
+        let total_label = match self.total_time {
+            Some(total_time) => data::format_duration(total_time),
+            None => "--:--".to_string()
         };
 
-        ui.label(RichText::new(data::format_duration(self.total_time)).size(16.0));
+        ui.label(RichText::new(total_label).size(16.0));
     }
 
     fn volume(&mut self, ui: &mut Ui) {
@@ -1274,6 +4045,15 @@ impl App for Main {
 
         styles::set_styles(ctx);
 
+        self.podcasts.poll();
+        self.poll_roots();
+        self.poll_scanner();
+        self.poll_watcher();
+        self.handle_remote();
+        self.publish_now_playing();
+        self.publish_state();
+        self.publish_title();
+
         self.big_player(ctx);
         self.mini_player(ctx);
 
@@ -1293,10 +4073,36 @@ impl App for Main {
         if self.playlist_add_open {
             TopBottomPanel::bottom("add_edit").frame(frame).resizable(false).min_height(height)
                 .max_height(height).show(ctx, |ui| { self.add_playlist(ui); });
+        } else if self.feed_open {
+            TopBottomPanel::bottom("feed").frame(frame).resizable(false).min_height(height)
+                .max_height(height).show(ctx, |ui| { self.add_podcast(ui); });
+        } else if self.url_open {
+            TopBottomPanel::bottom("url").frame(frame).resizable(false).min_height(height)
+                .max_height(height).show(ctx, |ui| { self.open_url(ui); });
+        } else if self.server_open {
+            TopBottomPanel::bottom("server").frame(frame).resizable(false).min_height(height)
+                .max_height(height).show(ctx, |ui| { self.server_settings(ui); });
+        } else if self.broadcast_open {
+            TopBottomPanel::bottom("broadcast").frame(frame).resizable(false).min_height(height)
+                .max_height(height).show(ctx, |ui| { self.broadcast_settings(ui); });
+        } else if self.remote_open {
+            TopBottomPanel::bottom("remote").frame(frame).resizable(false).min_height(height)
+                .max_height(height).show(ctx, |ui| { self.remote_settings(ui); });
+        } else if self.mpd_open {
+            TopBottomPanel::bottom("mpd").frame(frame).resizable(false).min_height(height)
+                .max_height(height).show(ctx, |ui| { self.mpd_settings(ui); });
         };
 
         if self.playlist_edit_open {
             CentralPanel::default().frame(frame).show(ctx, |ui| { self.edit_playlist(ui); });
+        } else if self.batch_editor_open {
+            CentralPanel::default().frame(frame).show(ctx, |ui| { self.batch_editor(ui); });
+        } else if self.organizer_open {
+            CentralPanel::default().frame(frame).show(ctx, |ui| { self.organizer(ui); });
+        } else if self.tag_editor_open {
+            CentralPanel::default().frame(frame).show(ctx, |ui| { self.tag_editor(ui); });
+        } else if self.lyrics_open {
+            CentralPanel::default().frame(frame).show(ctx, |ui| { self.lyrics_view(ui); });
         } else { CentralPanel::default().frame(frame).show(ctx, |ui| { self.viewer(ui); }); };
     }
 }
@@ -1304,10 +4110,23 @@ impl App for Main {
 struct Main {
     // Booleans
     is_shuffled: bool,
+    batch_editor_open: bool,
     big_player_open: bool,
+    broadcast_open: bool,
+    lyrics_editing: bool,
+    lyrics_open: bool,
     mini_player_open: bool,
+    mounts_probed: bool,
+    mpd_open: bool,
+    organizer_open: bool,
     playlist_add_open: bool,
     playlist_edit_open: bool,
+    feed_open: bool,
+    remote_open: bool,
+    server_open: bool,
+    tag_editor_open: bool,
+    url_open: bool,
+    scan_cancelled: bool,
     start_playback: bool,
     stop_playback: bool,
 
@@ -1319,34 +4138,95 @@ struct Main {
     dir: String,
     path: String,
     folders: Vec<String>,
+    mounted: HashSet<String>,
+    mount_probe: Option<Receiver<HashSet<String>>>,
+    pseudo_roots: Vec<String>,
+    roots: Vec<Root>,
 
     // Files
+    artwork: Artwork,
+    broadcast: Broadcast,
+    library: Library,
+    mpd: Mpd,
+    #[cfg(target_os = "linux")]
+    mpris: Mpris,
     playback: Arc<Mutex<Playback>>,
+    podcasts: Podcasts,
+    remote: Remote,
+    merge_scanners: Vec<Scanner>,
+    scanner: Option<Scanner>,
+    server: Server,
+    subtitles: Option<Subtitles>,
+    video: Option<Video>,
+    video_texture: Option<TextureHandle>,
+    visualizer: Visualizer,
+    watcher: Option<Watcher>,
 
     // Misc.
     volume: i32,
+    batch: Option<Batch>,
+    batch_message: String,
+    batch_undo: Vec<Vec<(String, Tags)>>,
+    broadcast_host: String,
+    broadcast_mount: String,
+    broadcast_password: String,
+    broadcast_port: String,
+    broadcast_user: String,
+    feed_input: String,
+    lyrics: Option<Lyrics>,
+    lyrics_draft: String,
+    lyrics_line: Option<usize>,
+    lyrics_message: String,
+    lyrics_stamp: usize,
+    mpd_password: String,
+    mpd_port: String,
+    organize_message: String,
+    organize_moves: Vec<Move>,
+    organize_pattern: String,
+    organize_retags: Vec<Retag>,
+    remote_port: String,
+    server_port: String,
+    subtitle_offset: i64,
+    subtitle_size: f32,
     temp_playlist_name: String,
+    tag_edit: Tags,
+    tag_message: String,
+    tag_original: Tags,
+    url_input: String,
+    url_name: String,
 
     // Playlists
     active_playlist: Option<Playlist>,
+    active_podcast: Option<String>,
     edited_playlist: Option<Playlist>,
     now_playinglist: Option<Playlist>,
     pseudo_playlist: Option<Playlist>,
     sorted_playlist: Option<Playlist>,
     playlists: Vec<Playlist>,
+    stations: Vec<Station>,
 
     // Sources
+    missing: HashSet<String>,
+    lyrics_source: String,
     now_playing: String,
     selected: String,
+    tag_source: String,
+    video_source: String,
 
     // States
+    broadcast_format: Format,
+    list_mode: ListMode,
+    organize_mode: OrganizeMode,
     play_state: PlayState,
     repeat_state: RepeatState,
     
     // Time
     elapsed_time: Duration,
     start_time: Duration,
-    total_time: Duration,
+    total_time: Option<Duration>,
+    seek_preview: Option<Duration>,
+    podcast_saved: Instant,
+    roots_checked: Instant,
     stopwatch_instant: Option<Instant>
 }
 
@@ -1373,6 +4253,8 @@ pub fn main() {
 
     let _ = filesys::create_config();
     let _ = filesys::create_playlists();
+    let _ = filesys::create_roots();
+    let _ = filesys::create_stations();
 
     let _ = run_native(&name, options, Box::new(|cc| {
         install_image_loaders(&cc.egui_ctx);
@@ -0,0 +1,159 @@
+use std::error::Error;
+
+use crate::utils::data::{self, TagField, Tags};
+
+impl Batch {
+    //** Getters **//
+    pub fn get_edit_mut(&mut self) -> &mut Tags {
+        &mut self.edit
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_sources(&self) -> Vec<String> {
+        self.originals.iter().map(|(source, _tags)| source.clone()).collect()
+    }
+
+    pub fn is_kept(&self, field: TagField) -> bool {
+        self.keep[Self::index(field)]
+    }
+
+    pub fn keep_mut(&mut self, field: TagField) -> &mut bool {
+        &mut self.keep[Self::index(field)]
+    }
+
+    pub fn len(&self) -> usize {
+        self.originals.len()
+    }
+
+    fn index(field: TagField) -> usize {
+        TagField::ALL.iter().position(|entry| *entry == field).unwrap_or(0)
+    }
+
+    //** Misc. **//
+    pub fn apply(&mut self) -> Result<(Vec<(String, Tags)>, Vec<String>), Box<dyn Error>> {
+        self.edit.validate()?;
+
+        let mut errors: Vec<String> = Vec::new();
+        let mut step: Vec<(String, Tags)> = Vec::new();
+
+        for (source, before) in self.originals.clone() {
+            let after = self.merge(&before);
+
+            if after == before { continue; };
+
+            match data::write_tags(&source, &after) {
+                Ok(()) => step.push((source, before)),
+                Err(error) => errors.push(format!("{}: {}", source, error))
+            };
+        };
+
+        self.reload();
+
+        Ok((step, errors))
+    }
+
+    fn merge(&self, before: &Tags) -> Tags {
+        let mut after = before.clone();
+
+        for field in TagField::ALL {
+            if !self.is_kept(field) { after.set(field, self.edit.get(field)); };
+        };
+
+        after
+    }
+
+    pub fn new(name: String, sources: Vec<String>) -> Self {
+        let mut batch = Self {
+            // Arrays
+            keep: [true; 10],
+            originals: sources.into_iter().map(|source| (source, Tags::new())).collect(),
+
+            // Misc.
+            edit: Tags::new(),
+            name
+        };
+
+        batch.reload();
+
+        batch
+    }
+
+    pub fn preview(&self) -> Vec<Edit> {
+        let mut edits: Vec<Edit> = Vec::new();
+
+        for (source, before) in &self.originals {
+            let after = self.merge(before);
+
+            for field in TagField::ALL {
+                if before.get(field) != after.get(field) {
+                    edits.push(Edit {
+                        after: after.get(field),
+                        before: before.get(field),
+                        field,
+                        source: source.clone()
+                    });
+                };
+            };
+        };
+
+        edits
+    }
+
+    pub fn reload(&mut self) {
+        for (source, tags) in &mut self.originals {
+            *tags = data::read_tags(source).unwrap_or_default();
+        };
+    }
+}
+
+impl Edit {
+    //** Getters **//
+    pub fn get_after(&self) -> String {
+        self.after.clone()
+    }
+
+    pub fn get_before(&self) -> String {
+        self.before.clone()
+    }
+
+    pub fn get_field(&self) -> TagField {
+        self.field
+    }
+
+    pub fn get_source(&self) -> String {
+        self.source.clone()
+    }
+}
+
+pub fn undo(step: Vec<(String, Tags)>) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+
+    for (source, tags) in step {
+        if let Err(error) = data::write_tags(&source, &tags) {
+            errors.push(format!("{}: {}", source, error));
+        };
+    };
+
+    errors
+}
+
+pub struct Batch {
+    // Arrays
+    keep: [bool; 10],
+    originals: Vec<(String, Tags)>,
+
+    // Misc.
+    edit: Tags,
+    name: String
+}
+
+#[derive(Clone, Debug)]
+pub struct Edit {
+    after: String,
+    before: String,
+    field: TagField,
+    source: String
+}
\ No newline at end of file
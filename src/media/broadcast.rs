@@ -0,0 +1,213 @@
+use std::{
+    error::Error,
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
+    thread,
+    time::Duration
+};
+
+use crate::{
+    media::{
+        encoder,
+        source::Mount,
+        stream::StreamStatus,
+        tap::Outputs
+    },
+    utils::web
+};
+
+const AGENT: &str = "ComRad";
+const RETRIES: u64 = 5;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+impl Broadcast {
+    //** Getters **//
+    pub fn get_mount(&self) -> Mount {
+        self.mount.clone()
+    }
+
+    pub fn get_status(&self) -> StreamStatus {
+        self.status.lock().map(|status| status.clone()).unwrap_or(StreamStatus::Idle)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.as_ref().is_some_and(|running| running.load(Ordering::SeqCst))
+    }
+
+    //** Misc. **//
+    pub fn new(outputs: Arc<Mutex<Outputs>>, mount: Mount) -> Self {
+        Self {
+            // Settings
+            mount,
+
+            // Shared
+            outputs,
+            status: Arc::new(Mutex::new(StreamStatus::Idle)),
+            title: Arc::new(Mutex::new(String::new())),
+
+            // Thread
+            running: None
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.stop();
+
+        // Each connection reports through its own status, so a stopped one cannot overwrite it
+        self.status = Arc::new(Mutex::new(StreamStatus::Connecting));
+
+        let mount = self.mount.clone();
+        let outputs = self.outputs.clone();
+        let running = Arc::new(AtomicBool::new(true));
+        let status = self.status.clone();
+        let title = self.title.clone();
+
+        self.running = Some(running.clone());
+
+        thread::spawn(move || {
+            let mut attempt = 0;
+
+            while running.load(Ordering::SeqCst) {
+                let error = match connect(&mount) {
+                    Ok(mut stream) => {
+                        attempt = 0;
+
+                        set_status(&status, StreamStatus::Streaming);
+
+                        // A fresh connection starts without a title until one is sent
+                        let current = title.lock().map(|title| title.clone()).unwrap_or_default();
+
+                        if !current.is_empty() { update_title(mount.clone(), current); };
+
+                        match encoder::stream(&mut stream, mount.get_format(), &outputs, &running) {
+                            Ok(_) => break,
+                            Err(error) => error.to_string()
+                        }
+                    },
+
+                    // Bad credentials or a taken mount will not fix themselves
+                    Err(error) if error.kind() == ErrorKind::PermissionDenied => {
+                        set_status(&status, StreamStatus::Failed(error.to_string()));
+                        running.store(false, Ordering::SeqCst);
+
+                        return;
+                    },
+
+                    Err(error) => error.to_string()
+                };
+
+                attempt += 1;
+
+                if attempt > RETRIES {
+                    set_status(&status, StreamStatus::Failed(error));
+                    running.store(false, Ordering::SeqCst);
+
+                    return;
+                };
+
+                set_status(&status, StreamStatus::Reconnecting);
+
+                thread::sleep(Duration::from_secs(attempt));
+            };
+
+            set_status(&status, StreamStatus::Idle);
+        });
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(running) = self.running.take() { running.store(false, Ordering::SeqCst); };
+
+        set_status(&self.status, StreamStatus::Idle);
+    }
+
+    //** Setters **//
+    pub fn set_mount(&mut self, mount: Mount) {
+        self.mount = mount;
+    }
+
+    pub fn set_title(&mut self, next: String) {
+        if let Ok(mut title) = self.title.lock() {
+            if *title == next { return; }
+
+            *title = next.clone();
+        };
+
+        if self.is_running() && !next.is_empty() { update_title(self.mount.clone(), next); };
+    }
+}
+
+impl Drop for Broadcast {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn connect(mount: &Mount) -> io::Result<TcpStream> {
+    let address = (mount.get_host().as_str(), mount.get_port()).to_socket_addrs()?.next()
+        .ok_or(io::Error::new(ErrorKind::NotFound, "Unknown host"))?;
+
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    // Icecast answers PUT with 100 Continue before it takes any audio
+    write!(stream, concat!("PUT {} HTTP/1.1\r\nHost: {}:{}\r\nAuthorization: Basic {}\r\n",
+        "User-Agent: {}\r\nContent-Type: {}\r\nIce-Name: {}\r\nIce-Public: 0\r\n",
+        "Expect: 100-continue\r\n\r\n"), mount.get_mount(), mount.get_host(), mount.get_port(),
+        credentials(mount), AGENT, mount.get_format().get_content_type(), AGENT)?;
+
+    let mut line = String::new();
+
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let code = line.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok());
+
+    match code {
+        Some(100) | Some(200) => Ok(stream),
+        Some(401) | Some(403) => Err(io::Error::new(ErrorKind::PermissionDenied,
+            format!("Mount refused the source ({})", line.trim()))),
+        Some(_) => Err(io::Error::other(format!("Server error ({})", line.trim()))),
+        None => Err(io::Error::new(ErrorKind::InvalidData, "No answer from the server"))
+    }
+}
+
+fn credentials(mount: &Mount) -> String {
+    web::base64(format!("{}:{}", mount.get_user(), mount.get_password()).as_bytes())
+}
+
+fn send_title(mount: &Mount, title: &str) -> Result<(), Box<dyn Error>> {
+    let agent = ureq::AgentBuilder::new().timeout_connect(TIMEOUT).timeout_read(TIMEOUT).build();
+    let url = format!("http://{}:{}/admin/metadata", mount.get_host(), mount.get_port());
+
+    agent.get(&url).query("mount", &mount.get_mount()).query("mode", "updinfo")
+        .query("song", title).set("Authorization", &format!("Basic {}", credentials(mount)))
+        .set("User-Agent", AGENT).call()?;
+
+    Ok(())
+}
+
+fn set_status(status: &Arc<Mutex<StreamStatus>>, next: StreamStatus) {
+    if let Ok(mut status) = status.lock() { *status = next; };
+}
+
+fn update_title(mount: Mount, title: String) {
+    if !mount.get_format().has_titles() { return; }
+
+    // Title updates go through the admin interface, which must not hold up the audio
+    thread::spawn(move || { let _ = send_title(&mount, &title); });
+}
+
+pub struct Broadcast {
+    // Settings
+    mount: Mount,
+
+    // Shared
+    outputs: Arc<Mutex<Outputs>>,
+    status: Arc<Mutex<StreamStatus>>,
+    title: Arc<Mutex<String>>,
+
+    // Thread
+    running: Option<Arc<AtomicBool>>
+}
\ No newline at end of file
@@ -0,0 +1,499 @@
+use ffmpeg_next::{
+    self as ffmpeg,
+    ChannelLayout,
+    Packet,
+    codec::{self, encoder::audio::Encoder as AudioEncoder},
+    format::{Sample, sample::Type},
+    frame::Audio as AudioFrame
+};
+
+use rand::{Rng, rng};
+
+use std::{
+    error::Error,
+    io::Write,
+    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}, mpsc::RecvTimeoutError},
+    time::Duration
+};
+
+use crate::media::tap::Outputs;
+
+pub const CHANNELS: u16 = 2;
+pub const SAMPLE_RATE: u32 = 48000;
+
+const BIT_RATE: usize = 192_000;
+const FLAC_BLOCK: usize = 4608;
+const OPUS_PRE_SKIP: u16 = 312;
+const VENDOR: &str = "ComRad";
+
+// Paused or stopped playback is filled with silence so listeners stay connected
+const SILENCE: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format { Flac, Mp3, Opus }
+
+impl Encoder {
+    //** Misc. **//
+    fn drain(&mut self, output: &mut Vec<u8>) {
+        let mut packet = Packet::empty();
+
+        while self.encoder.receive_packet(&mut packet).is_ok() {
+            let Some(data) = packet.data() else { continue };
+
+            match self.format {
+                Format::Opus => {
+                    self.granule += packet.duration().max(0) as u64;
+
+                    output.extend(self.ogg.page(data, self.granule, 0));
+                },
+
+                _ => output.extend_from_slice(data)
+            };
+        };
+    }
+
+    pub fn encode(&mut self, samples: &[f32], channels: u16, sample_rate: u32)
+        -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut output = Vec::new();
+
+        self.resampler.process(samples, channels, sample_rate, &mut self.pending);
+
+        let length = self.frame_size * CHANNELS as usize;
+
+        while self.pending.len() >= length {
+            let block = self.pending.drain(..length).collect::<Vec<_>>();
+            let frame = self.frame(&block);
+
+            self.encoder.send_frame(&frame)?;
+            self.drain(&mut output);
+        };
+
+        Ok(output)
+    }
+
+    pub fn finish(&mut self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut output = Vec::new();
+
+        if !self.pending.is_empty() {
+            let block = std::mem::take(&mut self.pending);
+            let frame = self.frame(&block);
+
+            self.encoder.send_frame(&frame)?;
+        };
+
+        self.encoder.send_eof()?;
+        self.drain(&mut output);
+
+        Ok(output)
+    }
+
+    fn frame(&mut self, samples: &[f32]) -> AudioFrame {
+        let count = samples.len() / CHANNELS as usize;
+        let mut frame = AudioFrame::new(self.format.get_sample_format(), count,
+            ChannelLayout::STEREO);
+
+        frame.set_rate(SAMPLE_RATE);
+        frame.set_pts(Some(self.pts));
+
+        self.pts += count as i64;
+
+        match self.format {
+            Format::Flac => {
+                let data = frame.data_mut(0);
+
+                for (index, sample) in samples.iter().enumerate() {
+                    let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+
+                    data[index * 2..index * 2 + 2].copy_from_slice(&value.to_ne_bytes());
+                };
+            },
+
+            Format::Mp3 => {
+                for channel in 0..CHANNELS as usize {
+                    let plane = frame.plane_mut::<f32>(channel);
+
+                    for (index, value) in plane.iter_mut().enumerate().take(count) {
+                        *value = samples[index * CHANNELS as usize + channel];
+                    };
+                };
+            },
+
+            Format::Opus => {
+                let data = frame.data_mut(0);
+
+                for (index, sample) in samples.iter().enumerate() {
+                    data[index * 4..index * 4 + 4].copy_from_slice(&sample.to_ne_bytes());
+                };
+            }
+        };
+
+        frame
+    }
+
+    pub fn header(&mut self) -> Vec<u8> {
+        match self.format {
+            Format::Flac => stream_info(self.frame_size),
+            Format::Mp3 => Vec::new(),
+
+            Format::Opus => {
+                let mut head = b"OpusHead".to_vec();
+
+                head.push(1);
+                head.push(CHANNELS as u8);
+                head.extend_from_slice(&OPUS_PRE_SKIP.to_le_bytes());
+                head.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+                head.extend_from_slice(&[0, 0, 0]);
+
+                let mut tags = b"OpusTags".to_vec();
+
+                tags.extend_from_slice(&(VENDOR.len() as u32).to_le_bytes());
+                tags.extend_from_slice(VENDOR.as_bytes());
+                tags.extend_from_slice(&0u32.to_le_bytes());
+
+                let mut header = self.ogg.page(&head, 0, 0x02);
+
+                header.extend(self.ogg.page(&tags, 0, 0));
+
+                header
+            }
+        }
+    }
+
+    pub fn new(format: Format) -> Result<Self, Box<dyn Error>> {
+        ffmpeg::init()?;
+
+        let codec = ffmpeg::encoder::find_by_name(format.get_codec())
+            .ok_or(format!("{} encoder is not available", format.get_label()))?;
+
+        let mut encoder = codec::context::Context::new_with_codec(codec).encoder().audio()?;
+
+        encoder.set_rate(SAMPLE_RATE as i32);
+        encoder.set_channel_layout(ChannelLayout::STEREO);
+        encoder.set_format(format.get_sample_format());
+        encoder.set_bit_rate(BIT_RATE);
+        encoder.set_time_base((1, SAMPLE_RATE as i32));
+
+        let encoder = encoder.open_as(codec)?;
+
+        // FLAC takes any block size, the lossy codecs report the one they need
+        let frame_size = match encoder.frame_size() {
+            0 => FLAC_BLOCK,
+            size => size as usize
+        };
+
+        Ok(Self {
+            encoder,
+            format,
+            frame_size,
+            granule: 0,
+            ogg: Ogg { sequence: 0, serial: rng().random() },
+            pending: Vec::new(),
+            pts: 0,
+            resampler: Resampler::new()
+        })
+    }
+
+    pub fn silence(&mut self, duration: Duration) -> Result<Vec<u8>, Box<dyn Error>> {
+        let count = (duration.as_secs_f64() * SAMPLE_RATE as f64) as usize * CHANNELS as usize;
+
+        self.encode(&vec![0.0; count], CHANNELS, SAMPLE_RATE)
+    }
+}
+
+impl Format {
+    //** Getters **//
+    fn get_codec(&self) -> &'static str {
+        match self {
+            Format::Flac => "flac",
+            Format::Mp3 => "libmp3lame",
+            Format::Opus => "libopus"
+        }
+    }
+
+    pub fn get_content_type(&self) -> &'static str {
+        match self {
+            Format::Flac => "audio/flac",
+            Format::Mp3 => "audio/mpeg",
+            Format::Opus => "audio/ogg"
+        }
+    }
+
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            Format::Flac => "FLAC",
+            Format::Mp3 => "MP3",
+            Format::Opus => "Opus"
+        }
+    }
+
+    fn get_sample_format(&self) -> Sample {
+        match self {
+            Format::Flac => Sample::I16(Type::Packed),
+            Format::Mp3 => Sample::F32(Type::Planar),
+            Format::Opus => Sample::F32(Type::Packed)
+        }
+    }
+
+    //** Misc. **//
+    pub fn all() -> [Format; 3] {
+        [Format::Mp3, Format::Opus, Format::Flac]
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::all().into_iter().find(|format| format.get_label().eq_ignore_ascii_case(label.trim()))
+    }
+
+    // Icecast only takes admin title updates for non-Ogg streams
+    pub fn has_titles(&self) -> bool {
+        !matches!(self, Format::Opus)
+    }
+}
+
+impl Ogg {
+    fn page(&mut self, packet: &[u8], granule: u64, flags: u8) -> Vec<u8> {
+        let mut segments = vec![255u8; packet.len() / 255];
+
+        segments.push((packet.len() % 255) as u8);
+
+        let mut page = Vec::with_capacity(27 + segments.len() + packet.len());
+
+        page.extend_from_slice(b"OggS");
+        page.push(0);
+        page.push(flags);
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&self.serial.to_le_bytes());
+        page.extend_from_slice(&self.sequence.to_le_bytes());
+        page.extend_from_slice(&[0; 4]);
+        page.push(segments.len() as u8);
+        page.extend_from_slice(&segments);
+        page.extend_from_slice(packet);
+
+        let checksum = crc32(&page);
+
+        page[22..26].copy_from_slice(&checksum.to_le_bytes());
+
+        self.sequence += 1;
+
+        page
+    }
+}
+
+impl Resampler {
+    fn new() -> Self {
+        Self {
+            channels: CHANNELS,
+            position: 0.0,
+            previous: [0.0; 2],
+            sample_rate: SAMPLE_RATE
+        }
+    }
+
+    fn process(&mut self, samples: &[f32], channels: u16, sample_rate: u32,
+        output: &mut Vec<f32>) {
+        if channels == 0 || sample_rate == 0 { return; }
+
+        // A new track with a different layout starts interpolating from scratch
+        if channels != self.channels || sample_rate != self.sample_rate {
+            *self = Self { channels, sample_rate, ..Self::new() };
+        };
+
+        let frames = samples.chunks_exact(channels as usize)
+            .map(|frame| [frame[0], *frame.get(1).unwrap_or(&frame[0])])
+            .collect::<Vec<_>>();
+
+        let Some(last) = frames.last().copied() else { return };
+
+        if sample_rate == SAMPLE_RATE {
+            output.extend(frames.iter().flatten());
+
+            return;
+        };
+
+        // Index 0 is the last frame of the previous chunk, so interpolation spans chunk edges
+        let step = sample_rate as f64 / SAMPLE_RATE as f64;
+
+        while self.position < frames.len() as f64 {
+            let index = self.position.floor() as usize;
+            let fraction = (self.position - index as f64) as f32;
+            let start = if index == 0 { self.previous } else { frames[index - 1] };
+            let end = frames[index];
+
+            for channel in 0..2 {
+                output.push(start[channel] + (end[channel] - start[channel]) * fraction);
+            };
+
+            self.position += step;
+        };
+
+        self.position -= frames.len() as f64;
+        self.previous = last;
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut checksum = 0u32;
+
+    for byte in data {
+        checksum ^= (*byte as u32) << 24;
+
+        for _ in 0..8 {
+            checksum = if checksum & 0x8000_0000 != 0 { (checksum << 1) ^ 0x04c1_1db7 }
+                else { checksum << 1 };
+        };
+    };
+
+    checksum
+}
+
+pub fn stream(writer: &mut impl Write, format: Format, outputs: &Arc<Mutex<Outputs>>,
+    running: &AtomicBool) -> Result<(), Box<dyn Error>> {
+    let mut encoder = Encoder::new(format)?;
+    let receiver = outputs.lock().map_err(|_| "Output unavailable")?.subscribe();
+
+    writer.write_all(&encoder.header())?;
+
+    while running.load(Ordering::SeqCst) {
+        let bytes = match receiver.recv_timeout(SILENCE) {
+            Ok(chunk) => encoder.encode(chunk.get_samples(), chunk.get_channels(),
+                chunk.get_sample_rate())?,
+
+            Err(RecvTimeoutError::Timeout) => encoder.silence(SILENCE)?,
+            Err(RecvTimeoutError::Disconnected) => break
+        };
+
+        if !bytes.is_empty() { writer.write_all(&bytes)?; };
+    };
+
+    Ok(())
+}
+
+fn stream_info(block: usize) -> Vec<u8> {
+    let mut info = b"fLaC".to_vec();
+
+    // A single STREAMINFO block with unknown frame sizes, length and checksum
+    info.extend_from_slice(&[0x80, 0, 0, 34]);
+    info.extend_from_slice(&(block as u16).to_be_bytes());
+    info.extend_from_slice(&(block as u16).to_be_bytes());
+    info.extend_from_slice(&[0; 6]);
+
+    let packed = (SAMPLE_RATE as u64) << 44 | (CHANNELS as u64 - 1) << 41 | 15 << 36;
+
+    info.extend_from_slice(&packed.to_be_bytes());
+    info.extend_from_slice(&[0; 16]);
+
+    info
+}
+
+pub struct Encoder {
+    encoder: AudioEncoder,
+    format: Format,
+    frame_size: usize,
+    granule: u64,
+    ogg: Ogg,
+    pending: Vec<f32>,
+    pts: i64,
+    resampler: Resampler
+}
+
+struct Ogg {
+    sequence: u32,
+    serial: u32
+}
+
+struct Resampler {
+    channels: u16,
+    position: f64,
+    previous: [f32; 2],
+    sample_rate: u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_like_ogg() {
+        // The Ogg CRC is CRC-32/CKSUM without the final inversion
+        assert_eq!(crc32(b"123456789"), 0x89a1_897f);
+    }
+
+    #[test]
+    fn writes_known_ogg_pages() {
+        let mut ogg = Ogg { sequence: 0, serial: 0x0102_0304 };
+
+        assert_eq!(ogg.page(b"hello", 0, 0x02), vec![
+            0x4f, 0x67, 0x67, 0x53, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x04, 0x03, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0xae, 0x09, 0xf6, 0x13, 0x01, 0x05,
+            0x68, 0x65, 0x6c, 0x6c, 0x6f
+        ]);
+
+        // Packets of exactly 255 bytes need a terminating zero lacing value
+        let page = ogg.page(&[7; 510], 960, 0);
+
+        assert_eq!(&page[18..22], &1u32.to_le_bytes());
+        assert_eq!(&page[6..14], &960u64.to_le_bytes());
+        assert_eq!(&page[26..30], &[3, 255, 255, 0]);
+        assert_eq!(page.len(), 27 + 3 + 510);
+
+        let segments = ogg.page(&[7; 600], 0, 0);
+
+        assert_eq!(&segments[26..30], &[3, 255, 255, 90]);
+
+        let mut blank = segments.clone();
+
+        blank[22..26].copy_from_slice(&[0; 4]);
+
+        assert_eq!(&segments[22..26], &crc32(&blank).to_le_bytes());
+    }
+
+    #[test]
+    fn lays_out_stream_info() {
+        let info = stream_info(FLAC_BLOCK);
+
+        assert_eq!(info.len(), 4 + 4 + 34);
+        assert_eq!(&info[..4], b"fLaC");
+
+        // Last metadata block, type STREAMINFO, 34 bytes long
+        assert_eq!(&info[4..8], &[0x80, 0, 0, 34]);
+        assert_eq!(&info[8..12], &[0x12, 0x00, 0x12, 0x00]);
+        assert_eq!(&info[12..18], &[0; 6]);
+
+        let packed = u64::from_be_bytes(info[18..26].try_into().unwrap());
+
+        assert_eq!(packed >> 44, SAMPLE_RATE as u64);
+        assert_eq!(((packed >> 41) & 0x7) + 1, CHANNELS as u64);
+        assert_eq!(((packed >> 36) & 0x1f) + 1, 16);
+        assert_eq!(packed & 0xf_ffff_ffff, 0);
+        assert_eq!(&info[26..], &[0; 16]);
+    }
+
+    #[test]
+    fn resamples_across_chunk_boundaries() {
+        // A ramp makes every interpolated frame checkable against its place on the timeline
+        let input = (0..4410).flat_map(|index| [index as f32, -(index as f32)])
+            .collect::<Vec<_>>();
+
+        let mut whole = Vec::new();
+
+        Resampler::new().process(&input, 2, 44100, &mut whole);
+
+        let mut chunked = Vec::new();
+        let mut resampler = Resampler::new();
+
+        for chunk in input.chunks(2 * 333) { resampler.process(chunk, 2, 44100, &mut chunked); };
+
+        // 100 ms at 44.1 kHz is 4800 frames at 48 kHz
+        assert!((whole.len() / 2).abs_diff(4800) <= 1);
+        assert!(whole.len().abs_diff(chunked.len()) <= 2);
+
+        let step = 44100.0 / SAMPLE_RATE as f64;
+
+        for (index, (frame, other)) in chunked.chunks(2).zip(whole.chunks(2)).enumerate() {
+            let expected = (index as f64 * step - 1.0).max(0.0) as f32;
+
+            assert!((frame[0] - expected).abs() < 0.01, "frame {}: {}", index, frame[0]);
+            assert!((frame[1] + expected).abs() < 0.01, "frame {}: {}", index, frame[1]);
+            assert!((frame[0] - other[0]).abs() < 0.01);
+        };
+    }
+}
\ No newline at end of file
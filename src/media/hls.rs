@@ -0,0 +1,1363 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    io::{self, Read, Seek, SeekFrom},
+    sync::{Arc, Mutex, mpsc::{self, Receiver, RecvTimeoutError, SyncSender}},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH}
+};
+
+use crate::media::stream::StreamStatus;
+
+const LIVE_EDGE: usize = 3;
+const LIVE_WINDOW: u64 = 10;
+const PREFETCH: usize = 3;
+const RETRIES: usize = 3;
+const TIMEOUT: Duration = Duration::from_secs(15);
+const TS_PACKET: usize = 188;
+
+// Only switch up when measured throughput leaves this much headroom over the variant's bitrate
+const HEADROOM: f64 = 0.75;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format { Mp4, Packed, Ts }
+
+impl HlsStream {
+    //** Getters **//
+    pub fn get_duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    pub fn get_hint(&self) -> String {
+        self.hint.clone()
+    }
+
+    //** Misc. **//
+    pub fn open(url: &str, status: Arc<Mutex<StreamStatus>>) -> Result<Self, Box<dyn Error>> {
+        if let Ok(mut status) = status.lock() { *status = StreamStatus::Connecting; };
+
+        let agent = ureq::AgentBuilder::new().timeout_connect(TIMEOUT).timeout_read(TIMEOUT)
+            .build();
+
+        let text = fetch_text(&agent, url)?;
+
+        let (media_url, variants) = match text.contains("#EXT-X-STREAM-INF") {
+            true => select_rendition(url, &text)?,
+            false => (url.to_string(), Vec::new())
+        };
+
+        let playlist = match media_url == url {
+            true => MediaPlaylist::parse(url, &text),
+            false => MediaPlaylist::parse(&media_url, &fetch_text(&agent, &media_url)?)
+        };
+
+        if playlist.encrypted { return Err("Encrypted streams are not supported.".into()); }
+        if playlist.segments.is_empty() { return Err("Playlist has no playable segments.".into()); }
+
+        let duration = playlist.ended.then(|| playlist.segments.iter()
+            .map(|segment| Duration::from_secs_f64(segment.duration)).sum());
+
+        // Joining a live stream at the very start of its window would lag far behind broadcast
+        let skip = match playlist.ended {
+            true => 0,
+            false => playlist.segments.len().saturating_sub(LIVE_EDGE)
+        };
+
+        let mut fetcher = Fetcher {
+            agent,
+            demuxer: Demuxer::default(),
+            ended: playlist.ended,
+            format: playlist.format,
+            map: playlist.map.clone(),
+            media_url,
+            next_sequence: 0,
+            queue: playlist.segments.into_iter().skip(skip).collect(),
+            reloaded: Instant::now(),
+            target: playlist.target,
+            throughput: None,
+            variant: 0,
+            variants
+        };
+
+        // The first segment is fetched up front so the decoder can be told the codec
+        let first = fetcher.next_segment()?.ok_or("Playlist has no playable segments.")?;
+
+        let hint = match fetcher.format {
+            Format::Mp4 => "mp4",
+            Format::Packed | Format::Ts => fetcher.demuxer.hint
+        }.to_string();
+
+        let (sender, segments) = mpsc::sync_channel::<Result<Vec<u8>, String>>(PREFETCH);
+
+        thread::spawn(move || fetcher.run(sender));
+
+        Ok(Self {
+            current: first,
+            duration,
+            hint,
+            offset: 0,
+            position: 0,
+            segments: Mutex::new(segments),
+            status
+        })
+    }
+
+    fn set_status(&self, next: StreamStatus) {
+        if let Ok(mut status) = self.status.lock() {
+            if *status != next { *status = next; };
+        };
+    }
+}
+
+impl Fetcher {
+    fn fetch_segment(&mut self, uri: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let started = Instant::now();
+        let mut error: Box<dyn Error> = "Segment download failed.".into();
+
+        for _attempt in 0..RETRIES {
+            match fetch(&self.agent, uri) {
+                Ok(data) => {
+                    let seconds = started.elapsed().as_secs_f64().max(0.001);
+
+                    self.throughput = Some(data.len() as f64 * 8.0 / seconds);
+
+                    return Ok(data);
+                },
+
+                Err(next) => error = next
+            };
+        };
+
+        Err(error)
+    }
+
+    fn next_segment(&mut self) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        let mut failures = 0;
+
+        loop {
+            let segment = match self.queue.pop_front() {
+                Some(segment) => segment,
+
+                None if self.ended => return Ok(None),
+
+                None => {
+                    if let Err(error) = self.reload() {
+                        failures += 1;
+
+                        if failures >= RETRIES { return Err(error); }
+                    };
+
+                    continue;
+                }
+            };
+
+            self.next_sequence = segment.sequence + 1;
+
+            let data = match self.fetch_segment(&segment.uri) {
+                Ok(data) => data,
+
+                // A live segment that will not download is skipped rather than ending playback
+                Err(_) if !self.ended => continue,
+                Err(error) => return Err(error)
+            };
+
+            let mut output = Vec::new();
+
+            if let Some(map) = self.map.take() {
+                self.format = Format::Mp4;
+
+                output.extend(fetch(&self.agent, &map)?);
+            };
+
+            if self.format != Format::Mp4 {
+                self.format = if is_ts(&data) { Format::Ts } else { Format::Packed };
+            };
+
+            match self.format {
+                Format::Mp4 => output.extend(data),
+                Format::Packed => output.extend(self.demuxer.strip_id3(&data)),
+                Format::Ts => output.extend(self.demuxer.demux(&data))
+            };
+
+            self.switch_variant();
+
+            return Ok(Some(output));
+        };
+    }
+
+    fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        // Live playlists are only refreshed about once per target duration
+        let wait = Duration::from_secs_f64(self.target.max(1.0));
+
+        if let Some(remaining) = wait.checked_sub(self.reloaded.elapsed()) {
+            thread::sleep(remaining);
+        };
+
+        self.reloaded = Instant::now();
+
+        let text = fetch_text(&self.agent, &self.media_url)?;
+        let playlist = MediaPlaylist::parse(&self.media_url, &text);
+
+        self.ended = playlist.ended;
+        self.target = playlist.target;
+
+        let first = playlist.segments.first().map(|segment| segment.sequence).unwrap_or(0);
+
+        // Falling out of the live window means resuming from the oldest segment still listed
+        if self.next_sequence < first { self.next_sequence = first; };
+
+        self.queue.extend(playlist.segments.into_iter()
+            .filter(|segment| segment.sequence >= self.next_sequence));
+
+        Ok(())
+    }
+
+    fn run(mut self, sender: SyncSender<Result<Vec<u8>, String>>) {
+        loop {
+            let message = match self.next_segment() {
+                Ok(Some(data)) => Ok(data),
+                Ok(None) => return,
+                Err(error) => Err(error.to_string())
+            };
+
+            let failed = message.is_err();
+
+            // A full channel blocks here, which is what limits the prefetch depth
+            if sender.send(message).is_err() || failed { return; }
+        };
+    }
+
+    fn switch_variant(&mut self) {
+        // Init segments differ per variant, so fMP4 streams stay on their first choice
+        if self.variants.len() < 2 || self.format == Format::Mp4 { return; }
+
+        let throughput = match self.throughput {
+            Some(throughput) => throughput * HEADROOM,
+            None => return
+        };
+
+        let best = self.variants.iter().enumerate()
+            .filter(|(_index, variant)| variant.bandwidth as f64 <= throughput)
+            .max_by_key(|(_index, variant)| variant.bandwidth).map(|(index, _variant)| index)
+            .unwrap_or(0);
+
+        if best == self.variant { return; }
+
+        let url = self.variants[best].uri.clone();
+
+        let playlist = match fetch_text(&self.agent, &url) {
+            Ok(text) => MediaPlaylist::parse(&url, &text),
+            Err(_) => return
+        };
+
+        // Variants share sequence numbers, so the new one picks up right where this one stopped
+        self.variant = best;
+        self.media_url = url;
+        self.ended = playlist.ended;
+        self.target = playlist.target;
+        self.queue = playlist.segments.into_iter()
+            .filter(|segment| segment.sequence >= self.next_sequence).collect();
+    }
+}
+
+impl MediaPlaylist {
+    fn parse(base: &str, text: &str) -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64();
+
+        // DASH manifests are XML, HLS playlists are plain lines starting with #EXTM3U
+        match text.trim_start().starts_with('<') {
+            true => Self::parse_dash(base, text, now),
+            false => Self::parse_hls(base, text)
+        }
+    }
+
+    fn parse_dash(base: &str, text: &str, now: f64) -> Self {
+        let mut playlist = Self {
+            encrypted: false,
+            ended: true,
+            format: Format::Mp4,
+            map: None,
+            segments: Vec::new(),
+            target: 6.0
+        };
+
+        let mpd = match parse_xml(text) {
+            Some(mpd) if mpd.name == "MPD" => mpd,
+            _ => return playlist
+        };
+
+        let dynamic = mpd.attribute("type") == Some("dynamic");
+
+        // Live manifests append periods, so the newest one is what is on air
+        let periods = mpd.children("Period").collect::<Vec<_>>();
+
+        let period = if dynamic { periods.last() } else { periods.first() };
+
+        let period = match period {
+            Some(period) => *period,
+            None => return playlist
+        };
+
+        let set = period.children("AdaptationSet")
+            .find(|set| is_audio(set) || set.children("Representation").any(is_audio));
+
+        // Without variant switching for fMP4 the best audio representation is used throughout
+        let representation = set.and_then(|set| set.children("Representation")
+            .filter(|rep| rep.attribute("mimeType").is_none() || is_audio(rep))
+            .max_by_key(|rep| rep.attribute("bandwidth").and_then(|bw| bw.parse::<u64>().ok())));
+
+        let (set, representation) = match (set, representation) {
+            (Some(set), Some(representation)) => (set, representation),
+            _ => return playlist
+        };
+
+        playlist.ended = !dynamic;
+        playlist.encrypted = set.child("ContentProtection").is_some()
+            || representation.child("ContentProtection").is_some();
+
+        let base = [&mpd, period, set, representation].iter()
+            .fold(base.to_string(), |base, node| match node.child("BaseURL") {
+                Some(url) => resolve(&base, url.text.trim()),
+                None => base
+            });
+
+        let id = representation.attribute("id").unwrap_or_default();
+        let bandwidth = representation.attribute("bandwidth").and_then(|bw| bw.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        let total = period.attribute("duration").or(mpd.attribute("mediaPresentationDuration"))
+            .and_then(parse_iso_duration);
+
+        // Representation settings override the ones shared by the whole adaptation set
+        let inherited = |name: &str| representation.child(name).or(set.child(name));
+
+        if let Some(template) = inherited("SegmentTemplate") {
+            let mut attributes = set.child("SegmentTemplate")
+                .map(|shared| shared.attributes.clone()).unwrap_or_default();
+
+            attributes.extend(template.attributes.clone());
+
+            let number = |key: &str, default: u64| attributes.get(key)
+                .and_then(|value| value.parse::<u64>().ok()).unwrap_or(default);
+
+            let timescale = number("timescale", 1).max(1) as f64;
+            let start_number = number("startNumber", 1);
+            let media = attributes.get("media").cloned().unwrap_or_default();
+
+            playlist.map = attributes.get("initialization")
+                .map(|init| resolve(&base, &fill_template(init, id, bandwidth, 0, 0)));
+
+            let mut segment = |sequence: u64, time: u64, length: f64| {
+                playlist.segments.push(Segment {
+                    duration: length,
+                    sequence,
+                    uri: resolve(&base, &fill_template(&media, id, bandwidth, sequence, time))
+                });
+            };
+
+            let timeline = template.child("SegmentTimeline").or(set.child("SegmentTemplate")
+                .and_then(|shared| shared.child("SegmentTimeline")));
+
+            if let Some(timeline) = timeline {
+                let mut time = 0;
+                let mut sequence = start_number;
+
+                for entry in timeline.children("S") {
+                    let value = |key: &str| entry.attribute(key)
+                        .and_then(|value| value.parse::<i64>().ok());
+
+                    time = value("t").map(|t| t.max(0) as u64).unwrap_or(time);
+
+                    let length = value("d").unwrap_or(0).max(0) as u64;
+
+                    // A negative repeat count would run to the next entry, so it counts once
+                    for _repeat in 0..=value("r").unwrap_or(0).max(0) {
+                        segment(sequence, time, length as f64 / timescale);
+
+                        time += length;
+                        sequence += 1;
+                    };
+                };
+            } else if let Some(duration) = attributes.get("duration")
+                .and_then(|value| value.parse::<u64>().ok()).filter(|duration| *duration > 0) {
+                let length = duration as f64 / timescale;
+
+                let (first, count) = match dynamic {
+                    true => {
+                        let start = mpd.attribute("availabilityStartTime").and_then(parse_datetime)
+                            .unwrap_or(now);
+                        let offset = period.attribute("start").and_then(parse_iso_duration)
+                            .unwrap_or(0.0);
+
+                        // A segment can only be fetched once it has been completely produced
+                        let available = ((now - start - offset) / length).max(0.0) as u64;
+                        let count = available.min(LIVE_WINDOW);
+
+                        (start_number + available - count, count)
+                    },
+
+                    false => (start_number, (total.unwrap_or(0.0) / length).ceil() as u64)
+                };
+
+                for sequence in first..first + count {
+                    segment(sequence, (sequence - start_number) * duration, length);
+                };
+            };
+
+            playlist.target = mpd.attribute("minimumUpdatePeriod").and_then(parse_iso_duration)
+                .or(playlist.segments.last().map(|segment| segment.duration))
+                .unwrap_or(playlist.target);
+        } else if let Some(list) = inherited("SegmentList") {
+            let timescale = list.attribute("timescale").and_then(|value| value.parse::<f64>().ok())
+                .filter(|timescale| timescale.is_finite()).unwrap_or(1.0).max(1.0);
+            let length = clamp_seconds(list.attribute("duration")
+                .and_then(|value| value.parse::<f64>().ok()).unwrap_or(0.0) / timescale);
+            let start_number = list.attribute("startNumber").and_then(|value| value.parse().ok())
+                .unwrap_or(1);
+
+            playlist.map = list.child("Initialization").and_then(|init| init.attribute("sourceURL"))
+                .map(|uri| resolve(&base, uri));
+
+            for (index, url) in list.children("SegmentURL").enumerate() {
+                let uri = url.attribute("media").map(|uri| resolve(&base, uri))
+                    .unwrap_or(base.clone());
+
+                let sequence = start_number + index as u64;
+
+                playlist.segments.push(Segment { duration: length, sequence, uri });
+            };
+        } else if !dynamic {
+            // With only a SegmentBase or BaseURL the representation is a single file
+            let duration = total.unwrap_or(0.0);
+
+            playlist.segments.push(Segment { duration, sequence: 0, uri: base });
+        };
+
+        playlist
+    }
+
+    fn parse_hls(base: &str, text: &str) -> Self {
+        let mut playlist = Self {
+            encrypted: false,
+            ended: false,
+            format: Format::Packed,
+            map: None,
+            segments: Vec::new(),
+            target: 6.0
+        };
+
+        let mut duration = 0.0;
+        let mut sequence = 0;
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+                playlist.target = value.parse().map(clamp_seconds).unwrap_or(playlist.target);
+            } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+                sequence = value.parse().unwrap_or(0);
+            } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+                duration = value.split(',').next().and_then(|value| value.parse().ok())
+                    .map(clamp_seconds).unwrap_or(0.0);
+            } else if let Some(value) = line.strip_prefix("#EXT-X-MAP:") {
+                playlist.map = parse_attributes(value).get("URI").map(|uri| resolve(base, uri));
+            } else if let Some(value) = line.strip_prefix("#EXT-X-KEY:") {
+                playlist.encrypted |= parse_attributes(value).get("METHOD")
+                    .is_some_and(|method| method != "NONE");
+            } else if line == "#EXT-X-ENDLIST" {
+                playlist.ended = true;
+            } else if !line.starts_with('#') {
+                playlist.segments.push(Segment { duration, sequence, uri: resolve(base, line) });
+
+                duration = 0.0;
+                sequence += 1;
+            };
+        };
+
+        playlist
+    }
+}
+
+impl Demuxer {
+    fn demux(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+
+        for packet in data.chunks_exact(TS_PACKET).filter(|packet| packet[0] == 0x47) {
+            let pid = ((packet[1] as u16 & 0x1F) << 8) | packet[2] as u16;
+            let start = packet[1] & 0x40 != 0;
+            let control = (packet[3] >> 4) & 0x03;
+
+            // Adaptation fields carry timing and stuffing, never audio
+            let offset = match control {
+                1 => 4,
+                3 => 5 + packet[4] as usize,
+                _ => continue
+            };
+
+            if offset >= TS_PACKET { continue; }
+
+            let payload = &packet[offset..];
+
+            if pid == 0 && start {
+                self.read_pat(payload);
+            } else if Some(pid) == self.pmt_pid && start {
+                self.read_pmt(payload);
+            } else if Some(pid) == self.audio_pid {
+                match start {
+                    true => output.extend_from_slice(skip_pes_header(payload)),
+                    false => output.extend_from_slice(payload)
+                };
+            };
+        };
+
+        output
+    }
+
+    fn read_pat(&mut self, payload: &[u8]) {
+        let table = match payload.first() {
+            Some(pointer) => &payload[(1 + *pointer as usize).min(payload.len())..],
+            None => return
+        };
+
+        // The first program entry after the 8-byte header points at the PMT
+        if let Some(entry) = table.get(8..12) {
+            self.pmt_pid = Some(((entry[2] as u16 & 0x1F) << 8) | entry[3] as u16);
+        };
+    }
+
+    fn read_pmt(&mut self, payload: &[u8]) {
+        let table = match payload.first() {
+            Some(pointer) => &payload[(1 + *pointer as usize).min(payload.len())..],
+            None => return
+        };
+
+        if table.len() < 12 { return; }
+
+        let section = (((table[1] as usize & 0x0F) << 8) | table[2] as usize).min(table.len() - 3);
+        let info = ((table[10] as usize & 0x0F) << 8) | table[11] as usize;
+        let end = (3 + section).saturating_sub(4);
+        let mut index = 12 + info;
+
+        while index + 5 <= end {
+            let kind = table[index];
+            let pid = ((table[index + 1] as u16 & 0x1F) << 8) | table[index + 2] as u16;
+            let length = ((table[index + 3] as usize & 0x0F) << 8) | table[index + 4] as usize;
+
+            let hint = match kind {
+                0x0F => Some("aac"),
+                0x03 | 0x04 => Some("mp3"),
+                _ => None
+            };
+
+            if let Some(hint) = hint {
+                self.audio_pid = Some(pid);
+                self.hint = hint;
+
+                return;
+            };
+
+            index += 5 + length;
+        };
+    }
+
+    fn strip_id3(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut data = data;
+
+        // Packed audio segments open with an ID3 tag holding the segment's timestamp
+        while data.len() >= 10 && &data[0..3] == b"ID3" {
+            let size = data[6..10].iter()
+                .fold(0usize, |size, byte| (size << 7) | (*byte as usize & 0x7F));
+            let footer = if data[5] & 0x10 != 0 { 10 } else { 0 };
+
+            data = &data[(10 + size + footer).min(data.len())..];
+        };
+
+        self.hint = match data {
+            [0xFF, sync, ..] if sync & 0xF6 == 0xF0 => "aac",
+            _ => "mp3"
+        };
+
+        data.to_vec()
+    }
+}
+
+impl Node {
+    fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(String::as_str)
+    }
+
+    fn child(&self, name: &str) -> Option<&Node> {
+        self.children(name).next()
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Node> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+impl Default for Demuxer {
+    fn default() -> Self {
+        Self {
+            audio_pid: None,
+            hint: "aac",
+            pmt_pid: None
+        }
+    }
+}
+
+impl Read for HlsStream {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.offset >= self.current.len() {
+            let segments = self.segments.lock()
+                .map_err(|_| io::Error::other("Segment queue poisoned."))?;
+
+            let mut received = segments.recv_timeout(Duration::from_millis(250));
+
+            // Live streams wait on the next segment for up to a full timeout
+            if let Err(RecvTimeoutError::Timeout) = received {
+                self.set_status(StreamStatus::Buffering);
+
+                received = segments.recv_timeout(TIMEOUT);
+            };
+
+            drop(segments);
+
+            match received {
+                Ok(Ok(data)) => {
+                    self.current = data;
+                    self.offset = 0;
+
+                    self.set_status(StreamStatus::Streaming);
+                },
+
+                Ok(Err(error)) => {
+                    self.set_status(StreamStatus::Failed(error.clone()));
+
+                    return Err(io::Error::other(error));
+                },
+
+                Err(RecvTimeoutError::Timeout) => {
+                    self.set_status(StreamStatus::Failed("Timed out.".to_string()));
+
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "Stream timed out."));
+                },
+
+                Err(RecvTimeoutError::Disconnected) => return Ok(0)
+            };
+        };
+
+        let count = out.len().min(self.current.len() - self.offset);
+
+        out[..count].copy_from_slice(&self.current[self.offset..self.offset + count]);
+
+        self.offset += count;
+        self.position += count as u64;
+
+        Ok(count)
+    }
+}
+
+impl Seek for HlsStream {
+    fn seek(&mut self, from: SeekFrom) -> io::Result<u64> {
+        // Segments are consumed as they arrive, so only position queries are answered
+        match from {
+            SeekFrom::Current(0) => Ok(self.position),
+            SeekFrom::Start(offset) if offset == self.position => Ok(self.position),
+            _ => Err(io::Error::new(io::ErrorKind::Unsupported, "HLS streams are not seekable."))
+        }
+    }
+}
+
+// Playlists in the wild carry "#EXTINF:-1," and worse, which Duration cannot hold
+fn clamp_seconds(value: f64) -> f64 {
+    if value.is_finite() { value.max(0.0) } else { 0.0 }
+}
+
+fn fetch(agent: &ureq::Agent, url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut data = Vec::new();
+
+    agent.get(url).call()?.into_reader().read_to_end(&mut data)?;
+
+    Ok(data)
+}
+
+fn fetch_text(agent: &ureq::Agent, url: &str) -> Result<String, Box<dyn Error>> {
+    Ok(String::from_utf8_lossy(&fetch(agent, url)?).to_string())
+}
+
+fn fill_template(template: &str, id: &str, bandwidth: u64, number: u64, time: u64) -> String {
+    let mut output = String::new();
+
+    // Identifiers sit between pairs of dollar signs, optionally with a printf width like %05d
+    for (index, part) in template.split('$').enumerate() {
+        if index % 2 == 0 { output += part; continue; };
+
+        let (name, format) = part.split_once('%').unwrap_or((part, ""));
+        let width = format.trim_end_matches('d').parse::<usize>().unwrap_or(0);
+
+        match name {
+            "" => output.push('$'),
+            "Bandwidth" => output += &format!("{:0width$}", bandwidth, width = width),
+            "Number" => output += &format!("{:0width$}", number, width = width),
+            "RepresentationID" => output += id,
+            "Time" => output += &format!("{:0width$}", time, width = width),
+            _ => output += &format!("${}$", part)
+        };
+    };
+
+    output
+}
+
+fn is_audio(node: &Node) -> bool {
+    let mime = node.attribute("mimeType").unwrap_or_default();
+
+    // Only fragmented MP4 audio is handled; WebM and video-only sets are passed over
+    mime == "audio/mp4" || (mime.is_empty() && node.attribute("contentType") == Some("audio"))
+}
+
+fn is_ts(data: &[u8]) -> bool {
+    data.len() >= TS_PACKET * 2 && data[0] == 0x47 && data[TS_PACKET] == 0x47
+}
+
+pub fn is_playlist(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or_default().to_lowercase();
+
+    path.ends_with(".m3u8") || path.ends_with(".mpd")
+}
+
+fn parse_attributes(text: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut key = String::new();
+    let mut value = String::new();
+    let mut in_key = true;
+    let mut quoted = false;
+
+    // Quoted values such as CODECS="mp4a.40.2,avc1.4d401e" may contain commas
+    for c in text.chars().chain([',']) {
+        match c {
+            '"' => quoted = !quoted,
+            '=' if in_key => in_key = false,
+
+            ',' if !quoted => {
+                if !key.trim().is_empty() {
+                    attributes.insert(key.trim().to_string(), value.trim().to_string());
+                };
+
+                key.clear();
+                value.clear();
+                in_key = true;
+            },
+
+            _ if in_key => key.push(c),
+            _ => value.push(c)
+        };
+    };
+
+    attributes
+}
+
+fn parse_datetime(text: &str) -> Option<f64> {
+    let (date, time) = text.trim().split_once('T')?;
+    let mut date = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+
+    let (time, offset) = match time.strip_suffix('Z') {
+        Some(time) => (time, 0),
+
+        None => match time.rfind(['+', '-']) {
+            Some(index) => {
+                let sign = if time[index..].starts_with('-') { -1 } else { 1 };
+                let zone = &time[index + 1..];
+                let (hours, minutes) = zone.split_once(':').unwrap_or((zone, "0"));
+
+                (&time[..index], sign * (hours.parse::<i64>().ok()? * 3600
+                    + minutes.parse::<i64>().ok()? * 60))
+            },
+
+            None => (time, 0)
+        }
+    };
+
+    let mut time = time.split(':');
+    let (hours, minutes) = (time.next()?.parse::<i64>().ok()?, time.next()?.parse::<i64>().ok()?);
+    let seconds = time.next().unwrap_or("0").parse::<f64>().ok()?;
+
+    // Days since the epoch for a proleptic Gregorian date, with years starting in March
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    Some((days * 86400 + hours * 3600 + minutes * 60 - offset) as f64 + seconds)
+}
+
+fn parse_iso_duration(text: &str) -> Option<f64> {
+    let text = text.trim().strip_prefix('P')?;
+    let (date, time) = text.split_once('T').unwrap_or((text, ""));
+    let mut seconds = 0.0;
+
+    let date_units = [('Y', 31_536_000.0), ('M', 2_592_000.0), ('W', 604_800.0), ('D', 86_400.0)];
+    let time_units = [('H', 3600.0), ('M', 60.0), ('S', 1.0)];
+
+    for (part, units) in [(date, &date_units[..]), (time, &time_units[..])] {
+        let mut number = String::new();
+
+        for c in part.chars() {
+            match units.iter().find(|(unit, _scale)| *unit == c) {
+                Some((_unit, scale)) => {
+                    seconds += number.parse::<f64>().ok()? * scale;
+
+                    number.clear();
+                },
+
+                None => number.push(c)
+            };
+        };
+    };
+
+    Some(clamp_seconds(seconds))
+}
+
+fn parse_xml(text: &str) -> Option<Node> {
+    // The bottom of the stack only collects the document element
+    let mut stack: Vec<Node> = vec![Node::default()];
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        stack.last_mut()?.text += &unescape(rest[..start].trim());
+        rest = &rest[start..];
+
+        if rest.starts_with("<!--") {
+            rest = &rest[rest.find("-->")? + 3..];
+
+            continue;
+        };
+
+        if let Some(data) = rest.strip_prefix("<![CDATA[") {
+            let end = data.find("]]>")?;
+
+            stack.last_mut()?.text += &data[..end];
+            rest = &data[end + 3..];
+
+            continue;
+        };
+
+        // Attribute values may contain '>', so the tag ends at the first one outside quotes
+        let mut quote = None;
+
+        let end = rest.char_indices().find(|(_index, c)| {
+            match (quote, *c) {
+                (None, '"' | '\'') => quote = Some(*c),
+                (Some(open), c) if open == c => quote = None,
+                (None, '>') => return true,
+                _ => {}
+            };
+
+            false
+        })?.0;
+
+        let tag = &rest[1..end];
+
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') { continue; };
+
+        if tag.starts_with('/') {
+            if stack.len() < 2 { return None; }
+
+            let node = stack.pop()?;
+
+            stack.last_mut()?.children.push(node);
+
+            continue;
+        };
+
+        let closed = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+
+        // Namespace prefixes like "mpd:" are dropped, as nothing here mixes vocabularies
+        let node = Node {
+            attributes: xml_attributes(attributes),
+            name: name.rsplit(':').next().unwrap_or(name).to_string(),
+            ..Node::default()
+        };
+
+        if closed { stack.last_mut()?.children.push(node); } else { stack.push(node); };
+    };
+
+    while stack.len() > 1 {
+        let node = stack.pop()?;
+
+        stack.last_mut()?.children.push(node);
+    };
+
+    stack.pop()?.children.into_iter().next()
+}
+
+fn resolve(base: &str, uri: &str) -> String {
+    if uri.starts_with("http://") || uri.starts_with("https://") { return uri.to_string(); }
+
+    let scheme = base.split("://").next().unwrap_or("https");
+
+    if let Some(rest) = uri.strip_prefix("//") { return format!("{}://{}", scheme, rest); }
+
+    let path = base.split(['?', '#']).next().unwrap_or_default();
+
+    if uri.starts_with('/') {
+        let host = path.split("://").nth(1).and_then(|rest| rest.split('/').next())
+            .unwrap_or_default();
+
+        return format!("{}://{}{}", scheme, host, uri);
+    };
+
+    match path.rfind('/') {
+        Some(index) => format!("{}{}", &path[..=index], uri),
+        None => uri.to_string()
+    }
+}
+
+fn select_rendition(base: &str, text: &str) -> Result<(String, Vec<Variant>), Box<dyn Error>> {
+    let mut audio: Vec<(String, bool, String)> = Vec::new();
+    let mut variants: Vec<Variant> = Vec::new();
+    let mut pending: Option<HashMap<String, String>> = None;
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(value) = line.strip_prefix("#EXT-X-MEDIA:") {
+            let attributes = parse_attributes(value);
+
+            if attributes.get("TYPE").is_some_and(|kind| kind == "AUDIO") {
+                if let Some(uri) = attributes.get("URI") {
+                    audio.push((attributes.get("GROUP-ID").cloned().unwrap_or_default(),
+                        attributes.get("DEFAULT").is_some_and(|default| default == "YES"),
+                        resolve(base, uri)));
+                };
+            };
+        } else if let Some(value) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            pending = Some(parse_attributes(value));
+        } else if !line.starts_with('#') {
+            if let Some(attributes) = pending.take() {
+                variants.push(Variant {
+                    audio: attributes.get("AUDIO").cloned(),
+                    bandwidth: attributes.get("BANDWIDTH").and_then(|value| value.parse().ok())
+                        .unwrap_or(0),
+                    codecs: attributes.get("CODECS").cloned().unwrap_or_default(),
+                    uri: resolve(base, line)
+                });
+            };
+        };
+    };
+
+    variants.sort_by_key(|variant| variant.bandwidth);
+
+    let first = variants.first().ok_or("HLS master playlist has no variants.")?;
+
+    // A separate audio rendition avoids downloading video that would only be thrown away
+    let rendition = audio.iter()
+        .filter(|(group, ..)| first.audio.as_ref().is_none_or(|audio| audio == group))
+        .max_by_key(|(_group, default, _uri)| *default);
+
+    if let Some((_group, _default, uri)) = rendition { return Ok((uri.clone(), Vec::new())); }
+
+    // Switching between codecs mid-stream would break the decoder, so only matching variants count
+    let codecs = first.codecs.clone();
+
+    let variants = variants.into_iter().filter(|variant| variant.codecs == codecs)
+        .collect::<Vec<_>>();
+
+    Ok((variants[0].uri.clone(), variants))
+}
+
+fn skip_pes_header(payload: &[u8]) -> &[u8] {
+    if payload.len() < 9 || payload[0..3] != [0x00, 0x00, 0x01] { return payload; }
+
+    &payload[(9 + payload[8] as usize).min(payload.len())..]
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"")
+        .replace("&apos;", "'").replace("&amp;", "&")
+}
+
+fn xml_attributes(text: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = text.trim();
+
+    while let Some(equals) = rest.find('=') {
+        let key = rest[..equals].trim().to_string();
+        let value = rest[equals + 1..].trim_start();
+
+        let quote = match value.chars().next() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => break
+        };
+
+        let end = match value[1..].find(quote) {
+            Some(end) => end + 1,
+            None => break
+        };
+
+        // Only the local name matters, so xmlns declarations and prefixes fall away
+        let key = key.rsplit(':').next().unwrap_or(&key).to_string();
+
+        attributes.insert(key, unescape(&value[1..end]));
+        rest = value[end + 1..].trim_start();
+    };
+
+    attributes
+}
+
+struct Demuxer {
+    audio_pid: Option<u16>,
+    hint: &'static str,
+    pmt_pid: Option<u16>
+}
+
+struct Fetcher {
+    // Network
+    agent: ureq::Agent,
+    media_url: String,
+    throughput: Option<f64>,
+
+    // Playlist
+    ended: bool,
+    map: Option<String>,
+    next_sequence: u64,
+    queue: VecDeque<Segment>,
+    reloaded: Instant,
+    target: f64,
+
+    // Segments
+    demuxer: Demuxer,
+    format: Format,
+
+    // Variants
+    variant: usize,
+    variants: Vec<Variant>
+}
+
+pub struct HlsStream {
+    current: Vec<u8>,
+    duration: Option<Duration>,
+    hint: String,
+    offset: usize,
+    position: u64,
+    segments: Mutex<Receiver<Result<Vec<u8>, String>>>,
+    status: Arc<Mutex<StreamStatus>>
+}
+
+struct MediaPlaylist {
+    encrypted: bool,
+    ended: bool,
+    format: Format,
+    map: Option<String>,
+    segments: Vec<Segment>,
+    target: f64
+}
+
+#[derive(Default)]
+struct Node {
+    attributes: HashMap<String, String>,
+    children: Vec<Node>,
+    name: String,
+    text: String
+}
+
+struct Segment {
+    duration: f64,
+    sequence: u64,
+    uri: String
+}
+
+struct Variant {
+    audio: Option<String>,
+    bandwidth: u64,
+    codecs: String,
+    uri: String
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "https://radio.example/live/index.m3u8?token=abc";
+
+    // Wraps payload in one transport stream packet, padding with adaptation field stuffing
+    fn packet(pid: u16, start: bool, payload: &[u8]) -> Vec<u8> {
+        let flags = if start { 0x40 } else { 0x00 };
+        let stuffing = TS_PACKET - 5 - payload.len();
+        let mut packet = vec![0x47, flags | (pid >> 8) as u8, pid as u8, 0x30, stuffing as u8];
+
+        if stuffing > 0 {
+            packet.push(0x00);
+            packet.extend(vec![0xFF; stuffing - 1]);
+        };
+
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    #[test]
+    fn parses_attribute_lists() {
+        let attributes = parse_attributes("BANDWIDTH=128000,CODECS=\"mp4a.40.2,avc1.4d401e\",\
+            AUDIO=\"aud\"");
+
+        assert_eq!(attributes.get("BANDWIDTH").map(String::as_str), Some("128000"));
+        assert_eq!(attributes.get("CODECS").map(String::as_str), Some("mp4a.40.2,avc1.4d401e"));
+        assert_eq!(attributes.get("AUDIO").map(String::as_str), Some("aud"));
+    }
+
+    #[test]
+    fn parses_media_playlists() {
+        let text = "#EXTM3U\n#EXT-X-TARGETDURATION:10\n#EXT-X-MEDIA-SEQUENCE:7\n\
+            #EXT-X-MAP:URI=\"init.mp4\"\n#EXTINF:9.5,\nfirst.m4s\n#EXTINF:10.0,title\n\
+            /abs/second.m4s\n#EXT-X-ENDLIST\n";
+
+        let playlist = MediaPlaylist::parse(BASE, text);
+
+        assert!(playlist.ended);
+        assert!(!playlist.encrypted);
+        assert_eq!(playlist.target, 10.0);
+        assert_eq!(playlist.map.as_deref(), Some("https://radio.example/live/init.mp4"));
+        assert_eq!(playlist.segments.len(), 2);
+        assert_eq!(playlist.segments[0].sequence, 7);
+        assert_eq!(playlist.segments[0].duration, 9.5);
+        assert_eq!(playlist.segments[0].uri, "https://radio.example/live/first.m4s");
+        assert_eq!(playlist.segments[1].sequence, 8);
+        assert_eq!(playlist.segments[1].uri, "https://radio.example/abs/second.m4s");
+    }
+
+    #[test]
+    fn clamps_bad_segment_durations() {
+        let text = "#EXTM3U\n#EXT-X-TARGETDURATION:inf\n#EXTINF:-1,\na.ts\n#EXTINF:NaN,\nb.ts\n\
+            #EXTINF:4,\nc.ts\n#EXT-X-ENDLIST\n";
+
+        let playlist = MediaPlaylist::parse(BASE, text);
+        let durations = playlist.segments.iter().map(|segment| segment.duration)
+            .collect::<Vec<_>>();
+
+        assert_eq!(durations, vec![0.0, 0.0, 4.0]);
+        assert_eq!(playlist.target, 0.0);
+
+        let dash = r#"<MPD type="static" mediaPresentationDuration="PT4S"><Period>
+            <AdaptationSet mimeType="audio/mp4"><Representation id="a" bandwidth="1">
+            <SegmentList duration="-20"><SegmentURL media="s1.m4s"/></SegmentList>
+            </Representation></AdaptationSet></Period></MPD>"#;
+
+        assert_eq!(MediaPlaylist::parse(BASE, dash).segments[0].duration, 0.0);
+    }
+
+    #[test]
+    fn flags_encrypted_playlists() {
+        let text = "#EXTM3U\n#EXT-X-KEY:METHOD=AES-128,URI=\"key\"\n#EXTINF:6,\na.ts\n";
+
+        assert!(MediaPlaylist::parse(BASE, text).encrypted);
+        assert!(!MediaPlaylist::parse(BASE, "#EXTM3U\n#EXT-X-KEY:METHOD=NONE\n").encrypted);
+    }
+
+    #[test]
+    fn resolves_relative_uris() {
+        assert_eq!(resolve(BASE, "https://cdn.example/a.ts"), "https://cdn.example/a.ts");
+        assert_eq!(resolve(BASE, "//cdn.example/a.ts"), "https://cdn.example/a.ts");
+        assert_eq!(resolve(BASE, "/a.ts"), "https://radio.example/a.ts");
+        assert_eq!(resolve(BASE, "hi/a.ts"), "https://radio.example/live/hi/a.ts");
+        assert_eq!(resolve("http://radio.example/x", "//cdn/a.ts"), "http://cdn/a.ts");
+    }
+
+    #[test]
+    fn prefers_audio_renditions() {
+        let text = "#EXTM3U\n\
+            #EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",DEFAULT=NO,URI=\"alt.m3u8\"\n\
+            #EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",DEFAULT=YES,URI=\"main.m3u8\"\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=800000,AUDIO=\"aud\"\nvideo.m3u8\n";
+
+        let (url, variants) = select_rendition(BASE, text).unwrap();
+
+        assert_eq!(url, "https://radio.example/live/main.m3u8");
+        assert!(variants.is_empty());
+    }
+
+    #[test]
+    fn selects_the_lowest_matching_variant() {
+        let text = "#EXTM3U\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=256000,CODECS=\"mp4a.40.2\"\nhigh.m3u8\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=64000,CODECS=\"mp4a.40.2\"\nlow.m3u8\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=128000,CODECS=\"mp4a.40.5\"\nother.m3u8\n";
+
+        let (url, variants) = select_rendition(BASE, text).unwrap();
+
+        assert_eq!(url, "https://radio.example/live/low.m3u8");
+        assert_eq!(variants.iter().map(|variant| variant.bandwidth).collect::<Vec<_>>(),
+            vec![64000, 256000]);
+        assert!(select_rendition(BASE, "#EXTM3U\n").is_err());
+    }
+
+    #[test]
+    fn demuxes_transport_stream_audio() {
+        let pat = [0x00, 0x00, 0xB0, 0x0D, 0x00, 0x01, 0xC1, 0x00, 0x00, 0x00, 0x01, 0xE1, 0x00];
+
+        let pmt = [0x00, 0x02, 0xB0, 0x12, 0x00, 0x01, 0xC1, 0x00, 0x00, 0xE1, 0x01, 0xF0, 0x00,
+            0x0F, 0xE1, 0x01, 0xF0, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+        let mut pes = vec![0x00, 0x00, 0x01, 0xC0, 0x00, 0x00, 0x80, 0x80, 0x05,
+            0x21, 0x00, 0x01, 0x00, 0x01];
+
+        pes.extend_from_slice(&[0xFF, 0xF1, 0x50, 0x80]);
+
+        let mut data = packet(0x0000, true, &pat);
+
+        data.extend(packet(0x0100, true, &pmt));
+        data.extend(packet(0x0101, true, &pes));
+        data.extend(packet(0x0101, false, &[0x01, 0x02, 0x03]));
+        data.extend(packet(0x0102, true, &[0x09, 0x09]));
+
+        let mut demuxer = Demuxer::default();
+
+        assert!(is_ts(&data));
+        assert_eq!(demuxer.demux(&data), vec![0xFF, 0xF1, 0x50, 0x80, 0x01, 0x02, 0x03]);
+        assert_eq!(demuxer.pmt_pid, Some(0x0100));
+        assert_eq!(demuxer.audio_pid, Some(0x0101));
+        assert_eq!(demuxer.hint, "aac");
+    }
+
+    #[test]
+    fn strips_id3_from_packed_audio() {
+        let mut data = b"ID3\x04\x00\x00\x00\x00\x00\x02ab".to_vec();
+
+        data.extend_from_slice(&[0xFF, 0xFB, 0x90]);
+
+        let mut demuxer = Demuxer::default();
+
+        assert_eq!(demuxer.strip_id3(&data), vec![0xFF, 0xFB, 0x90]);
+        assert_eq!(demuxer.hint, "mp3");
+    }
+
+    #[test]
+    fn parses_static_dash_templates() {
+        let text = r#"<?xml version="1.0"?>
+            <MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="static"
+                mediaPresentationDuration="PT0H0M10.0S">
+              <BaseURL>media/</BaseURL>
+              <Period>
+                <AdaptationSet contentType="video" mimeType="video/mp4">
+                  <Representation id="v" bandwidth="900000"/>
+                </AdaptationSet>
+                <AdaptationSet contentType="audio" mimeType="audio/mp4">
+                  <SegmentTemplate timescale="1000" duration="4000" startNumber="1"
+                    initialization="$RepresentationID$/init.mp4"
+                    media="$RepresentationID$/seg-$Number%03d$.m4s?b=$Bandwidth$&amp;x=1"/>
+                  <Representation id="lo" bandwidth="64000"/>
+                  <Representation id="hi" bandwidth="128000"/>
+                </AdaptationSet>
+              </Period>
+            </MPD>"#;
+
+        let playlist = MediaPlaylist::parse("https://cdn.example/show/manifest.mpd", text);
+
+        assert!(playlist.ended);
+        assert_eq!(playlist.format, Format::Mp4);
+        assert_eq!(playlist.map.as_deref(), Some("https://cdn.example/show/media/hi/init.mp4"));
+        assert_eq!(playlist.segments.len(), 3);
+        assert_eq!(playlist.segments[0].uri,
+            "https://cdn.example/show/media/hi/seg-001.m4s?b=128000&x=1");
+        assert_eq!(playlist.segments[2].sequence, 3);
+        assert_eq!(playlist.segments[0].duration, 4.0);
+    }
+
+    #[test]
+    fn parses_dash_timelines() {
+        let text = r#"<MPD type="dynamic" minimumUpdatePeriod="PT2S">
+              <Period start="PT0S">
+                <AdaptationSet mimeType="audio/mp4">
+                  <ContentProtection schemeIdUri="urn:mpeg:dash:mp4protection:2011"/>
+                  <Representation id="a" bandwidth="96000">
+                    <SegmentTemplate timescale="48000" startNumber="10"
+                      media="a/$Time$.m4s" initialization="a/init.mp4">
+                      <SegmentTimeline>
+                        <S t="96000" d="96000" r="2"/>
+                        <S d="48000"/>
+                      </SegmentTimeline>
+                    </SegmentTemplate>
+                  </Representation>
+                </AdaptationSet>
+              </Period>
+            </MPD>"#;
+
+        let playlist = MediaPlaylist::parse_dash("https://live.example/a.mpd", text, 0.0);
+        let uris = playlist.segments.iter().map(|segment| segment.uri.clone())
+            .collect::<Vec<_>>();
+
+        assert!(!playlist.ended);
+        assert!(playlist.encrypted);
+        assert_eq!(playlist.target, 2.0);
+        assert_eq!(uris, vec!["https://live.example/a/96000.m4s",
+            "https://live.example/a/192000.m4s", "https://live.example/a/288000.m4s",
+            "https://live.example/a/384000.m4s"]);
+        assert_eq!(playlist.segments[3].sequence, 13);
+        assert_eq!(playlist.segments[3].duration, 1.0);
+    }
+
+    #[test]
+    fn numbers_live_dash_segments_from_the_clock() {
+        let text = r#"<MPD type="dynamic" availabilityStartTime="2024-01-01T00:00:00Z">
+              <Period><AdaptationSet contentType="audio">
+                <SegmentTemplate duration="2" startNumber="5" media="s$Number$.m4s"/>
+                <Representation id="a" bandwidth="1"/>
+              </AdaptationSet></Period>
+            </MPD>"#;
+
+        // 2024-01-01 is 1704067200 seconds after the epoch; 25 seconds in, 12 are complete
+        let playlist = MediaPlaylist::parse_dash("http://x/live.mpd", text, 1704067225.0);
+        let numbers = playlist.segments.iter().map(|segment| segment.sequence)
+            .collect::<Vec<_>>();
+
+        assert_eq!(numbers, (7..17).collect::<Vec<_>>());
+        assert_eq!(playlist.segments[0].uri, "http://x/s7.m4s");
+        assert_eq!(playlist.target, 2.0);
+    }
+
+    #[test]
+    fn parses_dash_segment_lists() {
+        let text = r#"<MPD><Period><AdaptationSet mimeType="audio/mp4">
+              <Representation id="a" bandwidth="1">
+                <SegmentList timescale="10" duration="20">
+                  <Initialization sourceURL="init.mp4"/>
+                  <SegmentURL media="one.m4s"/><SegmentURL media="two.m4s"/>
+                </SegmentList>
+              </Representation>
+            </AdaptationSet></Period></MPD>"#;
+
+        let playlist = MediaPlaylist::parse("http://x/dir/list.mpd", text);
+
+        assert_eq!(playlist.map.as_deref(), Some("http://x/dir/init.mp4"));
+        assert_eq!(playlist.segments.iter().map(|segment| segment.uri.as_str())
+            .collect::<Vec<_>>(), vec!["http://x/dir/one.m4s", "http://x/dir/two.m4s"]);
+        assert_eq!(playlist.segments[1].duration, 2.0);
+    }
+
+    #[test]
+    fn skips_dash_without_mp4_audio() {
+        let text = r#"<MPD><Period><AdaptationSet mimeType="audio/webm">
+              <Representation id="a" bandwidth="1"/>
+            </AdaptationSet></Period></MPD>"#;
+
+        assert!(MediaPlaylist::parse("http://x/a.mpd", text).segments.is_empty());
+    }
+
+    #[test]
+    fn parses_iso_times() {
+        assert_eq!(parse_iso_duration("PT1H2M3.5S"), Some(3723.5));
+        assert_eq!(parse_iso_duration("P1DT1S"), Some(86401.0));
+        assert_eq!(parse_iso_duration("1S"), None);
+        assert_eq!(parse_datetime("1970-01-01T00:00:00Z"), Some(0.0));
+        assert_eq!(parse_datetime("2024-02-29T12:30:15.5Z"), Some(1709209815.5));
+        assert_eq!(parse_datetime("2024-02-29T14:30:15.5+02:00"), Some(1709209815.5));
+    }
+
+    #[test]
+    fn recognizes_playlist_urls() {
+        assert!(is_playlist("https://radio.example/live/index.m3u8?token=abc"));
+        assert!(is_playlist("https://radio.example/live/Manifest.MPD"));
+        assert!(!is_playlist("https://radio.example/live/stream.mp3"));
+    }
+}
\ No newline at end of file
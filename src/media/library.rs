@@ -0,0 +1,382 @@
+use rusqlite::{Connection, params};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    sync::{Arc, Mutex, mpsc::{self, Sender}},
+    thread,
+    time::{Duration, UNIX_EPOCH}
+};
+
+use crate::{
+    media::waveform::Waveform,
+    utils::{data::{self, TrackMetadata}, filesys}
+};
+
+const SCHEMA_VERSION: i32 = 5;
+
+enum Job {
+    Index(String),
+    Reindex(String),
+    Remove(String)
+}
+
+impl Entry {
+    //** Getters **//
+    pub fn get_format(&self) -> String {
+        self.format.clone()
+    }
+
+    pub fn get_metadata(&self) -> TrackMetadata {
+        self.metadata.clone()
+    }
+
+    pub fn get_path(&self) -> String {
+        self.path.clone()
+    }
+
+    pub fn get_total_time(&self) -> Option<Duration> {
+        self.metadata.get_duration()
+    }
+
+    //** Misc. **//
+    fn is_current(&self, mtime: i64, size: i64) -> bool {
+        self.mtime == mtime && self.size == size
+    }
+
+    fn read(path: &str) -> Option<Self> {
+        let (mtime, size) = stat(path)?;
+
+        // Files without readable tags (video, raw WAV) are still indexed with empty metadata
+        let mut metadata = data::read_metadata(path).unwrap_or_default();
+
+        if metadata.get_duration().is_none() { metadata.set_duration(data::get_total_time(path)); };
+
+        Some(Self {
+            // Numbers
+            mtime,
+            size,
+
+            // Strings
+            format: Path::new(path).extension().and_then(|ext| ext.to_str())
+                .unwrap_or_default().to_lowercase(),
+            path: path.to_string(),
+
+            // Misc.
+            metadata
+        })
+    }
+}
+
+impl Library {
+    //** Getters **//
+    pub fn get(&self, source: &str) -> Option<Entry> {
+        if source.is_empty() { return None; }
+
+        if !self.is_checked(source) {
+            self.set_checked(source);
+
+            let _ = self.sender.send(Job::Index(source.to_string()));
+        };
+
+        self.lookup(source)
+    }
+
+    pub fn get_album(&self, source: &str) -> String {
+        self.get_metadata(source).get_album().unwrap_or_default()
+    }
+
+    pub fn get_entries(&self) -> Arc<Mutex<HashMap<String, Entry>>> {
+        self.entries.clone()
+    }
+
+    pub fn get_metadata(&self, source: &str) -> TrackMetadata {
+        self.get(source).map(|entry| entry.get_metadata()).unwrap_or_default()
+    }
+
+    pub fn get_title(&self, source: &str) -> String {
+        self.get_metadata(source).get_title().unwrap_or_default()
+    }
+
+    pub fn get_total_time(&self, source: &str) -> Option<Duration> {
+        // Misses are indexed by the worker, so a new track has no length for a frame or two
+        let duration = self.get(source).and_then(|entry| entry.get_total_time())?;
+
+        // Padding keeps end-of-track detection from cutting off the last of the audio
+        Some(duration + Duration::from_secs(1))
+    }
+
+    pub fn get_waveform(&self, source: &str) -> Option<Waveform> {
+        if source.is_empty() { return None; }
+
+        let mut waveforms = self.waveforms.lock().ok()?;
+
+        match waveforms.get(source) {
+            Some(waveform) => waveform.clone(),
+
+            None => {
+                // Queued sources hold None until the worker has a result
+                waveforms.insert(source.to_string(), None);
+
+                let _ = self.waveform_sender.send(source.to_string());
+
+                None
+            }
+        }
+    }
+
+    fn is_checked(&self, source: &str) -> bool {
+        match self.checked.lock() {
+            Ok(checked) => checked.contains(source),
+            Err(_) => true
+        }
+    }
+
+    fn lookup(&self, source: &str) -> Option<Entry> {
+        self.entries.lock().ok()?.get(source).cloned()
+    }
+
+    //** Misc. **//
+    pub fn new() -> Self {
+        let connection = open(filesys::get_library_path());
+        let mut entries: HashMap<String, Entry> = HashMap::new();
+
+        if let Some(conn) = &connection {
+            for entry in load(conn) { entries.insert(entry.get_path(), entry); };
+        };
+
+        let entries = Arc::new(Mutex::new(entries));
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let shared = entries.clone();
+
+        thread::spawn(move || {
+            for job in receiver {
+                let entry = match job {
+                    Job::Index(source) => {
+                        let (mtime, size) = match stat(&source) {
+                            Some(meta) => meta,
+                            None => continue
+                        };
+
+                        let cached = shared.lock().ok().and_then(|map| map.get(&source).cloned());
+
+                        if let Some(entry) = cached {
+                            if entry.is_current(mtime, size) { continue; };
+                        };
+
+                        match Entry::read(&source) {
+                            Some(entry) => entry,
+                            None => continue
+                        }
+                    },
+
+                    Job::Reindex(source) => match Entry::read(&source) {
+                        Some(entry) => entry,
+                        None => continue
+                    },
+
+                    Job::Remove(source) => {
+                        if let Ok(mut map) = shared.lock() { map.remove(&source); };
+                        if let Some(conn) = &connection { let _ = delete(conn, &source); };
+
+                        continue;
+                    }
+                };
+
+                if let Ok(mut map) = shared.lock() { map.insert(entry.get_path(), entry.clone()); };
+                if let Some(conn) = &connection { let _ = store(conn, &entry); };
+            };
+        });
+
+        let waveforms = Arc::new(Mutex::new(HashMap::new()));
+        let (waveform_sender, waveform_receiver) = mpsc::channel::<String>();
+        let decoded = waveforms.clone();
+        let waveform_connection = open(filesys::get_library_path());
+
+        // Decoding a whole track is slow, so waveforms get their own worker
+        thread::spawn(move || {
+            for source in waveform_receiver {
+                let (mtime, size) = match stat(&source) {
+                    Some(meta) => meta,
+                    None => continue
+                };
+
+                let cached = waveform_connection.as_ref()
+                    .and_then(|conn| load_waveform(conn, &source, mtime, size));
+
+                let waveform = cached.or_else(|| {
+                    let waveform = Waveform::compute(&source)?;
+
+                    if let Some(conn) = &waveform_connection {
+                        let _ = store_waveform(conn, &source, mtime, size, &waveform);
+                    };
+
+                    Some(waveform)
+                });
+
+                if let Ok(mut map) = decoded.lock() { map.insert(source, waveform); };
+            };
+        });
+
+        Self {
+            checked: Arc::new(Mutex::new(HashSet::new())),
+            entries,
+            sender,
+            waveform_sender,
+            waveforms
+        }
+    }
+
+    pub fn refresh(&self, source: &str) {
+        self.set_checked(source);
+
+        if let Ok(mut waveforms) = self.waveforms.lock() { waveforms.remove(source); };
+
+        let _ = self.sender.send(Job::Reindex(source.to_string()));
+    }
+
+    pub fn remove(&self, source: &str) {
+        if let Ok(mut entries) = self.entries.lock() { entries.remove(source); };
+        if let Ok(mut waveforms) = self.waveforms.lock() { waveforms.remove(source); };
+        if let Ok(mut checked) = self.checked.lock() { checked.remove(source); };
+
+        let _ = self.sender.send(Job::Remove(source.to_string()));
+    }
+
+    pub fn scan(&self, sources: Vec<String>) {
+        for source in sources { let _ = self.get(&source); };
+    }
+
+    //** Setters **//
+    fn set_checked(&self, source: &str) {
+        if let Ok(mut checked) = self.checked.lock() { checked.insert(source.to_string()); };
+    }
+}
+
+fn delete(conn: &Connection, path: &str) -> rusqlite::Result<usize> {
+    conn.execute("DELETE FROM tracks WHERE path = ?1", params![path])
+}
+
+fn load(conn: &Connection) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    let query = "SELECT path, mtime, size, format, metadata FROM tracks";
+
+    if let Ok(mut statement) = conn.prepare(query) {
+        let rows = statement.query_map([], |row| {
+            Ok(Entry {
+                // Numbers
+                mtime: row.get(1)?,
+                size: row.get(2)?,
+
+                // Strings
+                format: row.get(3)?,
+                path: row.get(0)?,
+
+                // Misc.
+                metadata: TrackMetadata::decode(&row.get::<_, String>(4)?)
+            })
+        });
+
+        if let Ok(rows) = rows {
+            for row in rows.flatten() { entries.push(row); };
+        };
+    };
+
+    entries
+}
+
+fn load_waveform(conn: &Connection, path: &str, mtime: i64, size: i64) -> Option<Waveform> {
+    let bytes: Vec<u8> = conn.query_row(
+        "SELECT data FROM waveforms WHERE path = ?1 AND mtime = ?2 AND size = ?3",
+        params![path, mtime, size], |row| row.get(0)
+    ).ok()?;
+
+    Waveform::decode(&bytes)
+}
+
+fn open(path: String) -> Option<Connection> {
+    let conn = Connection::open(path).ok()?;
+
+    // Index and waveform workers each hold a connection, so writers wait instead of failing
+    conn.busy_timeout(Duration::from_secs(5)).ok()?;
+
+    let _ = conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get::<_, String>(0));
+    let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).ok()?;
+
+    // The index is only a cache, so older layouts are dropped and rebuilt
+    if version < SCHEMA_VERSION {
+        conn.execute_batch(&format!(
+            "DROP TABLE IF EXISTS tracks; DROP TABLE IF EXISTS waveforms;
+            PRAGMA user_version = {};", SCHEMA_VERSION
+        )).ok()?;
+    };
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tracks (
+            path TEXT PRIMARY KEY,
+            mtime INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            format TEXT NOT NULL,
+            metadata TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS waveforms (
+            path TEXT PRIMARY KEY,
+            mtime INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            data BLOB NOT NULL
+        );"
+    ).ok()?;
+
+    Some(conn)
+}
+
+fn stat(path: &str) -> Option<(i64, i64)> {
+    let meta = fs::metadata(path).ok()?;
+
+    if !meta.is_file() { return None; }
+
+    let mtime = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+
+    Some((mtime, meta.len() as i64))
+}
+
+fn store(conn: &Connection, entry: &Entry) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT OR REPLACE INTO tracks (path, mtime, size, format, metadata)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![entry.path, entry.mtime, entry.size, entry.format, entry.metadata.encode()]
+    )
+}
+
+fn store_waveform(conn: &Connection, path: &str, mtime: i64, size: i64, waveform: &Waveform)
+    -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT OR REPLACE INTO waveforms (path, mtime, size, data) VALUES (?1, ?2, ?3, ?4)",
+        params![path, mtime, size, waveform.encode()]
+    )
+}
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    // Numbers
+    mtime: i64,
+    size: i64,
+
+    // Strings
+    format: String,
+    path: String,
+
+    // Misc.
+    metadata: TrackMetadata
+}
+
+pub struct Library {
+    checked: Arc<Mutex<HashSet<String>>>,
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+    sender: Sender<Job>,
+    waveform_sender: Sender<String>,
+    waveforms: Arc<Mutex<HashMap<String, Option<Waveform>>>>
+}
\ No newline at end of file
@@ -0,0 +1,167 @@
+use std::{
+    error::Error,
+    fs,
+    path::Path,
+    time::Duration
+};
+
+use crate::utils::data;
+
+impl Line {
+    //** Getters **//
+    pub fn get_text(&self) -> String {
+        self.text.clone()
+    }
+
+    pub fn get_time(&self) -> Option<Duration> {
+        self.time
+    }
+}
+
+impl Lyrics {
+    //** Getters **//
+    pub fn get_current(&self, elapsed: Duration) -> Option<usize> {
+        let position = elapsed.as_millis() as i64 + self.offset;
+
+        self.lines.iter().enumerate()
+            .filter_map(|(index, line)| line.time.map(|time| (index, time.as_millis() as i64)))
+            .take_while(|(_index, time)| *time <= position)
+            .last().map(|(index, _time)| index)
+    }
+
+    pub fn get_lines(&self) -> Vec<Line> {
+        self.lines.clone()
+    }
+
+    pub fn get_offset(&self) -> i64 {
+        self.offset
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.lines.iter().any(|line| line.time.is_some())
+    }
+
+    //** Misc. **//
+    pub fn load(source: &str) -> Option<Self> {
+        let sidecar = fs::read_to_string(sidecar_path(source)).ok();
+        let text = sidecar.or_else(|| data::read_lyrics(source))?;
+        let lyrics = Self::parse(&text);
+
+        if lyrics.lines.is_empty() { None } else { Some(lyrics) }
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut lines: Vec<Line> = Vec::new();
+        let mut offset = 0;
+
+        for raw in text.lines() {
+            let mut rest = raw.trim();
+            let mut times: Vec<Duration> = Vec::new();
+
+            while let Some(tag) = rest.strip_prefix('[').and_then(|tag| tag.split_once(']')) {
+                let (inner, after) = tag;
+
+                if let Some(value) = inner.strip_prefix("offset:") {
+                    offset = value.trim().parse::<i64>().unwrap_or(0);
+                } else if let Some(time) = parse_time(inner) { times.push(time); }
+                // Other ID tags ([ar:], [ti:], ...) are metadata and never displayed
+                else if !inner.split_once(':').is_some_and(|(key, _value)| !key.is_empty()
+                    && key.chars().all(|c| c.is_ascii_alphabetic())) { break; };
+
+                rest = after;
+            };
+
+            let text = rest.trim().to_string();
+
+            if times.is_empty() {
+                if rest.len() != raw.trim().len() && text.is_empty() { continue; };
+
+                lines.push(Line { text, time: None });
+            } else {
+                for time in times { lines.push(Line { text: text.clone(), time: Some(time) }); };
+            };
+        };
+
+        // Lines stamped more than once are repeated, so order everything by time
+        if lines.iter().all(|line| line.time.is_some()) { lines.sort_by_key(|line| line.time); };
+
+        while lines.last().is_some_and(|line| line.text.is_empty() && line.time.is_none()) {
+            lines.pop();
+        };
+
+        Self {
+            lines,
+            offset
+        }
+    }
+
+    pub fn plain(text: &str) -> Self {
+        Self {
+            lines: text.lines().map(|text| Line { text: text.trim().to_string(), time: None })
+                .collect(),
+            offset: 0
+        }
+    }
+
+    pub fn save(&self, source: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(sidecar_path(source), self.to_lrc())?;
+
+        Ok(())
+    }
+
+    pub fn to_lrc(&self) -> String {
+        let mut text = String::new();
+
+        if self.offset != 0 { text += &format!("[offset:{}]\n", self.offset); };
+
+        for line in &self.lines {
+            match line.time {
+                Some(time) => text += &format!("[{}]{}\n", format_time(time), line.text),
+                None => text += &format!("{}\n", line.text)
+            };
+        };
+
+        text
+    }
+
+    //** Setters **//
+    pub fn set_offset(&mut self, offset: i64) {
+        self.offset = offset;
+    }
+
+    pub fn set_time(&mut self, index: usize, time: Option<Duration>) {
+        if let Some(line) = self.lines.get_mut(index) { line.time = time; };
+    }
+}
+
+fn format_time(time: Duration) -> String {
+    let hundredths = time.as_millis() / 10;
+
+    format!("{:02}:{:02}.{:02}", hundredths / 6000, (hundredths / 100) % 60, hundredths % 100)
+}
+
+fn parse_time(text: &str) -> Option<Duration> {
+    let (minutes, seconds) = text.split_once(':')?;
+    let minutes = minutes.trim().parse::<u64>().ok()?;
+    let seconds = seconds.trim().replace(':', ".").parse::<f64>().ok()?;
+
+    if !(0.0..60.0).contains(&seconds) { return None; }
+
+    Some(Duration::from_secs(minutes * 60) + Duration::from_secs_f64(seconds))
+}
+
+pub fn sidecar_path(source: &str) -> String {
+    Path::new(source).with_extension("lrc").display().to_string()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    text: String,
+    time: Option<Duration>
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Lyrics {
+    lines: Vec<Line>,
+    offset: i64
+}
\ No newline at end of file
@@ -1,2 +1,23 @@
+pub mod batch;
+pub mod broadcast;
+pub mod encoder;
+pub mod hls;
+pub mod library;
+pub mod lyrics;
+pub mod mpd;
+#[cfg(target_os = "linux")]
+pub mod mpris;
+pub mod organize;
 pub mod playback;
-pub mod source;
\ No newline at end of file
+pub mod podcast;
+pub mod remote;
+pub mod scanner;
+pub mod server;
+pub mod source;
+pub mod stream;
+pub mod subtitles;
+pub mod tap;
+pub mod video;
+pub mod visualizer;
+pub mod watcher;
+pub mod waveform;
\ No newline at end of file
@@ -0,0 +1,757 @@
+use egui::Context;
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    io::{BufRead, BufReader, Write},
+    net::{Shutdown, TcpStream},
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender}
+    },
+    thread::{self, JoinHandle},
+    time::Duration
+};
+
+use crate::{
+    media::{
+        library::Entry,
+        remote::{Command, PlayerState}
+    },
+    utils::{filesys, web}
+};
+
+const GREETING: &str = "OK MPD 0.23.0\n";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Error codes from the protocol's ack.h
+const ACK_ARG: u8 = 2;
+const ACK_PASSWORD: u8 = 3;
+const ACK_PERMISSION: u8 = 4;
+const ACK_UNKNOWN: u8 = 5;
+const ACK_NO_EXIST: u8 = 50;
+const ACK_SYSTEM: u8 = 52;
+
+const COMMANDS: [&str; 23] = [
+    "add", "close", "command_list_begin", "command_list_end", "command_list_ok_begin",
+    "commands", "currentsong", "find", "idle", "list", "next", "noidle", "password", "pause",
+    "ping", "play", "playid", "playlistinfo", "previous", "search", "setvol", "status", "stop"
+];
+
+// Commands a client may send before it gives the password
+const OPEN_COMMANDS: [&str; 4] = ["close", "commands", "password", "ping"];
+
+const TAGS: [(&str, &str); 8] = [
+    ("album", "Album"), ("albumartist", "AlbumArtist"), ("artist", "Artist"), ("date", "Date"),
+    ("file", "file"), ("genre", "Genre"), ("title", "Title"), ("track", "Track")
+];
+
+enum Event {
+    Changed(&'static str),
+    Closed,
+    Line(String)
+}
+
+type Ack = (u8, String);
+
+impl Mpd {
+    //** Getters **//
+    pub fn get_clients(&self) -> usize {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    pub fn get_message(&self) -> String {
+        self.message.clone()
+    }
+
+    pub fn get_password(&self) -> String {
+        self.password.clone()
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.as_ref().is_some_and(|running| running.load(Ordering::SeqCst))
+    }
+
+    //** Misc. **//
+    pub fn new(context: Context, library: Arc<Mutex<HashMap<String, Entry>>>, port: u16,
+        password: String) -> Self {
+        let (sender, commands) = mpsc::channel();
+
+        Self {
+            // Settings
+            password,
+            port,
+
+            // Shared
+            clients: Arc::new(Mutex::new(Vec::new())),
+            connected: Arc::new(AtomicUsize::new(0)),
+            context,
+            library,
+            snapshot: Arc::new(Mutex::new(Snapshot::default())),
+
+            // Thread
+            accept: None,
+            commands,
+            message: String::new(),
+            running: None,
+            sender
+        }
+    }
+
+    pub fn poll(&mut self) -> Vec<Command> {
+        self.commands.try_iter().collect()
+    }
+
+    pub fn start(&mut self) {
+        self.stop();
+
+        // Without a password only clients on this machine may connect
+        let host = if self.password.is_empty() { "127.0.0.1" } else { "0.0.0.0" };
+
+        let running = Arc::new(AtomicBool::new(true));
+
+        let shared = Shared {
+            clients: self.clients.clone(),
+            connected: self.connected.clone(),
+            context: self.context.clone(),
+            library: self.library.clone(),
+            password: self.password.clone(),
+            running: running.clone(),
+            sender: self.sender.clone(),
+            snapshot: self.snapshot.clone()
+        };
+
+        let handler = move |stream| { let _ = respond(stream, shared.clone()); };
+
+        match web::listen(host, self.port, running.clone(), handler) {
+            Ok(accept) => {
+                self.accept = Some(accept);
+                self.message = String::new();
+                self.running = Some(running);
+            },
+
+            Err(error) => {
+                self.message = format!("Unable to listen on port {}: {}", self.port, error);
+            }
+        };
+    }
+
+    pub fn stop(&mut self) {
+        // Clients notice the flag within a poll interval and hang up
+        if let Some(running) = self.running.take() { running.store(false, Ordering::SeqCst); };
+
+        if let Some(accept) = self.accept.take() { let _ = accept.join(); };
+    }
+
+    //** Setters **//
+    pub fn set_password(&mut self, password: String) {
+        self.password = password;
+    }
+
+    pub fn set_port(&mut self, port: u16) {
+        self.port = port;
+    }
+
+    pub fn set_state(&mut self, state: PlayerState, queue: Vec<String>) {
+        let mut changed = Vec::new();
+
+        if let Ok(mut snapshot) = self.snapshot.lock() {
+            let previous = &snapshot.state;
+
+            if !previous.get_now_playing().is_same(&state.get_now_playing()) {
+                changed.push("player");
+            };
+
+            if previous.get_volume() != state.get_volume() { changed.push("mixer"); };
+
+            if previous.get_repeat() != state.get_repeat()
+                || previous.is_shuffled() != state.is_shuffled() { changed.push("options"); };
+
+            if snapshot.queue != queue {
+                changed.push("playlist");
+
+                snapshot.queue = queue;
+                snapshot.version += 1;
+            };
+
+            snapshot.state = state;
+        };
+
+        if changed.is_empty() { return; }
+
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain(|client| {
+                changed.iter().all(|subsystem| client.send(Event::Changed(*subsystem)).is_ok())
+            });
+        };
+    }
+}
+
+impl Drop for Mpd {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Snapshot {
+    //** Getters **//
+    fn get_index(&self) -> Option<usize> {
+        let source = self.state.get_now_playing().get_source();
+
+        self.queue.iter().position(|entry| *entry == source)
+    }
+}
+
+fn ack(code: u8, index: usize, command: &str, message: &str) -> String {
+    format!("ACK [{}@{}] {{{}}} {}\n", code, index, command, message)
+}
+
+fn bad(message: &str) -> Ack {
+    (ACK_ARG, message.to_string())
+}
+
+fn current_song(shared: &Shared, snapshot: &Snapshot) -> String {
+    let now_playing = snapshot.state.get_now_playing();
+    let source = now_playing.get_source();
+
+    if source.is_empty() { return String::new(); }
+
+    let mut song = match lookup(shared, &source) {
+        Some(entry) => describe(&entry),
+
+        // Streams and unindexed files only have what the player shows
+        None => {
+            let mut song = format!("file: {}\n", source);
+
+            for (label, value) in [("Title", now_playing.get_title()),
+                ("Artist", now_playing.get_artist()), ("Album", now_playing.get_album())] {
+                if !value.is_empty() { song += &format!("{}: {}\n", label, value); };
+            };
+
+            song
+        }
+    };
+
+    if let Some(index) = snapshot.get_index() {
+        song += &format!("Pos: {}\nId: {}\n", index, index + 1);
+    };
+
+    song
+}
+
+fn denied(command: &str) -> Ack {
+    (ACK_PERMISSION, format!("you don't have permission for \"{}\"", command))
+}
+
+fn describe(entry: &Entry) -> String {
+    let mut song = format!("file: {}\n", entry.get_path());
+
+    for (name, heading) in TAGS {
+        if name == "file" { continue; }
+
+        if let Some(value) = tag(entry, name) { song += &format!("{}: {}\n", heading, value); };
+    };
+
+    if let Some(duration) = entry.get_total_time() {
+        song += &format!("Time: {}\nduration: {:.3}\n", duration.as_secs(),
+            duration.as_secs_f64());
+    };
+
+    song
+}
+
+fn execute(args: &[String], shared: &Shared) -> Result<String, Ack> {
+    let snapshot = shared.snapshot.lock().map(|snapshot| snapshot.clone()).unwrap_or_default();
+    let playing = snapshot.state.get_now_playing().get_state() == "playing";
+    let argument = args.get(1).cloned();
+
+    match args[0].as_str() {
+        "add" => {
+            let uri = argument.ok_or(missing())?;
+
+            // Clients may only queue what the library holds, not any file on the machine
+            let known = shared.library.lock().is_ok_and(|library| library.contains_key(&uri));
+
+            if !filesys::is_url(uri.clone()) && !known {
+                return Err((ACK_NO_EXIST, "No such song".to_string()));
+            };
+
+            send(shared, Command::Queue(uri))
+        },
+
+        "commands" => Ok(COMMANDS.iter().map(|name| format!("command: {}\n", name)).collect()),
+        "currentsong" => Ok(current_song(shared, &snapshot)),
+        "find" => search(args, shared, true),
+        "list" => list(args, shared),
+        "next" => send(shared, Command::Next),
+
+        "pause" => match argument.as_deref() {
+            Some("0") => send(shared, Command::Play),
+            Some("1") => send(shared, Command::Pause),
+            _ => send(shared, if playing { Command::Pause } else { Command::Play })
+        },
+
+        "ping" => Ok(String::new()),
+
+        "play" => match argument {
+            Some(position) => {
+                send(shared, Command::PlayIndex(index(&position, snapshot.queue.len())?))
+            },
+
+            None => send(shared, Command::Play)
+        },
+
+        "playid" => match argument {
+            Some(id) => {
+                let id = id.parse::<usize>().map_err(|_| bad("Invalid song id"))?;
+
+                if id == 0 || id > snapshot.queue.len() {
+                    return Err((ACK_NO_EXIST, "No such song".to_string()));
+                };
+
+                send(shared, Command::PlayIndex(id - 1))
+            },
+
+            None => send(shared, Command::Play)
+        },
+
+        "playlistinfo" => {
+            let only = match argument {
+                Some(position) => Some(index(&position, snapshot.queue.len())?),
+                None => None
+            };
+
+            Ok(snapshot.queue.iter().enumerate()
+                .filter(|(position, _)| only.is_none_or(|only| only == *position))
+                .map(|(position, source)| {
+                    let song = match lookup(shared, source) {
+                        Some(entry) => describe(&entry),
+                        None => format!("file: {}\n", source)
+                    };
+
+                    format!("{}Pos: {}\nId: {}\n", song, position, position + 1)
+                }).collect())
+        },
+
+        "previous" => send(shared, Command::Previous),
+        "search" => search(args, shared, false),
+
+        "setvol" => {
+            let volume = argument.ok_or(missing())?.parse::<i32>()
+                .map_err(|_| bad("Invalid volume"))?;
+
+            send(shared, Command::Volume(volume.clamp(0, 100)))
+        },
+
+        "status" => Ok(status(&snapshot)),
+        "stop" => send(shared, Command::Stop),
+
+        name => Err((ACK_UNKNOWN, format!("unknown command \"{}\"", name)))
+    }
+}
+
+fn filters(args: &[String]) -> Result<Vec<(String, String)>, Ack> {
+    if args.len() % 2 != 0 { return Err(bad("Incorrect number of arguments")); }
+
+    args.chunks(2).map(|pair| {
+        let name = pair[0].to_lowercase();
+
+        if name != "any" && label(&name).is_none() {
+            return Err(bad(&format!("Unknown tag type \"{}\"", pair[0])));
+        };
+
+        Ok((name, pair[1].clone()))
+    }).collect()
+}
+
+fn idle(stream: &mut TcpStream, shared: &Shared, events: &Receiver<Event>,
+    changed: &mut BTreeSet<&'static str>, wanted: &[String]) -> Result<bool, Box<dyn Error>> {
+    loop {
+        let matched = changed.iter().copied().filter(|subsystem| {
+            wanted.is_empty() || wanted.iter().any(|name| name.as_str() == *subsystem)
+        }).collect::<Vec<_>>();
+
+        if !matched.is_empty() {
+            let mut reply = String::new();
+
+            for subsystem in matched {
+                changed.remove(subsystem);
+
+                reply += &format!("changed: {}\n", subsystem);
+            };
+
+            stream.write_all((reply + "OK\n").as_bytes())?;
+
+            return Ok(true);
+        };
+
+        match events.recv_timeout(POLL_INTERVAL) {
+            Ok(Event::Changed(subsystem)) => { changed.insert(subsystem); },
+
+            Ok(Event::Line(line)) if line.trim() == "noidle" => {
+                stream.write_all(b"OK\n")?;
+
+                return Ok(true);
+            },
+
+            // Anything else while idle is a protocol error, which MPD answers by hanging up
+            Ok(Event::Line(_)) | Ok(Event::Closed) | Err(RecvTimeoutError::Disconnected) => {
+                return Ok(false);
+            },
+
+            Err(RecvTimeoutError::Timeout) => {
+                if !shared.running.load(Ordering::SeqCst) { return Ok(false); }
+            }
+        };
+    };
+}
+
+fn index(position: &str, length: usize) -> Result<usize, Ack> {
+    position.parse::<usize>().ok().filter(|position| *position < length)
+        .ok_or(bad("Bad song index"))
+}
+
+fn label(name: &str) -> Option<&'static str> {
+    TAGS.iter().find(|(tag, _)| *tag == name).map(|(_, label)| *label)
+}
+
+fn list(args: &[String], shared: &Shared) -> Result<String, Ack> {
+    let kind = args.get(1).map(|kind| kind.to_lowercase()).ok_or(missing())?;
+    let label = label(&kind).ok_or(bad(&format!("Unknown tag type \"{}\"", kind)))?;
+
+    // Older clients list an artist's albums with the artist as the only filter
+    let filters = match (kind.as_str(), &args[2..]) {
+        ("album", [artist]) => vec![("artist".to_string(), artist.clone())],
+        (_, rest) => filters(rest)?
+    };
+
+    let values = shared.library.lock().map(|library| {
+        library.values().filter(|entry| matches(entry, &filters, true))
+            .filter_map(|entry| tag(entry, &kind)).collect::<BTreeSet<_>>()
+    }).unwrap_or_default();
+
+    Ok(values.iter().map(|value| format!("{}: {}\n", label, value)).collect())
+}
+
+fn lookup(shared: &Shared, source: &str) -> Option<Entry> {
+    shared.library.lock().ok()?.get(source).cloned()
+}
+
+fn matches(entry: &Entry, filters: &[(String, String)], exact: bool) -> bool {
+    filters.iter().all(|(name, wanted)| {
+        let check = |value: String| match exact {
+            true => value == *wanted,
+            false => value.to_lowercase().contains(&wanted.to_lowercase())
+        };
+
+        match name.as_str() {
+            "any" => TAGS.iter().filter_map(|(tag_name, _)| tag(entry, tag_name)).any(check),
+            name => tag(entry, name).is_some_and(check)
+        }
+    })
+}
+
+fn missing() -> Ack {
+    bad("Missing argument")
+}
+
+fn next_line(shared: &Shared, events: &Receiver<Event>, changed: &mut BTreeSet<&'static str>)
+    -> Option<String> {
+    while shared.running.load(Ordering::SeqCst) {
+        match events.recv_timeout(POLL_INTERVAL) {
+            // Changes between commands wait for the client's next idle
+            Ok(Event::Changed(subsystem)) => { changed.insert(subsystem); },
+            Ok(Event::Line(line)) => return Some(line),
+            Ok(Event::Closed) | Err(RecvTimeoutError::Disconnected) => return None,
+            Err(RecvTimeoutError::Timeout) => {}
+        };
+    };
+
+    None
+}
+
+fn respond(mut stream: TcpStream, shared: Shared) -> Result<(), Box<dyn Error>> {
+    stream.set_nonblocking(false)?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+    let (sender, events) = mpsc::channel();
+    let reader = stream.try_clone()?;
+    let lines = sender.clone();
+
+    // Reading on its own thread lets idle wait for both the client and the player at once
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else { break };
+
+            if lines.send(Event::Line(line)).is_err() { break; }
+        };
+
+        let _ = lines.send(Event::Closed);
+    });
+
+    shared.clients.lock().map_err(|_| "Clients unavailable")?.push(sender);
+    shared.connected.fetch_add(1, Ordering::SeqCst);
+
+    let result = session(&mut stream, &shared, &events);
+
+    shared.connected.fetch_sub(1, Ordering::SeqCst);
+
+    let _ = stream.shutdown(Shutdown::Both);
+
+    result
+}
+
+fn search(args: &[String], shared: &Shared, exact: bool) -> Result<String, Ack> {
+    let filters = filters(&args[1..])?;
+
+    if filters.is_empty() { return Err(missing()); }
+
+    // Matches are described under the lock so the library is never copied
+    let mut found = shared.library.lock().map(|library| {
+        library.values().filter(|entry| matches(entry, &filters, exact))
+            .map(|entry| (entry.get_path(), describe(entry))).collect::<Vec<_>>()
+    }).unwrap_or_default();
+
+    found.sort();
+
+    Ok(found.into_iter().map(|(_, song)| song).collect())
+}
+
+fn send(shared: &Shared, command: Command) -> Result<String, Ack> {
+    shared.sender.send(command).map_err(|_| (ACK_SYSTEM, "Player unavailable".to_string()))?;
+    shared.context.request_repaint();
+
+    Ok(String::new())
+}
+
+fn session(stream: &mut TcpStream, shared: &Shared, events: &Receiver<Event>)
+    -> Result<(), Box<dyn Error>> {
+    let mut allowed = shared.password.is_empty();
+    let mut changed = BTreeSet::new();
+    let mut list: Option<(bool, Vec<Vec<String>>)> = None;
+
+    stream.write_all(GREETING.as_bytes())?;
+
+    while let Some(line) = next_line(shared, events, &mut changed) {
+        let args = split(&line);
+        let Some(name) = args.first().cloned() else { continue };
+
+        if let Some((verbose, commands)) = list.as_mut() {
+            if name != "command_list_end" {
+                commands.push(args);
+
+                continue;
+            };
+
+            let mut reply = String::new();
+            let mut failed = false;
+
+            // A list stops at its first failure and reports where it happened
+            for (index, command) in commands.iter().enumerate() {
+                match execute(command, shared) {
+                    Ok(output) => {
+                        reply += &output;
+
+                        if *verbose { reply += "list_OK\n"; };
+                    },
+
+                    Err((code, message)) => {
+                        reply += &ack(code, index, &command[0], &message);
+                        failed = true;
+
+                        break;
+                    }
+                };
+            };
+
+            if !failed { reply += "OK\n"; };
+
+            list = None;
+
+            stream.write_all(reply.as_bytes())?;
+
+            continue;
+        };
+
+        match name.as_str() {
+            "close" => break,
+
+            "password" => {
+                let reply = match args.get(1) {
+                    Some(given) if !shared.password.is_empty() && *given == shared.password => {
+                        allowed = true;
+
+                        "OK\n".to_string()
+                    },
+
+                    Some(_) => ack(ACK_PASSWORD, 0, &name, "incorrect password"),
+                    None => ack(ACK_ARG, 0, &name, "Missing argument")
+                };
+
+                stream.write_all(reply.as_bytes())?;
+            },
+
+            // Lists cannot start before the password either, so they need no check of their own
+            command if !allowed && !OPEN_COMMANDS.contains(&command) => {
+                let (code, message) = denied(command);
+
+                stream.write_all(ack(code, 0, command, &message).as_bytes())?;
+            },
+
+            "command_list_begin" => list = Some((false, Vec::new())),
+            "command_list_ok_begin" => list = Some((true, Vec::new())),
+
+            "idle" => {
+                if !idle(stream, shared, events, &mut changed, &args[1..])? { break; }
+            },
+
+            "noidle" => {},
+
+            _ => {
+                let reply = match execute(&args, shared) {
+                    Ok(output) => output + "OK\n",
+                    Err((code, message)) => ack(code, 0, &name, &message)
+                };
+
+                stream.write_all(reply.as_bytes())?;
+            }
+        };
+    };
+
+    Ok(())
+}
+
+fn split(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut characters = line.trim().chars().peekable();
+
+    while let Some(&first) = characters.peek() {
+        if first.is_whitespace() {
+            characters.next();
+
+            continue;
+        };
+
+        let mut arg = String::new();
+
+        // Quoted arguments may hold spaces and backslash-escaped quotes
+        if first == '"' {
+            characters.next();
+
+            while let Some(character) = characters.next() {
+                match character {
+                    '\\' => { if let Some(escaped) = characters.next() { arg.push(escaped); }; },
+                    '"' => break,
+                    character => arg.push(character)
+                };
+            };
+        } else {
+            while let Some(&character) = characters.peek() {
+                if character.is_whitespace() { break; }
+
+                arg.push(character);
+                characters.next();
+            };
+        };
+
+        args.push(arg);
+    };
+
+    args
+}
+
+fn status(snapshot: &Snapshot) -> String {
+    let state = &snapshot.state;
+    let now_playing = state.get_now_playing();
+    let repeat = state.get_repeat();
+
+    let playback = match now_playing.get_state().as_str() {
+        "playing" => "play",
+        "paused" => "pause",
+        _ => "stop"
+    };
+
+    let mut status = format!(concat!("volume: {}\nrepeat: {}\nrandom: {}\nsingle: {}\n",
+        "consume: 0\nplaylist: {}\nplaylistlength: {}\nstate: {}\n"), state.get_volume(),
+        (repeat == "all" || repeat == "one") as u8, state.is_shuffled() as u8,
+        (repeat == "one") as u8, snapshot.version, snapshot.queue.len(), playback);
+
+    if let Some(index) = snapshot.get_index() {
+        status += &format!("song: {}\nsongid: {}\n", index, index + 1);
+    };
+
+    if playback != "stop" {
+        let elapsed = now_playing.get_position();
+
+        status += &format!("elapsed: {:.3}\n", elapsed.as_secs_f64());
+
+        if let Some(duration) = now_playing.get_duration() {
+            status += &format!("time: {}:{}\nduration: {:.3}\n", elapsed.as_secs(),
+                duration.as_secs(), duration.as_secs_f64());
+        };
+    };
+
+    status
+}
+
+fn tag(entry: &Entry, name: &str) -> Option<String> {
+    let metadata = entry.get_metadata();
+
+    match name {
+        "album" => metadata.get_album(),
+        "albumartist" => metadata.get_album_artist(),
+        "artist" => metadata.get_artist(),
+        "date" => metadata.get_year().map(|year| year.to_string()),
+        "file" => Some(entry.get_path()),
+        "genre" => metadata.get_genre(),
+        "title" => metadata.get_title(),
+        "track" => metadata.get_track().map(|track| track.to_string()),
+        _ => None
+    }.filter(|value| !value.is_empty())
+}
+
+pub struct Mpd {
+    // Settings
+    password: String,
+    port: u16,
+
+    // Shared
+    clients: Arc<Mutex<Vec<Sender<Event>>>>,
+    connected: Arc<AtomicUsize>,
+    context: Context,
+    library: Arc<Mutex<HashMap<String, Entry>>>,
+    snapshot: Arc<Mutex<Snapshot>>,
+
+    // Thread
+    accept: Option<JoinHandle<()>>,
+    commands: Receiver<Command>,
+    message: String,
+    running: Option<Arc<AtomicBool>>,
+    sender: Sender<Command>
+}
+
+#[derive(Clone)]
+struct Shared {
+    clients: Arc<Mutex<Vec<Sender<Event>>>>,
+    connected: Arc<AtomicUsize>,
+    context: Context,
+    library: Arc<Mutex<HashMap<String, Entry>>>,
+    password: String,
+    running: Arc<AtomicBool>,
+    sender: Sender<Command>,
+    snapshot: Arc<Mutex<Snapshot>>
+}
+
+#[derive(Clone, Default)]
+struct Snapshot {
+    queue: Vec<String>,
+    state: PlayerState,
+    version: u32
+}
\ No newline at end of file
@@ -0,0 +1,642 @@
+use egui::{Context, ViewportCommand};
+
+use std::{
+    collections::{HashMap, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, mpsc::{self, Receiver, Sender}},
+    time::{Duration, Instant}
+};
+
+use zbus::{
+    blocking::{Connection, connection::Builder},
+    interface,
+    object_server::SignalEmitter,
+    zvariant::{ObjectPath, OwnedValue, Value}
+};
+
+use crate::{
+    media::remote::{Command, PlayerState},
+    utils::web
+};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.comrad";
+const IDENTITY: &str = "ComRad";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+const PATH: &str = "/org/mpris/MediaPlayer2";
+const TRACK_PATH: &str = "/org/comrad/track";
+
+// Jumps larger than this between frames are reported to clients as seeks
+const SEEK_TOLERANCE: Duration = Duration::from_secs(1);
+
+// Main cycles repeat modes in this order
+const REPEAT_ORDER: [&str; 3] = ["none", "all", "one"];
+
+impl Current {
+    //** Getters **//
+    fn get_position(&self) -> Duration {
+        let position = self.state.get_now_playing().get_position();
+
+        // Clients poll the position between frames, so playing tracks keep counting
+        match self.is_playing() {
+            true => position + self.updated.elapsed(),
+            false => position
+        }
+    }
+
+    fn get_track_id(&self) -> String {
+        let source = self.state.get_now_playing().get_source();
+
+        if source.is_empty() { return NO_TRACK.to_string(); }
+
+        let mut hasher = DefaultHasher::new();
+
+        source.hash(&mut hasher);
+
+        format!("{}/{:016x}", TRACK_PATH, hasher.finish())
+    }
+
+    fn is_playing(&self) -> bool {
+        self.state.get_now_playing().get_state() == "playing"
+    }
+
+    //** Misc. **//
+    fn new() -> Self {
+        Self {
+            art: None,
+            state: PlayerState::default(),
+            updated: Instant::now()
+        }
+    }
+}
+
+impl Mpris {
+    //** Misc. **//
+    pub fn new(context: Context) -> Self {
+        let (sender, commands) = mpsc::channel();
+
+        let current = Arc::new(Mutex::new(Current::new()));
+
+        let player = Player { context: context.clone(), current: current.clone(), sender };
+
+        // A second instance cannot take the plain name and registers under its process id
+        let connection = connect(None, BUS_NAME.to_string(), Root { context: context.clone() },
+            player.clone()).or_else(|_| {
+            connect(None, format!("{}.instance{}", BUS_NAME, std::process::id()),
+                Root { context }, player)
+        }).ok();
+
+        Self {
+            // Shared
+            connection,
+            current,
+
+            // Thread
+            commands
+        }
+    }
+
+    pub fn poll(&mut self) -> Vec<Command> {
+        self.commands.try_iter().collect()
+    }
+
+    //** Setters **//
+    pub fn set_state(&mut self, state: PlayerState, art: Option<String>) {
+        let Some(connection) = &self.connection else { return };
+        let position = state.get_now_playing().get_position();
+
+        let (changed, seeked) = match self.current.lock() {
+            Ok(mut current) => {
+                let expected = current.get_position();
+                let same_track = current.state.get_now_playing().get_source()
+                    == state.get_now_playing().get_source();
+
+                let changed = !current.state.is_same(&state) || current.art != art;
+                let seeked = same_track && position.abs_diff(expected) > SEEK_TOLERANCE;
+
+                *current = Current { art, state, updated: Instant::now() };
+
+                (changed, seeked)
+            },
+
+            Err(_) => return
+        };
+
+        if changed || seeked {
+            let _ = notify(connection, changed, seeked.then_some(position.as_micros() as i64));
+        };
+    }
+}
+
+// Helpers live apart from the interface, which exports every method it holds
+impl Player {
+    fn current(&self) -> Current {
+        self.current.lock().map(|current| current.clone()).unwrap_or(Current::new())
+    }
+
+    fn send(&self, command: Command) {
+        if self.sender.send(command).is_ok() { self.context.request_repaint(); };
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    //** Getters **//
+    #[zbus(property)]
+    fn can_control(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        self.current().state.get_now_playing().get_duration().is_some()
+    }
+
+    #[zbus(property)]
+    fn loop_status(&self) -> String {
+        match self.current().state.get_repeat().as_str() {
+            "all" => "Playlist",
+            "one" => "Track",
+            _ => "None"
+        }.to_string()
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        let current = self.current();
+        let now_playing = current.state.get_now_playing();
+        let source = now_playing.get_source();
+        let mut metadata = HashMap::new();
+
+        if let Ok(path) = ObjectPath::try_from(current.get_track_id()) {
+            insert(&mut metadata, "mpris:trackid", Value::from(path));
+        };
+
+        if source.is_empty() { return metadata; }
+
+        if let Some(duration) = now_playing.get_duration() {
+            insert(&mut metadata, "mpris:length", Value::from(duration.as_micros() as i64));
+        };
+
+        if let Some(art) = &current.art {
+            insert(&mut metadata, "mpris:artUrl", Value::from(file_url(art)));
+        };
+
+        if !now_playing.get_album().is_empty() {
+            insert(&mut metadata, "xesam:album", Value::from(now_playing.get_album()));
+        };
+
+        if !now_playing.get_artist().is_empty() {
+            insert(&mut metadata, "xesam:artist", Value::from(vec![now_playing.get_artist()]));
+        };
+
+        let url = if source.contains("://") { source.clone() } else { file_url(&source) };
+
+        insert(&mut metadata, "xesam:title", Value::from(now_playing.get_title()));
+        insert(&mut metadata, "xesam:url", Value::from(url));
+
+        metadata
+    }
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn playback_status(&self) -> String {
+        match self.current().state.get_now_playing().get_state().as_str() {
+            "playing" => "Playing",
+            "paused" => "Paused",
+            _ => "Stopped"
+        }.to_string()
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        self.current().get_position().as_micros() as i64
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn shuffle(&self) -> bool {
+        self.current().state.is_shuffled()
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.current().state.get_volume() as f64 / 100.0
+    }
+
+    //** Misc. **//
+    fn next(&self) {
+        self.send(Command::Next);
+    }
+
+    fn open_uri(&self, uri: String) {
+        // Local files arrive as escaped file urls, streams are passed on as they are
+        let source = match uri.strip_prefix("file://") {
+            Some(path) => web::decode_path(path.strip_prefix("localhost").unwrap_or(path)),
+            None => uri
+        };
+
+        self.send(Command::Open(source));
+    }
+
+    fn pause(&self) {
+        self.send(Command::Pause);
+    }
+
+    fn play(&self) {
+        self.send(Command::Play);
+    }
+
+    fn play_pause(&self) {
+        match self.current().is_playing() {
+            true => self.send(Command::Pause),
+            false => self.send(Command::Play)
+        };
+    }
+
+    fn previous(&self) {
+        self.send(Command::Previous);
+    }
+
+    fn seek(&self, offset: i64) {
+        let current = self.current();
+        let position = current.get_position().as_micros() as i64 + offset;
+
+        // Seeking past the end moves on, as the specification asks
+        match current.state.get_now_playing().get_duration() {
+            Some(duration) if position > duration.as_micros() as i64 => self.send(Command::Next),
+            Some(_) => self.send(Command::Seek(Duration::from_micros(position.max(0) as u64))),
+            None => {}
+        };
+    }
+
+    #[zbus(signal)]
+    async fn seeked(emitter: &SignalEmitter<'_>, position: i64) -> zbus::Result<()>;
+
+    fn set_position(&self, track_id: ObjectPath<'_>, position: i64) {
+        let current = self.current();
+        let Some(duration) = current.state.get_now_playing().get_duration() else { return };
+
+        // Requests for a track that has since changed are ignored
+        if track_id.as_str() != current.get_track_id() || position < 0
+            || position > duration.as_micros() as i64 { return; }
+
+        self.send(Command::Seek(Duration::from_micros(position as u64)));
+    }
+
+    fn stop(&self) {
+        self.send(Command::Stop);
+    }
+
+    //** Setters **//
+    #[zbus(property)]
+    fn set_loop_status(&mut self, status: String) {
+        let target = match status.as_str() {
+            "Playlist" => "all",
+            "Track" => "one",
+            _ => "none"
+        };
+
+        let repeat = self.current().state.get_repeat();
+        let from = REPEAT_ORDER.iter().position(|mode| *mode == repeat).unwrap_or(0);
+        let to = REPEAT_ORDER.iter().position(|mode| *mode == target).unwrap_or(0);
+
+        for _ in 0..(to + REPEAT_ORDER.len() - from) % REPEAT_ORDER.len() {
+            self.send(Command::Repeat);
+        };
+    }
+
+    #[zbus(property)]
+    fn set_rate(&mut self, _rate: f64) {}
+
+    #[zbus(property)]
+    fn set_shuffle(&mut self, shuffle: bool) {
+        if shuffle != self.current().state.is_shuffled() { self.send(Command::Shuffle); };
+    }
+
+    #[zbus(property)]
+    fn set_volume(&mut self, volume: f64) {
+        self.send(Command::Volume((volume.clamp(0.0, 1.0) * 100.0).round() as i32));
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    //** Getters **//
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn desktop_entry(&self) -> String {
+        "comrad".to_string()
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        IDENTITY.to_string()
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        ["audio/mpeg", "audio/flac", "audio/ogg", "audio/wav", "audio/mp4", "video/mp4"]
+            .map(|mime| mime.to_string()).to_vec()
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        ["file", "http", "https"].map(|scheme| scheme.to_string()).to_vec()
+    }
+
+    //** Misc. **//
+    fn quit(&self) {
+        self.context.send_viewport_cmd(ViewportCommand::Close);
+    }
+
+    fn raise(&self) {
+        self.context.send_viewport_cmd(ViewportCommand::Focus);
+    }
+}
+
+// Without an address the session bus is used
+fn connect(address: Option<&str>, name: String, root: Root, player: Player)
+    -> zbus::Result<Connection> {
+    let builder = match address {
+        Some(address) => Builder::address(address)?,
+        None => Builder::session()?
+    };
+
+    builder.name(name)?.serve_at(PATH, root)?.serve_at(PATH, player)?.build()
+}
+
+fn file_url(path: &str) -> String {
+    format!("file://{}", web::encode_path(path))
+}
+
+fn insert(metadata: &mut HashMap<String, OwnedValue>, key: &str, value: Value<'_>) {
+    if let Ok(value) = value.try_to_owned() { metadata.insert(key.to_string(), value); };
+}
+
+fn notify(connection: &Connection, changed: bool, seeked: Option<i64>) -> zbus::Result<()> {
+    let player = connection.object_server().interface::<_, Player>(PATH)?;
+    let emitter = player.signal_emitter();
+    let player = player.get();
+
+    zbus::block_on(async {
+        if changed {
+            player.can_seek_changed(emitter).await?;
+            player.loop_status_changed(emitter).await?;
+            player.metadata_changed(emitter).await?;
+            player.playback_status_changed(emitter).await?;
+            player.shuffle_changed(emitter).await?;
+            player.volume_changed(emitter).await?;
+        };
+
+        if let Some(position) = seeked { Player::seeked(emitter, position).await?; };
+
+        Ok(())
+    })
+}
+
+#[derive(Clone)]
+struct Current {
+    art: Option<String>,
+    state: PlayerState,
+    updated: Instant
+}
+
+pub struct Mpris {
+    // Shared
+    connection: Option<Connection>,
+    current: Arc<Mutex<Current>>,
+
+    // Thread
+    commands: Receiver<Command>
+}
+
+#[derive(Clone)]
+struct Player {
+    context: Context,
+    current: Arc<Mutex<Current>>,
+    sender: Sender<Command>
+}
+
+struct Root {
+    context: Context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command as Process, Stdio}
+    };
+
+    use zbus::blocking::Proxy;
+
+    use crate::media::server::NowPlaying;
+
+    const INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+    // A private daemon keeps the tests off the desktop's session bus
+    fn bus() -> (Child, String) {
+        let mut daemon = Process::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"]).stdout(Stdio::piped())
+            .stderr(Stdio::null()).spawn().expect("dbus-daemon is needed for the MPRIS tests");
+
+        let mut address = String::new();
+
+        BufReader::new(daemon.stdout.take().unwrap()).read_line(&mut address).unwrap();
+
+        (daemon, address.trim().to_string())
+    }
+
+    fn paused(current: &Mutex<Current>, repeat: &str, position: Duration) {
+        let now_playing = NowPlaying::new("/music/a.flac".to_string(), "A".to_string(),
+            String::new(), String::new(), "paused".to_string(), position,
+            Some(Duration::from_secs(60)));
+
+        *current.lock().unwrap() = Current {
+            art: None,
+            state: PlayerState::new(now_playing, repeat.to_string(), false, 100),
+            updated: Instant::now()
+        };
+    }
+
+    fn serve(address: &str) -> (Connection, Receiver<Command>, Arc<Mutex<Current>>) {
+        let (sender, commands) = mpsc::channel();
+        let context = Context::default();
+        let current = Arc::new(Mutex::new(Current::new()));
+        let player = Player { context: context.clone(), current: current.clone(), sender };
+
+        let connection = connect(Some(address), BUS_NAME.to_string(), Root { context }, player)
+            .unwrap();
+
+        (connection, commands, current)
+    }
+
+    fn text(value: &Value<'_>) -> String {
+        match value {
+            Value::Value(inner) => text(inner),
+            Value::Str(text) => text.to_string(),
+            _ => String::new()
+        }
+    }
+
+    #[test]
+    fn cycles_loop_status() {
+        let (mut daemon, address) = bus();
+        let (_server, commands, current) = serve(&address);
+        let client = Builder::address(address.as_str()).unwrap().build().unwrap();
+        let proxy = Proxy::new(&client, BUS_NAME, PATH, INTERFACE).unwrap();
+
+        // Repeat only steps forward through none, all and one
+        for (status, repeats) in [("Playlist", 0), ("Track", 1), ("None", 2)] {
+            paused(&current, "all", Duration::ZERO);
+
+            proxy.set_property("LoopStatus", status).unwrap();
+
+            assert_eq!(commands.try_iter().filter(|command| matches!(command, Command::Repeat))
+                .count(), repeats, "{}", status);
+        };
+
+        let _ = daemon.kill();
+    }
+
+    #[test]
+    fn opens_escaped_file_urls() {
+        let (mut daemon, address) = bus();
+        let (_server, commands, _) = serve(&address);
+        let client = Builder::address(address.as_str()).unwrap().build().unwrap();
+
+        for uri in ["file:///music/Sigur%20R%C3%B3s/a+b.flac",
+            "file://localhost/music/Sigur%20R%C3%B3s/a+b.flac"] {
+            client.call_method(Some(BUS_NAME), PATH, Some(INTERFACE), "OpenUri", &(uri,))
+                .unwrap();
+
+            let command = commands.recv_timeout(Duration::from_secs(5)).unwrap();
+
+            assert!(matches!(command,
+                Command::Open(source) if source == "/music/Sigur Rós/a+b.flac"));
+        };
+
+        client.call_method(Some(BUS_NAME), PATH, Some(INTERFACE), "OpenUri",
+            &("http://radio.example/live?a=%20",)).unwrap();
+
+        assert!(matches!(commands.recv_timeout(Duration::from_secs(5)).unwrap(),
+            Command::Open(source) if source == "http://radio.example/live?a=%20"));
+
+        let _ = daemon.kill();
+    }
+
+    #[test]
+    fn reports_escaped_urls() {
+        let (mut daemon, address) = bus();
+        let (_server, _, current) = serve(&address);
+        let client = Builder::address(address.as_str()).unwrap().build().unwrap();
+
+        let now_playing = NowPlaying::new("/music/a #1.flac".to_string(), "A".to_string(),
+            String::new(), String::new(), "playing".to_string(), Duration::ZERO, None);
+
+        *current.lock().unwrap() = Current {
+            art: Some("/music/cover art.jpg".to_string()),
+            state: PlayerState::new(now_playing, "none".to_string(), false, 100),
+            updated: Instant::now()
+        };
+
+        let proxy = Proxy::new(&client, BUS_NAME, PATH, INTERFACE).unwrap();
+        let metadata = proxy.get_property::<HashMap<String, OwnedValue>>("Metadata").unwrap();
+
+        assert_eq!(text(&metadata["xesam:url"]), "file:///music/a%20%231.flac");
+        assert_eq!(text(&metadata["mpris:artUrl"]), "file:///music/cover%20art.jpg");
+
+        let _ = daemon.kill();
+    }
+    }
+
+    #[test]
+    fn seeks_within_the_track() {
+        let (mut daemon, address) = bus();
+        let (_server, commands, current) = serve(&address);
+        let client = Builder::address(address.as_str()).unwrap().build().unwrap();
+
+        paused(&current, "none", Duration::from_secs(10));
+
+        let seek = |offset: i64| {
+            client.call_method(Some(BUS_NAME), PATH, Some(INTERFACE), "Seek", &(offset,))
+                .unwrap();
+
+            commands.try_recv().ok()
+        };
+
+        assert!(matches!(seek(5_000_000),
+            Some(Command::Seek(position)) if position == Duration::from_secs(15)));
+        assert!(matches!(seek(-20_000_000),
+            Some(Command::Seek(position)) if position == Duration::ZERO));
+        assert!(matches!(seek(60_000_000), Some(Command::Next)));
+
+        let track_id = current.lock().unwrap().get_track_id();
+
+        let set_position = |track_id: &str, position: i64| {
+            client.call_method(Some(BUS_NAME), PATH, Some(INTERFACE), "SetPosition",
+                &(ObjectPath::try_from(track_id).unwrap(), position)).unwrap();
+
+            commands.try_recv().ok()
+        };
+
+        assert!(matches!(set_position(&track_id, 30_000_000),
+            Some(Command::Seek(position)) if position == Duration::from_secs(30)));
+
+        // Stale track ids and positions past the end are ignored
+        assert!(set_position(NO_TRACK, 30_000_000).is_none());
+        assert!(set_position(&track_id, -1).is_none());
+        assert!(set_position(&track_id, 61_000_000).is_none());
+
+        let _ = daemon.kill();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,296 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf}
+};
+
+use crate::utils::data::{self, TagField, Tags};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Field(TagField),
+    Literal(String)
+}
+
+impl Move {
+    //** Getters **//
+    pub fn get_conflict(&self) -> Option<String> {
+        self.conflict.clone()
+    }
+
+    pub fn get_from(&self) -> String {
+        self.from.clone()
+    }
+
+    pub fn get_to(&self) -> String {
+        self.to.clone()
+    }
+}
+
+impl Retag {
+    //** Getters **//
+    pub fn get_after(&self) -> Tags {
+        self.after.clone()
+    }
+
+    pub fn get_before(&self) -> Tags {
+        self.before.clone()
+    }
+
+    pub fn get_source(&self) -> String {
+        self.source.clone()
+    }
+}
+
+pub fn apply_renames(moves: &[Move]) -> (Vec<(String, String)>, Vec<String>) {
+    let mut errors: Vec<String> = Vec::new();
+    let mut moved: Vec<(String, String)> = Vec::new();
+
+    for entry in moves {
+        if entry.conflict.is_some() || entry.from == entry.to { continue; };
+
+        match move_file(&entry.from, &entry.to) {
+            Ok(()) => moved.push((entry.from.clone(), entry.to.clone())),
+            Err(error) => errors.push(format!("{}: {}", entry.from, error))
+        };
+    };
+
+    (moved, errors)
+}
+
+pub fn apply_retags(retags: &[Retag]) -> (Vec<String>, Vec<String>) {
+    let mut errors: Vec<String> = Vec::new();
+    let mut written: Vec<String> = Vec::new();
+
+    for retag in retags {
+        match data::write_tags(&retag.source, &retag.after) {
+            Ok(()) => written.push(retag.source.clone()),
+            Err(error) => errors.push(format!("{}: {}", retag.source, error))
+        };
+    };
+
+    (written, errors)
+}
+
+fn capture(tokens: &[Token], text: &str, found: &mut Vec<(TagField, String)>) -> bool {
+    match tokens.first() {
+        None => text.is_empty(),
+
+        Some(Token::Literal(literal)) => match text.strip_prefix(literal.as_str()) {
+            Some(rest) => capture(&tokens[1..], rest, found),
+            None => false
+        },
+
+        Some(Token::Field(field)) => {
+            // Shortest match first so literals after the field anchor as early as possible
+            for (end, _char) in text.char_indices().skip(1).chain([(text.len(), ' ')]) {
+                if end == 0 { continue; };
+
+                let value = &text[..end];
+
+                if value.contains('/') { break; };
+
+                found.push((*field, value.trim().to_string()));
+
+                if capture(&tokens[1..], &text[end..], found) { return true; };
+
+                found.pop();
+            };
+
+            false
+        }
+    }
+}
+
+fn field_value(field: TagField, tags: &Tags) -> String {
+    let value = tags.get(field);
+
+    if value.trim().is_empty() { return format!("Unknown {}", field.get_label()); }
+
+    match field {
+        TagField::Track | TagField::Disc => match value.trim().parse::<u32>() {
+            Ok(number) => format!("{:02}", number),
+            Err(_) => sanitize(&value)
+        },
+
+        _ => sanitize(&value)
+    }
+}
+
+fn move_file(from: &str, to: &str) -> Result<(), Box<dyn Error>> {
+    // The plan can be stale by the time it is applied, so never overwrite
+    if Path::new(to).exists() { return Err(format!("{} already exists.", to).into()); }
+
+    if let Some(parent) = Path::new(to).parent() { fs::create_dir_all(parent)?; };
+
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+
+        // Renaming fails across devices, so fall back to copying
+        Err(error) if error.kind() == ErrorKind::CrossesDevices => {
+            fs::copy(from, to)?;
+            fs::remove_file(from)?;
+
+            Ok(())
+        },
+
+        Err(error) => Err(error.into())
+    }
+}
+
+fn parse(pattern: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut literal = String::new();
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('%') {
+        let after = &rest[start + 1..];
+
+        let field = after.find('%').and_then(|end| {
+            let name = after[..end].to_lowercase().replace(' ', "");
+
+            TagField::ALL.iter()
+                .find(|field| field.get_label().to_lowercase().replace(' ', "") == name)
+                .map(|field| (*field, end))
+        });
+
+        match field {
+            Some((field, end)) => {
+                literal += &rest[..start];
+
+                if !literal.is_empty() { tokens.push(Token::Literal(literal.clone())); };
+
+                literal.clear();
+                tokens.push(Token::Field(field));
+
+                rest = &after[end + 1..];
+            },
+
+            None => {
+                literal += &rest[..start + 1];
+                rest = after;
+            }
+        };
+    };
+
+    literal += rest;
+
+    if !literal.is_empty() { tokens.push(Token::Literal(literal)); };
+
+    tokens
+}
+
+pub fn plan_renames(pattern: &str, root: &str, sources: &[String]) -> Vec<Move> {
+    let tokens = parse(pattern);
+    let mut moves: Vec<Move> = Vec::new();
+    let mut targets: HashMap<String, usize> = HashMap::new();
+
+    for source in sources {
+        let tags = data::read_tags(source).unwrap_or_default();
+        let mut relative = String::new();
+
+        for token in &tokens {
+            match token {
+                Token::Field(field) => relative += &field_value(*field, &tags),
+                Token::Literal(literal) => relative += literal
+            };
+        };
+
+        let mut target = PathBuf::from(root).join(relative.trim_matches('/'));
+
+        if let Some(ext) = Path::new(source).extension() {
+            let mut name = target.file_name().map(|name| name.to_os_string()).unwrap_or_default();
+
+            name.push(".");
+            name.push(ext);
+
+            target.set_file_name(name);
+        };
+
+        let to = target.display().to_string();
+        let mut conflict = None;
+
+        if to != *source && Path::new(&to).exists() {
+            conflict = Some("Target already exists.".to_string());
+        };
+
+        if let Some(index) = targets.get(&to) {
+            conflict = Some(format!("Same target as {}.", moves[*index].from));
+
+            moves[*index].conflict = Some(format!("Same target as {}.", source));
+        } else { targets.insert(to.clone(), moves.len()); };
+
+        moves.push(Move {
+            conflict,
+            from: source.clone(),
+            to
+        });
+    };
+
+    moves
+}
+
+pub fn plan_retags(pattern: &str, root: &str, sources: &[String]) -> Vec<Retag> {
+    let tokens = parse(pattern);
+    let depth = pattern.matches('/').count() + 1;
+    let mut retags: Vec<Retag> = Vec::new();
+
+    for source in sources {
+        let path = Path::new(source);
+        let relative = path.strip_prefix(root).unwrap_or(path).with_extension("");
+
+        let components = relative.iter().map(|part| part.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        if components.len() < depth { continue; };
+
+        let text = components[components.len() - depth..].join("/");
+        let mut found: Vec<(TagField, String)> = Vec::new();
+
+        if !capture(&tokens, &text, &mut found) { continue; };
+
+        let before = data::read_tags(source).unwrap_or_default();
+        let mut after = before.clone();
+
+        for (field, value) in found {
+            let value = match field {
+                TagField::Track | TagField::Disc => value.parse::<u32>()
+                    .map(|number| number.to_string()).unwrap_or(value),
+                _ => value
+            };
+
+            after.set(field, value);
+        };
+
+        if after != before {
+            retags.push(Retag {
+                after,
+                before,
+                source: source.clone()
+            });
+        };
+    };
+
+    retags
+}
+
+fn sanitize(value: &str) -> String {
+    value.chars().map(|c| if "/\\:*?\"<>|".contains(c) { '_' } else { c }).collect::<String>()
+        .trim().trim_end_matches('.').to_string()
+}
+
+#[derive(Clone, Debug)]
+pub struct Move {
+    conflict: Option<String>,
+    from: String,
+    to: String
+}
+
+#[derive(Clone, Debug)]
+pub struct Retag {
+    after: Tags,
+    before: Tags,
+    source: String
+}
\ No newline at end of file
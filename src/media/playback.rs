@@ -1,16 +1,62 @@
-use rodio::{Decoder, OutputStream, Sink};
+use rodio::{Decoder, OutputStream, Sink, Source};
 
 use std::{
     error::Error,
     fs::File,
+    sync::{Arc, Mutex, atomic::{AtomicU64, Ordering}},
+    thread,
     time::Duration
 };
 
-use crate::utils::data;
+use crate::{
+    media::{
+        hls::{self, HlsStream},
+        stream::{HttpStream, StreamMetadata, StreamStatus},
+        tap::{Outputs, Samples, Tap}
+    },
+    utils::{data, filesys}
+};
 
 impl Playback {
+    //** Getters **//
+    pub fn get_duration(&self) -> Option<Duration> {
+        self.duration.lock().ok().and_then(|duration| *duration)
+    }
+
+    pub fn get_metadata(&self) -> StreamMetadata {
+        self.metadata.lock().map(|metadata| metadata.clone()).unwrap_or_default()
+    }
+
+    pub fn get_outputs(&self) -> Arc<Mutex<Outputs>> {
+        self.outputs.clone()
+    }
+
+    pub fn get_position(&self) -> Duration {
+        self.sink.as_ref().map(|sink| sink.get_pos()).unwrap_or_default()
+    }
+
+    pub fn get_samples(&self) -> Arc<Mutex<Samples>> {
+        self.samples.clone()
+    }
+
+    pub fn get_status(&self) -> StreamStatus {
+        self.status.lock().map(|status| status.clone()).unwrap_or(StreamStatus::Idle)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        // An empty sink while a stream connects means nothing has been appended yet, and a failed
+        // stream stays put so its error remains on screen
+        match self.get_status() {
+            StreamStatus::Idle | StreamStatus::Streaming => {},
+            _ => return false
+        };
+
+        self.sink.as_ref().map(|sink| sink.empty()).unwrap_or(true)
+    }
+
     //** Misc. **//
     pub fn clear_playlist(&mut self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
         self.sink = None;
     }
 
@@ -18,32 +64,123 @@ impl Playback {
         let (sink, stream) = data::get_stream();
 
         Self {
+            // Output
+            outputs: Arc::new(Mutex::new(Outputs::new())),
+            samples: Arc::new(Mutex::new(Samples::new())),
+
+            // Playback
             stream: Some(stream),
-            sink: Some(sink)
+            sink: Some(Arc::new(sink)),
+
+            // Stream
+            duration: Arc::new(Mutex::new(None)),
+            generation: Arc::new(AtomicU64::new(0)),
+            metadata: Arc::new(Mutex::new(StreamMetadata::default())),
+            pending_seek: Arc::new(Mutex::new(None)),
+            status: Arc::new(Mutex::new(StreamStatus::Idle))
         }
     }
 
+    fn play_url(&mut self, url: String) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let current = self.generation.clone();
+        let duration = self.duration.clone();
+        let metadata = self.metadata.clone();
+        let outputs = self.outputs.clone();
+        let pending_seek = self.pending_seek.clone();
+        let samples = self.samples.clone();
+        let sink = self.sink.clone();
+        let status = self.status.clone();
+
+        if let Ok(mut duration) = duration.lock() { *duration = None; };
+        if let Ok(mut metadata) = metadata.lock() { metadata.clear(); };
+        if let Ok(mut status) = status.lock() { *status = StreamStatus::Connecting; };
+
+        // Connecting blocks on the network, so the UI only watches the status
+        thread::spawn(move || {
+            let decoded = open_remote(&url, &status, metadata);
+
+            // Stopping or switching tracks while connecting leaves this result unused
+            if current.load(Ordering::SeqCst) != generation { return; }
+
+            let (decoded, length) = match decoded {
+                Ok(decoded) => decoded,
+
+                Err(error) => {
+                    if let Ok(mut status) = status.lock() {
+                        *status = StreamStatus::Failed(error.to_string());
+                    };
+
+                    return;
+                }
+            };
+
+            if let Ok(mut duration) = duration.lock() {
+                *duration = length.or(decoded.total_duration());
+            };
+
+            if let Some(sink) = sink {
+                sink.append(Tap::new(decoded, samples, outputs));
+                sink.play();
+
+                if let Some(position) = pending_seek.lock().ok().and_then(|mut seek| seek.take()) {
+                    let _ = sink.try_seek(position);
+                };
+            };
+
+            if let Ok(mut status) = status.lock() {
+                if status.is_loading() { *status = StreamStatus::Streaming; };
+            };
+        });
+    }
+
     pub fn try_seek(&self, elapsed_time: Duration) {
+        // Streams still connecting apply the seek once the decoder is ready
+        if self.get_status().is_loading() {
+            if let Ok(mut pending_seek) = self.pending_seek.lock() {
+                *pending_seek = Some(elapsed_time);
+            };
+
+            return;
+        };
+
         if let Some(sink) = &self.sink { let _ = sink.try_seek(elapsed_time); };
     }
 
     //** Playback **//
     pub fn pause(&mut self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+
+        if let Ok(mut samples) = self.samples.lock() { samples.clear(); };
+        if let Ok(mut pending_seek) = self.pending_seek.lock() { *pending_seek = None; };
+
+        if let Ok(mut status) = self.status.lock() { *status = StreamStatus::Idle; };
+
         if let Some(sink) = &self.sink {
             sink.stop();
 
             if let Some(stream) = &self.stream {
-                self.sink = Some(Sink::connect_new(stream.mixer()));
+                self.sink = Some(Arc::new(Sink::connect_new(stream.mixer())));
             };
         };
     }
 
     pub fn play(&mut self, source: String) -> Result<(), Box<dyn Error>> {
+        if let Ok(mut samples) = self.samples.lock() { samples.clear(); };
+
+        if filesys::is_url(source.clone()) {
+            self.play_url(source);
+
+            return Ok(());
+        };
+
+        if let Ok(mut status) = self.status.lock() { *status = StreamStatus::Idle; };
+
         let file = File::open(source)?;
         let decoded = Decoder::try_from(file)?;
 
         if let Some(sink) = &self.sink {
-            sink.append(decoded);
+            sink.append(Tap::new(decoded, self.samples.clone(), self.outputs.clone()));
             sink.play();
         };
 
@@ -56,8 +193,53 @@ impl Playback {
     }
 }
 
+fn open_remote(url: &str, status: &Arc<Mutex<StreamStatus>>,
+    metadata: Arc<Mutex<StreamMetadata>>)
+    -> Result<(Box<dyn Source + Send>, Option<Duration>), Box<dyn Error>> {
+    if !hls::is_playlist(url) {
+        let mut stream = HttpStream::open(url, status.clone(), metadata)?;
+
+        // Playlist URLs without an .m3u8 or .mpd extension are only recognizable by content type
+        let is_hls = stream.get_content_type()
+            .is_some_and(|kind| kind.contains("mpegurl") || kind.contains("dash+xml"));
+
+        if !is_hls {
+            let hint = stream.get_hint();
+            let seekable = stream.is_seekable();
+            let length = stream.get_length();
+
+            let mut builder = Decoder::builder().with_data(stream).with_seekable(seekable);
+
+            if let Some(length) = length { builder = builder.with_byte_len(length); };
+            if let Some(hint) = hint { builder = builder.with_hint(&hint); };
+
+            return Ok((Box::new(builder.build()?), None));
+        };
+    };
+
+    let stream = HlsStream::open(url, status.clone())?;
+    let duration = stream.get_duration();
+    let hint = stream.get_hint();
+
+    let decoded = Decoder::builder().with_data(stream).with_seekable(false).with_hint(&hint)
+        .build()?;
+
+    Ok((Box::new(decoded), duration))
+}
+
 pub struct Playback {
+    // Output
+    outputs: Arc<Mutex<Outputs>>,
+    samples: Arc<Mutex<Samples>>,
+
     // Playback
     stream: Option<OutputStream>,
-    sink: Option<Sink>
+    sink: Option<Arc<Sink>>,
+
+    // Stream
+    duration: Arc<Mutex<Option<Duration>>>,
+    generation: Arc<AtomicU64>,
+    metadata: Arc<Mutex<StreamMetadata>>,
+    pending_seek: Arc<Mutex<Option<Duration>>>,
+    status: Arc<Mutex<StreamStatus>>
 }
\ No newline at end of file
@@ -0,0 +1,910 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::Path,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration
+};
+
+use crate::utils::{data, filesys};
+
+const SEPARATOR: &str = "⁘";
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+enum Update {
+    Downloaded(String, Result<String, String>),
+    Feed(String, Result<Podcast, String>)
+}
+
+impl Episode {
+    //** Getters **//
+    pub fn get_duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    pub fn get_path(&self) -> Option<String> {
+        self.path.clone().filter(|path| filesys::exists(path.clone()))
+    }
+
+    pub fn get_position(&self) -> Duration {
+        self.position
+    }
+
+    pub fn get_published(&self) -> String {
+        self.published.clone()
+    }
+
+    // Downloaded episodes play from disk; everything else streams from the enclosure
+    pub fn get_source(&self) -> String {
+        self.get_path().unwrap_or(self.url.clone())
+    }
+
+    pub fn get_title(&self) -> String {
+        self.title.clone()
+    }
+
+    pub fn get_url(&self) -> String {
+        self.url.clone()
+    }
+
+    pub fn is_played(&self) -> bool {
+        self.played
+    }
+
+    //** Misc. **//
+    fn is_source(&self, source: &str) -> bool {
+        self.url == source || self.path.as_deref() == Some(source)
+    }
+}
+
+impl Podcast {
+    //** Getters **//
+    pub fn get_episodes(&self) -> Vec<Episode> {
+        self.episodes.clone()
+    }
+
+    pub fn get_title(&self) -> String {
+        if self.title.is_empty() { self.url.clone() } else { self.title.clone() }
+    }
+
+    pub fn get_unplayed(&self) -> usize {
+        self.episodes.iter().filter(|episode| !episode.played).count()
+    }
+
+    pub fn get_url(&self) -> String {
+        self.url.clone()
+    }
+
+    //** Misc. **//
+    fn merge(&mut self, feed: Podcast) {
+        let mut episodes = feed.episodes;
+
+        // Refreshing keeps listening progress and downloads for episodes already known
+        for episode in &mut episodes {
+            if let Some(known) = self.episodes.iter().find(|known| known.guid == episode.guid) {
+                episode.path = known.path.clone();
+                episode.played = known.played;
+                episode.position = known.position;
+            };
+        };
+
+        self.episodes = episodes;
+        self.title = feed.title;
+    }
+}
+
+impl Podcasts {
+    //** Getters **//
+    pub fn get_episode(&self, source: &str) -> Option<Episode> {
+        self.podcasts.iter().flat_map(|podcast| &podcast.episodes)
+            .find(|episode| episode.is_source(source)).cloned()
+    }
+
+    pub fn get_message(&self) -> String {
+        self.message.clone()
+    }
+
+    pub fn get_podcast(&self, url: &str) -> Option<Podcast> {
+        self.podcasts.iter().find(|podcast| podcast.url == url).cloned()
+    }
+
+    pub fn get_podcasts(&self) -> Vec<Podcast> {
+        self.podcasts.clone()
+    }
+
+    pub fn get_title(&self, source: &str) -> Option<String> {
+        self.podcasts.iter()
+            .find(|podcast| podcast.episodes.iter().any(|episode| episode.is_source(source)))
+            .map(|podcast| podcast.get_title())
+    }
+
+    pub fn is_downloading(&self, url: &str) -> bool {
+        self.downloads.contains(url)
+    }
+
+    pub fn is_refreshing(&self, url: &str) -> bool {
+        self.refreshing.contains(url)
+    }
+
+    //** Misc. **//
+    pub fn download(&mut self, url: &str) {
+        let (podcast, episode) = match self.podcasts.iter().find_map(|podcast| {
+            podcast.episodes.iter().find(|episode| episode.url == url)
+                .map(|episode| (podcast.get_title(), episode.clone()))
+        }) {
+            Some(found) => found,
+            None => return
+        };
+
+        if !self.downloads.insert(url.to_string()) { return; }
+
+        let dir = self.dir.clone() + "/" + &sanitize(&podcast);
+        let sender = self.sender.clone();
+        let url = url.to_string();
+
+        thread::spawn(move || {
+            let result = download(&dir, &episode).map_err(|error| error.to_string());
+
+            let _ = sender.send(Update::Downloaded(url, result));
+        });
+    }
+
+    pub fn new() -> Self {
+        Self::open(filesys::get_podcasts_dir(), filesys::get_podcasts_path())
+    }
+
+    fn open(dir: String, path: String) -> Self {
+        let (sender, receiver) = mpsc::channel::<Update>();
+
+        Self {
+            // Files
+            dir,
+            path: path.clone(),
+
+            // Feeds
+            message: String::new(),
+            podcasts: load(&path),
+
+            // Workers
+            downloads: HashSet::new(),
+            receiver,
+            refreshing: HashSet::new(),
+            sender
+        }
+    }
+
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+
+        while let Ok(update) = self.receiver.try_recv() {
+            match update {
+                Update::Downloaded(url, result) => {
+                    self.downloads.remove(&url);
+
+                    match result {
+                        Ok(path) => self.update_episode(&url, |episode| episode.path = Some(path)),
+                        Err(error) => self.message = format!("Download failed: {}", error)
+                    };
+                },
+
+                Update::Feed(url, result) => {
+                    self.refreshing.remove(&url);
+
+                    match result {
+                        Ok(feed) => {
+                            let known = self.podcasts.iter_mut()
+                                .find(|podcast| podcast.url == url);
+
+                            match known {
+                                Some(podcast) => podcast.merge(feed),
+                                None => self.podcasts.push(feed)
+                            };
+                        },
+
+                        Err(error) => self.message = format!("Feed failed: {}", error)
+                    };
+                }
+            };
+
+            changed = true;
+        };
+
+        if changed { self.save(); };
+
+        changed
+    }
+
+    pub fn refresh(&mut self, url: &str) {
+        if !self.refreshing.insert(url.to_string()) { return; }
+
+        let sender = self.sender.clone();
+        let url = url.to_string();
+
+        self.message = String::new();
+
+        thread::spawn(move || {
+            let result = fetch_feed(&url).map_err(|error| error.to_string());
+
+            let _ = sender.send(Update::Feed(url, result));
+        });
+    }
+
+    pub fn refresh_all(&mut self) {
+        for url in self.podcasts.iter().map(|podcast| podcast.url.clone()).collect::<Vec<_>>() {
+            self.refresh(&url);
+        };
+    }
+
+    pub fn remove_download(&mut self, url: &str) {
+        if let Some(path) = self.get_episode(url).and_then(|episode| episode.get_path()) {
+            let _ = fs::remove_file(path);
+        };
+
+        self.update_episode(url, |episode| episode.path = None);
+        self.save();
+    }
+
+    fn save(&self) {
+        let _ = save(&self.path, &self.podcasts);
+    }
+
+    pub fn subscribe(&mut self, url: &str) {
+        let url = url.trim();
+
+        if !filesys::is_url(url.to_string()) {
+            self.message = "Feeds must be http(s) URLs.".to_string();
+
+            return;
+        };
+
+        self.refresh(url);
+    }
+
+    pub fn unsubscribe(&mut self, url: &str) {
+        self.podcasts.retain(|podcast| podcast.url != url);
+        self.save();
+    }
+
+    fn update_episode(&mut self, source: &str, update: impl FnOnce(&mut Episode)) {
+        let episode = self.podcasts.iter_mut().flat_map(|podcast| &mut podcast.episodes)
+            .find(|episode| episode.is_source(source));
+
+        if let Some(episode) = episode { update(episode); };
+    }
+
+    //** Setters **//
+    pub fn set_played(&mut self, source: &str, played: bool) {
+        self.update_episode(source, |episode| {
+            episode.played = played;
+            episode.position = Duration::ZERO;
+        });
+
+        self.save();
+    }
+
+    pub fn set_position(&mut self, source: &str, position: Duration) {
+        self.update_episode(source, |episode| episode.position = position);
+        self.save();
+    }
+}
+
+fn attribute(attributes: &str, key: &str) -> Option<String> {
+    for quote in ['"', '\''] {
+        let pattern = format!("{}={}", key, quote);
+
+        // A leading space keeps "url" from matching inside "xml:url"
+        let start = attributes.find(&format!(" {}", pattern))
+            .map(|index| index + 1 + pattern.len())
+            .or(attributes.strip_prefix(&pattern).map(|_rest| pattern.len()));
+
+        if let Some(start) = start {
+            let end = attributes[start..].find(quote)?;
+
+            return Some(decode(&attributes[start..start + end]));
+        };
+    };
+
+    None
+}
+
+fn blocks<'a>(text: &'a str, name: &str) -> Vec<&'a str> {
+    let mut blocks = Vec::new();
+    let mut rest = text;
+
+    while let Some((_attributes, inner, after)) = find_tag(rest, name) {
+        blocks.push(inner);
+        rest = after;
+    };
+
+    blocks
+}
+
+fn clean(text: &str) -> String {
+    text.replace(SEPARATOR, " ").replace(['\n', '\r', '\t'], " ").trim().to_string()
+}
+
+fn decode(text: &str) -> String {
+    let text = text.trim();
+
+    let text = match text.strip_prefix("<![CDATA[").and_then(|text| text.strip_suffix("]]>")) {
+        Some(inner) => return inner.trim().to_string(),
+        None => text
+    };
+
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find(';').filter(|end| *end <= 10) {
+            Some(end) => end,
+
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+
+                continue;
+            }
+        };
+
+        let entity = &rest[1..end];
+
+        let c = match entity {
+            "amp" => Some('&'),
+            "apos" => Some('\''),
+            "gt" => Some('>'),
+            "lt" => Some('<'),
+            "quot" => Some('"'),
+            _ => match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => entity.strip_prefix('#').and_then(|number| number.parse().ok())
+                    .and_then(char::from_u32)
+            }
+        };
+
+        match c {
+            Some(c) => decoded.push(c),
+            None => decoded.push_str(&rest[..=end])
+        };
+
+        rest = &rest[end + 1..];
+    };
+
+    decoded.push_str(rest);
+    decoded
+}
+
+fn download(dir: &str, episode: &Episode) -> Result<String, Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+
+    let path = episode.url.split(['?', '#']).next().unwrap_or_default();
+
+    let ext = Path::new(path).extension().and_then(|ext| ext.to_str())
+        .filter(|ext| ext.len() <= 4).unwrap_or("mp3").to_lowercase();
+
+    // Titles repeat across episodes ("Bonus", "Trailer"), so the guid keeps names apart
+    let target = format!("{}/{} [{}].{}", dir, sanitize(&episode.title),
+        &data::hash_bytes(episode.guid.as_bytes())[..8], ext);
+    let partial = target.clone() + ".part";
+
+    let agent = ureq::AgentBuilder::new().timeout_connect(TIMEOUT).build();
+    let mut reader = agent.get(&episode.url).call()?.into_reader();
+
+    // Writing to a side file means an interrupted download never looks complete
+    let mut file = File::create(&partial)?;
+    let copied = io::copy(&mut reader, &mut file).and_then(|_| file.flush());
+
+    drop(file);
+
+    // A broken transfer would otherwise leave its side file behind for good
+    if let Err(error) = copied {
+        let _ = fs::remove_file(&partial);
+
+        return Err(error.into());
+    };
+
+    fs::rename(&partial, &target)?;
+
+    Ok(target)
+}
+
+fn fetch_feed(url: &str) -> Result<Podcast, Box<dyn Error>> {
+    let agent = ureq::AgentBuilder::new().timeout_connect(TIMEOUT).timeout_read(TIMEOUT).build();
+    let mut bytes = Vec::new();
+
+    agent.get(url).call()?.into_reader().read_to_end(&mut bytes)?;
+
+    parse(url, &String::from_utf8_lossy(&bytes))
+}
+
+fn find_tag<'a>(text: &'a str, name: &str) -> Option<(&'a str, &'a str, &'a str)> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut offset = 0;
+
+    loop {
+        let start = offset + text[offset..].find(&open)?;
+        let after = &text[start + open.len()..];
+
+        // "<title" must not match "<titles" or similar longer names
+        if !after.starts_with([' ', '>', '/', '\t', '\n', '\r']) {
+            offset = start + open.len();
+
+            continue;
+        };
+
+        let end = after.find('>')?;
+        let attributes = &after[..end];
+
+        if attributes.ends_with('/') { return Some((attributes, "", &after[end + 1..])); }
+
+        let body = &after[end + 1..];
+        let finish = body.find(&close)?;
+
+        return Some((attributes, &body[..finish], &body[finish + close.len()..]));
+    };
+}
+
+fn load(path: &str) -> Vec<Podcast> {
+    let mut podcasts: Vec<Podcast> = Vec::new();
+    let text = fs::read_to_string(path).unwrap_or_default();
+
+    for line in text.lines() {
+        let fields = line.split(SEPARATOR).collect::<Vec<_>>();
+
+        match fields.as_slice() {
+            ["podcast", url, title] => podcasts.push(Podcast {
+                episodes: Vec::new(),
+                title: title.to_string(),
+                url: url.to_string()
+            }),
+
+            ["episode", guid, title, url, published, duration, played, position, path] => {
+                if let Some(podcast) = podcasts.last_mut() {
+                    podcast.episodes.push(Episode {
+                        duration: duration.parse().ok().map(Duration::from_secs),
+                        guid: guid.to_string(),
+                        path: if path.is_empty() { None } else { Some(path.to_string()) },
+                        played: *played == "true",
+                        position: Duration::from_millis(position.parse().unwrap_or(0)),
+                        published: published.to_string(),
+                        title: title.to_string(),
+                        url: url.to_string()
+                    });
+                };
+            },
+
+            _ => {}
+        };
+    };
+
+    podcasts
+}
+
+fn parse(url: &str, text: &str) -> Result<Podcast, Box<dyn Error>> {
+    let is_atom = !text.contains("<item") && text.contains("<entry");
+    let items = blocks(text, if is_atom { "entry" } else { "item" });
+
+    if items.is_empty() && !text.contains("<channel") && !text.contains("<feed") {
+        return Err("Not an RSS or Atom feed.".into());
+    };
+
+    // The feed title is the first one outside any item
+    let head = &text[..text.find(if is_atom { "<entry" } else { "<item" }).unwrap_or(text.len())];
+    let title = tag_text(head, "title").unwrap_or_default();
+
+    let mut episodes: Vec<Episode> = Vec::new();
+
+    for item in items {
+        let enclosure = match is_atom {
+            true => {
+                let mut rest = item;
+                let mut href = None;
+
+                while let Some((attributes, _inner, after)) = find_tag(rest, "link") {
+                    if attribute(attributes, "rel").as_deref() == Some("enclosure") {
+                        href = attribute(attributes, "href");
+
+                        break;
+                    };
+
+                    rest = after;
+                };
+
+                href
+            },
+
+            false => find_tag(item, "enclosure")
+                .and_then(|(attributes, _inner, _after)| attribute(attributes, "url"))
+        };
+
+        let enclosure = enclosure.or(find_tag(item, "media:content")
+            .and_then(|(attributes, _inner, _after)| attribute(attributes, "url")));
+
+        // Items without audio are blog posts or announcements
+        let url = match enclosure {
+            Some(url) => url,
+            None => continue
+        };
+
+        let published = tag_text(item, "pubDate").or(tag_text(item, "published"))
+            .or(tag_text(item, "updated")).unwrap_or_default();
+
+        episodes.push(Episode {
+            duration: tag_text(item, "itunes:duration").and_then(|text| parse_duration(&text)),
+            guid: tag_text(item, "guid").or(tag_text(item, "id")).unwrap_or(url.clone()),
+            path: None,
+            played: false,
+            position: Duration::ZERO,
+            published,
+            title: tag_text(item, "title").unwrap_or(url.clone()),
+            url
+        });
+    };
+
+    Ok(Podcast {
+        episodes,
+        title,
+        url: url.to_string()
+    })
+}
+
+fn parse_duration(text: &str) -> Option<Duration> {
+    // itunes:duration is either plain seconds or [hh:]mm:ss
+    let seconds = text.split(':').try_fold(0u64, |total, part| {
+        part.trim().split('.').next()?.parse::<u64>().ok().map(|value| total * 60 + value)
+    })?;
+
+    Some(Duration::from_secs(seconds))
+}
+
+fn sanitize(name: &str) -> String {
+    let clean = name.chars()
+        .map(|c| if c.is_alphanumeric() || " -_.()".contains(c) { c } else { '_' })
+        .take(80).collect::<String>();
+
+    let clean = clean.trim().trim_matches('.').to_string();
+
+    if clean.is_empty() { "untitled".to_string() } else { clean }
+}
+
+fn save(path: &str, podcasts: &[Podcast]) -> io::Result<()> {
+    let mut f = File::create(path)?;
+
+    for podcast in podcasts {
+        writeln!(&mut f, "podcast{0}{1}{0}{2}", SEPARATOR, clean(&podcast.url),
+            clean(&podcast.title))?;
+
+        for episode in &podcast.episodes {
+            let fields = [
+                "episode".to_string(),
+                clean(&episode.guid),
+                clean(&episode.title),
+                clean(&episode.url),
+                clean(&episode.published),
+                episode.duration.map(|duration| duration.as_secs().to_string()).unwrap_or_default(),
+                episode.played.to_string(),
+                episode.position.as_millis().to_string(),
+                episode.path.clone().unwrap_or_default()
+            ];
+
+            writeln!(&mut f, "{}", fields.join(SEPARATOR))?;
+        };
+    };
+
+    Ok(())
+}
+
+fn tag_text(text: &str, name: &str) -> Option<String> {
+    let (_attributes, inner, _after) = find_tag(text, name)?;
+    let text = clean(&decode(inner));
+
+    if text.is_empty() { None } else { Some(text) }
+}
+
+#[derive(Clone, Debug)]
+pub struct Episode {
+    // Feed
+    duration: Option<Duration>,
+    guid: String,
+    published: String,
+    title: String,
+    url: String,
+
+    // State
+    path: Option<String>,
+    played: bool,
+    position: Duration
+}
+
+#[derive(Clone, Debug)]
+pub struct Podcast {
+    episodes: Vec<Episode>,
+    title: String,
+    url: String
+}
+
+pub struct Podcasts {
+    // Files
+    dir: String,
+    path: String,
+
+    // Feeds
+    message: String,
+    podcasts: Vec<Podcast>,
+
+    // Workers
+    downloads: HashSet<String>,
+    receiver: Receiver<Update>,
+    refreshing: HashSet<String>,
+    sender: Sender<Update>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader},
+        net::TcpListener,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+        time::Instant
+    };
+
+    type Routes = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    fn feed(base: &str, items: &[(&str, &str)]) -> String {
+        let items = items.iter().map(|(guid, title)| format!("<item><title>{}</title>\
+            <guid>{}</guid><enclosure url=\"{}/audio/{}.mp3?token=1\"/></item>", title, guid, base,
+            guid)).collect::<String>();
+
+        format!("<rss><channel><title>Show</title>{}</channel></rss>", items)
+    }
+
+    fn ok(body: &str) -> Vec<u8> {
+        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(), body).into_bytes()
+    }
+
+    // Fresh per test so parallel runs and earlier failures never share files
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("comrad-podcast-{}-{}", name,
+            std::process::id()));
+
+        let _ = fs::remove_dir_all(&dir);
+
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    // Answers each path with the raw response stored for it, so tests can swap feeds
+    fn serve() -> (String, Routes) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let routes: Routes = Arc::new(Mutex::new(HashMap::new()));
+        let shared = routes.clone();
+
+        thread::spawn(move || {
+            for mut connection in listener.incoming().flatten() {
+                let mut reader = BufReader::new(connection.try_clone().unwrap());
+                let mut request = String::new();
+                let mut line = String::new();
+
+                let _ = reader.read_line(&mut request);
+
+                while reader.read_line(&mut line).is_ok_and(|read| read > 2) { line.clear(); };
+
+                let target = request.split_whitespace().nth(1).unwrap_or("/");
+                let path = target.split('?').next().unwrap_or(target);
+
+                let response = shared.lock().unwrap().get(path).cloned()
+                    .unwrap_or(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec());
+
+                let _ = connection.write_all(&response);
+            };
+        });
+
+        (base, routes)
+    }
+
+    fn settle(podcasts: &mut Podcasts) {
+        let started = Instant::now();
+
+        while !podcasts.refreshing.is_empty() || !podcasts.downloads.is_empty() {
+            assert!(started.elapsed() < Duration::from_secs(10), "workers never answered");
+
+            podcasts.poll();
+            thread::sleep(Duration::from_millis(10));
+        };
+    }
+
+    fn subscribed(name: &str, base: &str) -> (PathBuf, Podcasts) {
+        let dir = scratch(name);
+        let mut podcasts = Podcasts::open(dir.join("downloads").display().to_string(),
+            dir.join("podcasts.ini").display().to_string());
+
+        podcasts.subscribe(&format!("{}/feed.xml", base));
+        settle(&mut podcasts);
+
+        (dir, podcasts)
+    }
+
+    #[test]
+    fn decodes_entities_and_cdata() {
+        assert_eq!(decode("Tom &amp; Jerry"), "Tom & Jerry");
+        assert_eq!(decode("&lt;b&gt; &quot;hi&quot; &apos;x&apos;"), "<b> \"hi\" 'x'");
+        assert_eq!(decode("caf&#233; &#x2014; &#X41;"), "café — A");
+        assert_eq!(decode("  <![CDATA[ <p>raw &amp;</p> ]]>  "), "<p>raw &amp;</p>");
+        assert_eq!(decode("AT&T &bogus; & more"), "AT&T &bogus; & more");
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("3600"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("05:30"), Some(Duration::from_secs(330)));
+        assert_eq!(parse_duration("1:02:03"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("12.5"), Some(Duration::from_secs(12)));
+        assert_eq!(parse_duration("about an hour"), None);
+    }
+
+    #[test]
+    fn parses_rss_feeds() {
+        let text = r#"<?xml version="1.0"?>
+            <rss xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd"><channel>
+              <title>Show &amp; Tell</title>
+              <item>
+                <title><![CDATA[Episode 2]]></title>
+                <guid isPermaLink="false">ep-2</guid>
+                <pubDate>Tue, 02 Jan 2024 10:00:00 GMT</pubDate>
+                <itunes:duration>45:10</itunes:duration>
+                <enclosure url="https://cdn.example/ep2.mp3?a=1&amp;b=2" type="audio/mpeg"/>
+              </item>
+              <item><title>Blog post</title><link>https://example.com/post</link></item>
+              <item>
+                <title>Episode 1</title>
+                <enclosure length="1" url='https://cdn.example/ep1.m4a'/>
+              </item>
+            </channel></rss>"#;
+
+        let podcast = parse("https://example.com/feed.xml", text).unwrap();
+
+        assert_eq!(podcast.title, "Show & Tell");
+        assert_eq!(podcast.url, "https://example.com/feed.xml");
+        assert_eq!(podcast.episodes.len(), 2);
+
+        let (second, first) = (&podcast.episodes[0], &podcast.episodes[1]);
+
+        assert_eq!(second.title, "Episode 2");
+        assert_eq!(second.guid, "ep-2");
+        assert_eq!(second.url, "https://cdn.example/ep2.mp3?a=1&b=2");
+        assert_eq!(second.published, "Tue, 02 Jan 2024 10:00:00 GMT");
+        assert_eq!(second.duration, Some(Duration::from_secs(2710)));
+
+        // Without a guid the enclosure url identifies the episode
+        assert_eq!(first.guid, "https://cdn.example/ep1.m4a");
+        assert_eq!(first.duration, None);
+    }
+
+    #[test]
+    fn parses_atom_feeds() {
+        let text = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+              <title>Atom Show</title>
+              <entry>
+                <title>Pilot</title>
+                <id>urn:uuid:1</id>
+                <updated>2024-01-01T00:00:00Z</updated>
+                <link rel="alternate" href="https://example.com/pilot"/>
+                <link rel="enclosure" href="https://cdn.example/pilot.ogg"/>
+              </entry>
+            </feed>"#;
+
+        let podcast = parse("https://example.com/atom", text).unwrap();
+
+        assert_eq!(podcast.title, "Atom Show");
+        assert_eq!(podcast.episodes.len(), 1);
+        assert_eq!(podcast.episodes[0].url, "https://cdn.example/pilot.ogg");
+        assert_eq!(podcast.episodes[0].guid, "urn:uuid:1");
+        assert_eq!(podcast.episodes[0].published, "2024-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(parse("https://example.com", "<html><body>Hi</body></html>").is_err());
+    }
+
+    #[test]
+    fn refreshes_keep_listening_progress() {
+        let (base, routes) = serve();
+
+        routes.lock().unwrap().insert("/feed.xml".to_string(),
+            ok(&feed(&base, &[("ep-2", "Two"), ("ep-1", "One")])));
+
+        let (dir, mut podcasts) = subscribed("refresh", &base);
+        let url = |guid: &str| format!("{}/audio/{}.mp3?token=1", base, guid);
+
+        podcasts.set_position(&url("ep-1"), Duration::from_secs(30));
+        podcasts.set_played(&url("ep-2"), true);
+
+        routes.lock().unwrap().insert("/feed.xml".to_string(),
+            ok(&feed(&base, &[("ep-3", "Three"), ("ep-2", "Two"), ("ep-1", "One, renamed")])));
+
+        podcasts.refresh(&format!("{}/feed.xml", base));
+        settle(&mut podcasts);
+
+        // The saved list must carry the merged state too
+        let reloaded = Podcasts::open(dir.join("downloads").display().to_string(),
+            dir.join("podcasts.ini").display().to_string());
+
+        for podcasts in [&podcasts, &reloaded] {
+            let episodes = podcasts.get_podcasts()[0].get_episodes();
+            let titles = episodes.iter().map(Episode::get_title).collect::<Vec<_>>();
+
+            assert_eq!(titles, vec!["Three", "Two", "One, renamed"]);
+            assert!(!episodes[0].is_played());
+            assert!(episodes[1].is_played());
+            assert_eq!(episodes[2].get_position(), Duration::from_secs(30));
+        };
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn downloads_land_at_guid_named_paths() {
+        let (base, routes) = serve();
+
+        routes.lock().unwrap().insert("/feed.xml".to_string(),
+            ok(&feed(&base, &[("ep-1", "Bonus")])));
+        routes.lock().unwrap().insert("/audio/ep-1.mp3".to_string(), ok("ID3 audio"));
+
+        let (dir, mut podcasts) = subscribed("download", &base);
+        let url = format!("{}/audio/ep-1.mp3?token=1", base);
+
+        podcasts.download(&url);
+        settle(&mut podcasts);
+
+        let expected = format!("{}/Show/Bonus [{}].mp3", dir.join("downloads").display(),
+            &data::hash_bytes(b"ep-1")[..8]);
+
+        assert_eq!(podcasts.get_episode(&url).and_then(|episode| episode.get_path()),
+            Some(expected.clone()));
+        assert_eq!(fs::read_to_string(&expected).unwrap(), "ID3 audio");
+        assert!(!Path::new(&(expected + ".part")).exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn failed_downloads_leave_no_partial_file() {
+        let (base, routes) = serve();
+
+        routes.lock().unwrap().insert("/feed.xml".to_string(),
+            ok(&feed(&base, &[("ep-1", "Cut")])));
+
+        // The connection closes long before the promised length arrives
+        routes.lock().unwrap().insert("/audio/ep-1.mp3".to_string(),
+            b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\nConnection: close\r\n\r\nshort".to_vec());
+
+        let (dir, mut podcasts) = subscribed("partial", &base);
+        let url = format!("{}/audio/ep-1.mp3?token=1", base);
+
+        podcasts.download(&url);
+        settle(&mut podcasts);
+
+        let partial = format!("{}/Show/Cut [{}].mp3.part", dir.join("downloads").display(),
+            &data::hash_bytes(b"ep-1")[..8]);
+
+        assert!(podcasts.get_message().starts_with("Download failed"));
+        assert_eq!(podcasts.get_episode(&url).and_then(|episode| episode.get_path()), None);
+        assert!(!Path::new(&partial).exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,421 @@
+use egui::Context;
+use rand::{Rng, rng};
+use rand_distr::Alphanumeric;
+
+use std::{
+    error::Error,
+    io::{BufReader, Write},
+    net::{Shutdown, TcpStream},
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender}
+    },
+    thread::{self, JoinHandle},
+    time::Duration
+};
+
+use crate::{
+    media::server::NowPlaying,
+    utils::web::{self, Request}
+};
+
+const PING_INTERVAL: Duration = Duration::from_secs(5);
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+const TOKEN_LENGTH: usize = 32;
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub enum Command {
+    AddToPlaylist(String, String),
+    ListPlaylists(Sender<String>),
+    ListQueue(Sender<String>),
+    Next,
+    Open(String),
+    Pause,
+    Play,
+    PlayIndex(usize),
+    PlayPlaylist(String),
+    Previous,
+    Queue(String),
+    RemoveFromPlaylist(String, String),
+    Repeat,
+    Seek(Duration),
+    Shuffle,
+    Stop,
+    Unqueue(usize),
+    Volume(i32)
+}
+
+impl PlayerState {
+    //** Getters **//
+    pub fn get_now_playing(&self) -> NowPlaying {
+        self.now_playing.clone()
+    }
+
+    pub fn get_repeat(&self) -> String {
+        self.repeat.clone()
+    }
+
+    pub fn get_volume(&self) -> i32 {
+        self.volume
+    }
+
+    pub fn is_shuffled(&self) -> bool {
+        self.shuffle
+    }
+
+    //** Misc. **//
+    pub fn is_same(&self, other: &PlayerState) -> bool {
+        self.now_playing.is_same(&other.now_playing) && self.repeat == other.repeat
+            && self.shuffle == other.shuffle && self.volume == other.volume
+    }
+
+    pub fn new(now_playing: NowPlaying, repeat: String, shuffle: bool, volume: i32) -> Self {
+        Self {
+            now_playing,
+            repeat,
+            shuffle,
+            volume
+        }
+    }
+
+    fn to_json(&self) -> String {
+        format!("{{{},\"volume\":{},\"shuffle\":{},\"repeat\":{}}}", self.now_playing.to_fields(),
+            self.volume, self.shuffle, web::json_string(&self.repeat))
+    }
+}
+
+impl Remote {
+    //** Getters **//
+    pub fn get_message(&self) -> String {
+        self.message.clone()
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn get_token(&self) -> String {
+        self.token.clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.as_ref().is_some_and(|running| running.load(Ordering::SeqCst))
+    }
+
+    //** Misc. **//
+    pub fn new(context: Context, port: u16, token: String) -> Self {
+        let (sender, commands) = mpsc::channel();
+
+        Self {
+            // Settings
+            port,
+            token: if token.is_empty() { new_token() } else { token },
+
+            // Shared
+            clients: Arc::new(Mutex::new(Vec::new())),
+            context,
+            last: None,
+            state: Arc::new(Mutex::new("{}".to_string())),
+
+            // Thread
+            accept: None,
+            commands,
+            message: String::new(),
+            running: None,
+            sender
+        }
+    }
+
+    pub fn poll(&mut self) -> Vec<Command> {
+        self.commands.try_iter().collect()
+    }
+
+    pub fn start(&mut self) {
+        self.stop();
+
+        let running = Arc::new(AtomicBool::new(true));
+
+        let shared = Shared {
+            clients: self.clients.clone(),
+            context: self.context.clone(),
+            running: running.clone(),
+            sender: self.sender.clone(),
+            state: self.state.clone(),
+            token: self.token.clone()
+        };
+
+        let handler = move |stream| { let _ = respond(stream, shared.clone()); };
+
+        match web::listen("0.0.0.0", self.port, running.clone(), handler) {
+            Ok(accept) => {
+                self.accept = Some(accept);
+                self.message = String::new();
+                self.running = Some(running);
+            },
+
+            Err(error) => {
+                self.message = format!("Unable to listen on port {}: {}", self.port, error);
+            }
+        };
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(running) = self.running.take() { running.store(false, Ordering::SeqCst); };
+
+        if let Some(accept) = self.accept.take() { let _ = accept.join(); };
+
+        if let Ok(mut clients) = self.clients.lock() { clients.clear(); };
+    }
+
+    //** Setters **//
+    pub fn set_port(&mut self, port: u16) {
+        self.port = port;
+    }
+
+    pub fn set_state(&mut self, state: PlayerState) {
+        let json = state.to_json();
+        let changed = match &self.last {
+            Some(last) => !last.is_same(&state),
+            None => true
+        };
+
+        if let Ok(mut current) = self.state.lock() { *current = json.clone(); };
+
+        if changed {
+            let event = web::websocket_frame(&format!("{{\"event\":\"state\",\"state\":{}}}",
+                json));
+
+            if let Ok(mut clients) = self.clients.lock() {
+                clients.retain(|client| client.send(event.clone()).is_ok());
+            };
+        };
+
+        self.last = Some(state);
+    }
+
+    pub fn set_token(&mut self, token: String) {
+        self.token = token;
+    }
+}
+
+impl Drop for Remote {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn authorize(request: &Request, token: &str) -> bool {
+    // Browsers cannot set headers on a WebSocket, so the token may also come in the query
+    let given = request.get_header("authorization")
+        .and_then(|header| header.strip_prefix("Bearer ").map(|token| token.trim().to_string()))
+        .or(request.get_query("token"))
+        .unwrap_or_default();
+
+    !token.is_empty() && given.len() == token.len()
+        && given.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn command(request: &Request) -> Result<Command, (&'static str, &'static str)> {
+    let invalid = ("400 Bad Request", "Missing or invalid parameter");
+    let not_found = ("404 Not Found", "Unknown endpoint");
+
+    let text = |key: &str| request.get_query(key).filter(|value| !value.is_empty())
+        .ok_or(invalid);
+
+    let number = |key: &str| text(key)?.parse::<f64>().ok()
+        .filter(|value| value.is_finite()).ok_or(invalid);
+
+    if request.get_method() != "POST" { return Err(not_found); }
+
+    Ok(match request.get_path().as_str() {
+        "/api/next" => Command::Next,
+        "/api/pause" => Command::Pause,
+        "/api/play" => Command::Play,
+        "/api/previous" => Command::Previous,
+        "/api/repeat" => Command::Repeat,
+        "/api/seek" => Command::Seek(Duration::try_from_secs_f64(number("position")?.max(0.0))
+            .map_err(|_| invalid)?),
+        "/api/shuffle" => Command::Shuffle,
+        "/api/stop" => Command::Stop,
+        "/api/volume" => Command::Volume(number("level")?.clamp(0.0, 100.0) as i32),
+
+        "/api/playlists/add" => Command::AddToPlaylist(text("id")?, text("source")?),
+        "/api/playlists/play" => Command::PlayPlaylist(text("id")?),
+        "/api/playlists/remove" => Command::RemoveFromPlaylist(text("id")?, text("source")?),
+
+        "/api/queue/add" => Command::Queue(text("source")?),
+        "/api/queue/play" => Command::PlayIndex(number("index")? as usize),
+        "/api/queue/remove" => Command::Unqueue(number("index")? as usize),
+
+        _ => return Err(not_found)
+    })
+}
+
+fn events(mut stream: TcpStream, request: &Request, shared: &Shared)
+    -> Result<(), Box<dyn Error>> {
+    web::accept_websocket(&mut stream, request)?;
+
+    let (sender, receiver) = mpsc::channel();
+    let state = shared.state.lock().map(|state| state.clone()).unwrap_or_default();
+
+    // New clients get the current state right away instead of waiting for a change
+    stream.write_all(&web::websocket_frame(&format!("{{\"event\":\"state\",\"state\":{}}}",
+        state)))?;
+
+    shared.clients.lock().map_err(|_| "Clients unavailable")?.push(sender.clone());
+
+    let reader = stream.try_clone()?;
+
+    reader.set_read_timeout(None)?;
+
+    // Client frames are answered through the same queue so writes never interleave
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+
+        let close = loop {
+            match web::read_websocket_frame(&mut reader) {
+                Ok((0x8, payload)) => break payload,
+                Ok((0x9, payload)) => {
+                    let _ = sender.send(web::websocket_control(0xA, &payload));
+                },
+                Ok(_) => {},
+                Err(_) => break Vec::new()
+            };
+        };
+
+        let _ = sender.send(web::websocket_control(0x8, &close));
+    });
+
+    while shared.running.load(Ordering::SeqCst) {
+        match receiver.recv_timeout(PING_INTERVAL) {
+            Ok(frame) => {
+                stream.write_all(&frame)?;
+
+                if frame.first() == Some(&0x88) { break; }
+            },
+
+            Err(RecvTimeoutError::Timeout) => stream.write_all(&web::websocket_control(0x9, &[]))?,
+            Err(RecvTimeoutError::Disconnected) => break
+        };
+    };
+
+    // Hanging up also wakes the reader if the server stopped first
+    let _ = stream.shutdown(Shutdown::Both);
+
+    Ok(())
+}
+
+fn json_error(message: &str) -> String {
+    format!("{{\"error\":{}}}", web::json_string(message))
+}
+
+pub fn new_token() -> String {
+    (0..TOKEN_LENGTH).map(|_| rng().sample(Alphanumeric) as char).collect()
+}
+
+fn query(stream: &mut TcpStream, shared: &Shared, make: fn(Sender<String>) -> Command)
+    -> Result<(), Box<dyn Error>> {
+    let (sender, receiver) = mpsc::channel();
+
+    shared.sender.send(make(sender))?;
+    shared.context.request_repaint();
+
+    // Answers come from the UI thread on its next frame
+    match receiver.recv_timeout(REPLY_TIMEOUT) {
+        Ok(body) => web::write_response(stream, "200 OK", "application/json", body.as_bytes()),
+
+        Err(_) => web::write_response(stream, "503 Service Unavailable", "application/json",
+            json_error("Player did not answer").as_bytes())
+    }
+}
+
+fn respond(mut stream: TcpStream, shared: Shared) -> Result<(), Box<dyn Error>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+    let request = Request::read(&stream)?;
+
+    if request.get_method() == "OPTIONS" {
+        write!(stream, concat!("HTTP/1.1 204 No Content\r\nAccess-Control-Allow-Origin: *\r\n",
+            "Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n",
+            "Access-Control-Allow-Headers: Authorization\r\nConnection: close\r\n\r\n"))?;
+
+        return Ok(());
+    };
+
+    if !authorize(&request, &shared.token) {
+        return web::write_response(&mut stream, "401 Unauthorized", "application/json",
+            json_error("Missing or wrong token").as_bytes());
+    };
+
+    match (request.get_method().as_str(), request.get_path().as_str()) {
+        ("GET", "/api/events") if request.is_websocket() => {
+            return events(stream, &request, &shared);
+        },
+
+        ("GET", "/api/playlists") => return query(&mut stream, &shared, Command::ListPlaylists),
+        ("GET", "/api/queue") => return query(&mut stream, &shared, Command::ListQueue),
+
+        ("GET", "/api/state") => {
+            let state = shared.state.lock().map(|state| state.clone()).unwrap_or_default();
+
+            return web::write_response(&mut stream, "200 OK", "application/json",
+                state.as_bytes());
+        },
+
+        _ => {}
+    };
+
+    match command(&request) {
+        Ok(command) => {
+            shared.sender.send(command)?;
+            shared.context.request_repaint();
+
+            web::write_response(&mut stream, "202 Accepted", "application/json", b"{\"ok\":true}")
+        },
+
+        Err((status, message)) => web::write_response(&mut stream, status, "application/json",
+            json_error(message).as_bytes())
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PlayerState {
+    now_playing: NowPlaying,
+    repeat: String,
+    shuffle: bool,
+    volume: i32
+}
+
+pub struct Remote {
+    // Settings
+    port: u16,
+    token: String,
+
+    // Shared
+    clients: Arc<Mutex<Vec<Sender<Vec<u8>>>>>,
+    context: Context,
+    last: Option<PlayerState>,
+    state: Arc<Mutex<String>>,
+
+    // Thread
+    accept: Option<JoinHandle<()>>,
+    commands: Receiver<Command>,
+    message: String,
+    running: Option<Arc<AtomicBool>>,
+    sender: Sender<Command>
+}
+
+#[derive(Clone)]
+struct Shared {
+    clients: Arc<Mutex<Vec<Sender<Vec<u8>>>>>,
+    context: Context,
+    running: Arc<AtomicBool>,
+    sender: Sender<Command>,
+    state: Arc<Mutex<String>>,
+    token: String
+}
\ No newline at end of file
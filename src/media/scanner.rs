@@ -0,0 +1,155 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering}
+    },
+    thread
+};
+
+use crate::utils::filesys;
+
+impl Scanner {
+    //** Getters **//
+    pub fn get_dirs(&self) -> usize {
+        self.dirs.load(Ordering::Relaxed)
+    }
+
+    pub fn get_found(&self) -> usize {
+        self.found.load(Ordering::Relaxed)
+    }
+
+    pub fn get_roots(&self) -> Vec<String> {
+        self.roots.clone()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    //** Misc. **//
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn start(root: String) -> Self {
+        Self::start_all(vec![root])
+    }
+
+    pub fn start_all(roots: Vec<String>) -> Self {
+        let scanner = Self {
+            // Flags
+            cancelled: Arc::new(AtomicBool::new(false)),
+            finished: Arc::new(AtomicBool::new(false)),
+
+            // Progress
+            dirs: Arc::new(AtomicUsize::new(0)),
+            found: Arc::new(AtomicUsize::new(0)),
+
+            // Results
+            roots: roots.clone(),
+            sources: Arc::new(Mutex::new(None))
+        };
+
+        let walker = Walker {
+            cancelled: scanner.cancelled.clone(),
+            dirs: scanner.dirs.clone(),
+            found: scanner.found.clone(),
+            visited: HashSet::new()
+        };
+
+        let finished = scanner.finished.clone();
+        let sources = scanner.sources.clone();
+
+        thread::spawn(move || {
+            let mut walker = walker;
+            let mut list: Vec<String> = Vec::new();
+
+            for root in roots { walker.walk(PathBuf::from(root), &mut list); };
+
+            if !walker.is_cancelled() {
+                if let Ok(mut sources) = sources.lock() { *sources = Some(list); };
+            };
+
+            finished.store(true, Ordering::Release);
+        });
+
+        scanner
+    }
+
+    pub fn take_sources(&self) -> Option<Vec<String>> {
+        if !self.is_finished() { return None; }
+
+        self.sources.lock().ok()?.take()
+    }
+}
+
+impl Drop for Scanner {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl Walker {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn walk(&mut self, dir: PathBuf, list: &mut Vec<String>) {
+        if self.is_cancelled() { return; }
+
+        // Canonical paths guard against symlinks pointing back up the tree
+        match fs::canonicalize(&dir) {
+            Ok(real) => if !self.visited.insert(real) { return; },
+            Err(_) => return
+        };
+
+        let mut entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries.flatten().map(|entry| entry.path()).collect::<Vec<_>>(),
+            Err(_) => return
+        };
+
+        entries.sort();
+
+        self.dirs.fetch_add(1, Ordering::Relaxed);
+
+        for entry in entries {
+            if self.is_cancelled() { return; }
+
+            if entry.is_dir() { self.walk(entry, list); }
+            else if entry.is_file() {
+                let file_path = entry.display().to_string();
+
+                if filesys::is_media(file_path.clone()) {
+                    list.push(file_path);
+
+                    self.found.fetch_add(1, Ordering::Relaxed);
+                };
+            };
+        };
+    }
+}
+
+pub struct Scanner {
+    // Flags
+    cancelled: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+
+    // Progress
+    dirs: Arc<AtomicUsize>,
+    found: Arc<AtomicUsize>,
+
+    // Results
+    roots: Vec<String>,
+    sources: Arc<Mutex<Option<Vec<String>>>>
+}
+
+struct Walker {
+    cancelled: Arc<AtomicBool>,
+    dirs: Arc<AtomicUsize>,
+    found: Arc<AtomicUsize>,
+    visited: HashSet<PathBuf>
+}
\ No newline at end of file
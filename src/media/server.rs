@@ -0,0 +1,404 @@
+use rodio::{Decoder, Source};
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, Write},
+    net::{TcpStream, UdpSocket},
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering}
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant}
+};
+
+use crate::{
+    media::{
+        encoder::{self, Encoder, Format},
+        tap::Outputs
+    },
+    utils::web::{self, Request}
+};
+
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Shared tracks are sent slightly ahead of real time so players can buffer
+const TRACK_BLOCK: usize = 8192;
+const TRACK_LEAD: Duration = Duration::from_secs(2);
+
+impl NowPlaying {
+    //** Getters **//
+    pub fn get_album(&self) -> String {
+        self.album.clone()
+    }
+
+    pub fn get_artist(&self) -> String {
+        self.artist.clone()
+    }
+
+    pub fn get_duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    pub fn get_position(&self) -> Duration {
+        self.position
+    }
+
+    pub fn get_source(&self) -> String {
+        self.source.clone()
+    }
+
+    pub fn get_state(&self) -> String {
+        self.state.clone()
+    }
+
+    pub fn get_title(&self) -> String {
+        self.title.clone()
+    }
+
+    //** Misc. **//
+    pub fn new(source: String, title: String, artist: String, album: String, state: String,
+        position: Duration, duration: Option<Duration>) -> Self {
+        Self {
+            album,
+            artist,
+            duration,
+            position,
+            source,
+            state,
+            title
+        }
+    }
+
+    pub fn is_same(&self, other: &NowPlaying) -> bool {
+        // Position moves every frame, so it does not count as a change
+        self.album == other.album && self.artist == other.artist
+            && self.duration == other.duration && self.source == other.source
+            && self.state == other.state && self.title == other.title
+    }
+
+    pub fn to_fields(&self) -> String {
+        let duration = self.duration.map(|duration| format!("{:.3}", duration.as_secs_f64()))
+            .unwrap_or("null".to_string());
+
+        format!(concat!("\"title\":{},\"artist\":{},\"album\":{},\"source\":{},",
+            "\"state\":{},\"position\":{:.3},\"duration\":{}"), web::json_string(&self.title),
+            web::json_string(&self.artist), web::json_string(&self.album),
+            web::json_string(&self.source), web::json_string(&self.state),
+            self.position.as_secs_f64(), duration)
+    }
+}
+
+impl Server {
+    //** Getters **//
+    pub fn get_address(&self) -> Option<String> {
+        if !self.is_running() { return None; }
+
+        Some(format!("http://{}:{}", local_address(), self.port))
+    }
+
+    pub fn get_format(&self) -> Format {
+        self.format
+    }
+
+    pub fn get_listeners(&self) -> usize {
+        self.listeners.load(Ordering::SeqCst)
+    }
+
+    pub fn get_message(&self) -> String {
+        self.message.clone()
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn get_track(&self) -> Option<String> {
+        self.track.lock().ok().and_then(|track| track.clone())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.as_ref().is_some_and(|running| running.load(Ordering::SeqCst))
+    }
+
+    //** Misc. **//
+    pub fn new(outputs: Arc<Mutex<Outputs>>, port: u16, format: Format) -> Self {
+        Self {
+            // Settings
+            format,
+            port,
+
+            // Shared
+            listeners: Arc::new(AtomicUsize::new(0)),
+            now_playing: Arc::new(Mutex::new(NowPlaying::default())),
+            outputs,
+            track: Arc::new(Mutex::new(None)),
+
+            // Thread
+            accept: None,
+            message: String::new(),
+            running: None
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.stop();
+
+        let running = Arc::new(AtomicBool::new(true));
+
+        let shared = Shared {
+            format: self.format,
+            listeners: self.listeners.clone(),
+            now_playing: self.now_playing.clone(),
+            outputs: self.outputs.clone(),
+            running: running.clone(),
+            track: self.track.clone()
+        };
+
+        let handler = move |stream| { let _ = respond(stream, shared.clone()); };
+
+        match web::listen("0.0.0.0", self.port, running.clone(), handler) {
+            Ok(accept) => {
+                self.accept = Some(accept);
+                self.message = String::new();
+                self.running = Some(running);
+            },
+
+            Err(error) => {
+                self.message = format!("Unable to listen on port {}: {}", self.port, error);
+            }
+        };
+    }
+
+    pub fn stop(&mut self) {
+        // Listener threads notice the flag on their next chunk and hang up
+        if let Some(running) = self.running.take() { running.store(false, Ordering::SeqCst); };
+
+        if let Some(accept) = self.accept.take() { let _ = accept.join(); };
+    }
+
+    //** Setters **//
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+
+    pub fn set_now_playing(&mut self, now_playing: NowPlaying) {
+        if let Ok(mut current) = self.now_playing.lock() { *current = now_playing; };
+    }
+
+    pub fn set_port(&mut self, port: u16) {
+        self.port = port;
+    }
+
+    pub fn set_track(&mut self, track: Option<String>) {
+        if let Ok(mut current) = self.track.lock() { *current = track; };
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn local_address() -> String {
+    // Connecting a UDP socket sends nothing but reveals the interface used for the LAN;
+    // the target is a non-routable address so no real host is ever implied
+    UdpSocket::bind("0.0.0.0:0").and_then(|socket| {
+        socket.connect("10.255.255.255:1")?;
+        socket.local_addr()
+    }).map(|address| address.ip().to_string()).unwrap_or("localhost".to_string())
+}
+
+fn respond(mut stream: TcpStream, shared: Shared) -> Result<(), Box<dyn Error>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+    let request = Request::read(&stream)?;
+    let method = request.get_method();
+    let head = method == "HEAD";
+
+    if method != "GET" && !head {
+        return web::write_response(&mut stream, "405 Method Not Allowed", "text/plain", b"");
+    };
+
+    match request.get_path().as_str() {
+        "/now-playing" => {
+            let now_playing = shared.now_playing.lock().map(|now_playing| now_playing.clone())
+                .unwrap_or_default();
+
+            let body = format!("{{{},\"format\":{},\"listeners\":{}}}", now_playing.to_fields(),
+                web::json_string(shared.format.get_label()),
+                shared.listeners.load(Ordering::SeqCst));
+
+            web::write_response(&mut stream, "200 OK", "application/json",
+                if head { b"" } else { body.as_bytes() })
+        },
+
+        "/" | "/stream" => {
+            shared.listeners.fetch_add(1, Ordering::SeqCst);
+
+            let result = stream_output(&mut stream, &shared, head);
+
+            shared.listeners.fetch_sub(1, Ordering::SeqCst);
+
+            result
+        },
+
+        "/track" => {
+            let Some(track) = shared.track.lock().ok().and_then(|track| track.clone()) else {
+                return web::write_response(&mut stream, "404 Not Found", "text/plain", b"");
+            };
+
+            shared.listeners.fetch_add(1, Ordering::SeqCst);
+
+            let result = stream_track(&mut stream, &shared, &track, head);
+
+            shared.listeners.fetch_sub(1, Ordering::SeqCst);
+
+            result
+        },
+
+        _ => web::write_response(&mut stream, "404 Not Found", "text/plain", b"")
+    }
+}
+
+fn stream_output(stream: &mut TcpStream, shared: &Shared, head: bool)
+    -> Result<(), Box<dyn Error>> {
+    write_headers(stream, shared.format.get_content_type())?;
+
+    if head { return Ok(()); }
+
+    encoder::stream(stream, shared.format, &shared.outputs, &shared.running)
+}
+
+fn stream_track(stream: &mut TcpStream, shared: &Shared, track: &str, head: bool)
+    -> Result<(), Box<dyn Error>> {
+    let mut decoded = Decoder::try_from(File::open(track)?)?;
+    let mut encoder = Encoder::new(shared.format)?;
+
+    write_headers(stream, shared.format.get_content_type())?;
+
+    if head { return Ok(()); }
+
+    let channels: u16 = decoded.channels().into();
+    let sample_rate: u32 = decoded.sample_rate().into();
+    let rate = channels as f64 * sample_rate as f64;
+
+    let start = Instant::now();
+    let mut sent = Duration::ZERO;
+
+    stream.write_all(&encoder.header())?;
+
+    while shared.running.load(Ordering::SeqCst) {
+        let block = decoded.by_ref().take(TRACK_BLOCK).collect::<Vec<_>>();
+
+        if block.is_empty() { break; }
+
+        sent += Duration::from_secs_f64(block.len() as f64 / rate);
+
+        stream.write_all(&encoder.encode(&block, channels, sample_rate)?)?;
+
+        if let Some(wait) = sent.checked_sub(start.elapsed() + TRACK_LEAD) { thread::sleep(wait); };
+    };
+
+    stream.write_all(&encoder.finish()?)?;
+
+    Ok(())
+}
+
+fn write_headers(stream: &mut TcpStream, content_type: &str) -> io::Result<()> {
+    // No length is sent, the stream simply runs until either side hangs up
+    write!(stream, concat!("HTTP/1.1 200 OK\r\nContent-Type: {}\r\nCache-Control: no-cache\r\n",
+        "Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n"), content_type)
+}
+
+#[derive(Clone, Default)]
+pub struct NowPlaying {
+    album: String,
+    artist: String,
+    duration: Option<Duration>,
+    position: Duration,
+    source: String,
+    state: String,
+    title: String
+}
+
+pub struct Server {
+    // Settings
+    format: Format,
+    port: u16,
+
+    // Shared
+    listeners: Arc<AtomicUsize>,
+    now_playing: Arc<Mutex<NowPlaying>>,
+    outputs: Arc<Mutex<Outputs>>,
+    track: Arc<Mutex<Option<String>>>,
+
+    // Thread
+    accept: Option<JoinHandle<()>>,
+    message: String,
+    running: Option<Arc<AtomicBool>>
+}
+
+#[derive(Clone)]
+struct Shared {
+    format: Format,
+    listeners: Arc<AtomicUsize>,
+    now_playing: Arc<Mutex<NowPlaying>>,
+    outputs: Arc<Mutex<Outputs>>,
+    running: Arc<AtomicBool>,
+    track: Arc<Mutex<Option<String>>>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{io::Read, net::TcpListener};
+
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0").and_then(|listener| listener.local_addr()).unwrap().port()
+    }
+
+    #[test]
+    fn serves_now_playing_as_json() {
+        let port = free_port();
+        let mut server = Server::new(Arc::new(Mutex::new(Outputs::new())), port, Format::Mp3);
+
+        server.start();
+
+        assert!(server.is_running(), "{}", server.get_message());
+
+        server.set_now_playing(NowPlaying::new("/music/a\\b.flac".to_string(),
+            "Say \"Hi\"\n".to_string(), "Ünïcode".to_string(), String::new(),
+            "playing".to_string(), Duration::from_millis(1500),
+            Some(Duration::from_millis(90_250))));
+
+        let url = format!("http://127.0.0.1:{}/now-playing", port);
+        let response = ureq::get(&url).call().unwrap();
+
+        assert_eq!(response.content_type(), "application/json");
+
+        let mut body = String::new();
+
+        response.into_reader().read_to_string(&mut body).unwrap();
+
+        assert_eq!(body, concat!(r#"{"title":"Say \"Hi\"\n","artist":"Ünïcode","album":"","#,
+            r#""source":"/music/a\\b.flac","state":"playing","position":1.500,"#,
+            r#""duration":90.250,"format":"MP3","listeners":0}"#));
+
+        let head = ureq::head(&url).call().unwrap();
+
+        assert_eq!(head.header("Content-Length"), Some("0"));
+
+        server.stop();
+
+        // The port is free again as soon as stop returns
+        assert!(TcpListener::bind(("0.0.0.0", port)).is_ok());
+    }
+}
\ No newline at end of file
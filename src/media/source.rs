@@ -1,6 +1,60 @@
 use rand::{Rng, rng};
 use rand_distr::Alphanumeric;
 
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration
+};
+
+use crate::media::encoder::Format;
+
+const MOUNT_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl Mount {
+    //** Getters **//
+    pub fn get_format(&self) -> Format {
+        self.format
+    }
+
+    pub fn get_host(&self) -> String {
+        self.host.clone()
+    }
+
+    pub fn get_mount(&self) -> String {
+        self.mount.clone()
+    }
+
+    pub fn get_password(&self) -> String {
+        self.password.clone()
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn get_user(&self) -> String {
+        self.user.clone()
+    }
+
+    //** Misc. **//
+    pub fn new(host: String, port: u16, mount: String, user: String, password: String,
+        format: Format) -> Self {
+        let mount = mount.trim().trim_start_matches('/');
+
+        Self {
+            format,
+            host: host.trim().to_string(),
+            mount: "/".to_string() + mount,
+            password,
+            port,
+            user: user.trim().to_string()
+        }
+    }
+}
+
 impl Playlist {
     //** Getters **//
     pub fn get_id(&self) -> Option<String> {
@@ -43,6 +97,10 @@ impl Playlist {
         }
     }
 
+    pub fn remove_index(&mut self, index: usize) -> Option<String> {
+        if index < self.sources.len() { Some(self.sources.remove(index)) } else { None }
+    }
+
     pub fn remove_source(&mut self, source: String) {
         let mut index = 0;
 
@@ -52,6 +110,25 @@ impl Playlist {
         };
     }
 
+    pub fn replace_source(&mut self, source: &str, replacement: &str) -> bool {
+        let mut replaced = false;
+
+        for entry in &mut self.sources {
+            if entry == source {
+                *entry = replacement.to_string();
+
+                replaced = true;
+            };
+        };
+
+        replaced
+    }
+
+    pub fn sort_sources(&mut self) {
+        self.sources.sort_by(|a, b| Path::new(a).cmp(Path::new(b)));
+        self.sources.dedup();
+    }
+
     //** Setters **//
     pub fn set_id(&mut self, id: String) {
         self.id = id;
@@ -62,6 +139,94 @@ impl Playlist {
     }
 }
 
+impl Root {
+    //** Getters **//
+    pub fn get_name(&self) -> String {
+        match Path::new(&self.path).file_name() {
+            Some(name) => name.display().to_string(),
+            None => self.path.clone()
+        }
+    }
+
+    pub fn get_path(&self) -> String {
+        self.path.clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_mounted(&self) -> bool {
+        let (sender, receiver) = mpsc::channel::<bool>();
+        let path = self.path.clone();
+
+        // A dead network share can block a stat indefinitely, so give up on it instead
+        thread::spawn(move || { let _ = sender.send(mounted(&path)); });
+
+        receiver.recv_timeout(MOUNT_TIMEOUT).unwrap_or(false)
+    }
+
+    //** Misc. **//
+    pub fn new(path: String) -> Self {
+        Self {
+            enabled: true,
+            path
+        }
+    }
+
+    //** Setters **//
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+}
+
+impl Station {
+    //** Getters **//
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_url(&self) -> String {
+        self.url.clone()
+    }
+
+    //** Misc. **//
+    pub fn new(name: String, url: String) -> Self {
+        Self {
+            name,
+            url
+        }
+    }
+}
+
+fn mounted(path: &str) -> bool {
+    // A hung share blocks right here, which the caller's timeout turns into unmounted
+    Path::new(path).is_dir()
+}
+
+pub fn probe_mounts(roots: Vec<Root>) -> Receiver<HashSet<String>> {
+    let (sender, receiver) = mpsc::channel::<HashSet<String>>();
+
+    thread::spawn(move || {
+        let mounted = roots.iter().filter(|root| root.is_mounted()).map(|root| root.get_path())
+            .collect::<HashSet<_>>();
+
+        let _ = sender.send(mounted);
+    });
+
+    receiver
+}
+
+#[derive(Clone, Debug)]
+pub struct Mount {
+    format: Format,
+    host: String,
+    mount: String,
+    password: String,
+    port: u16,
+    user: String
+}
+
 #[derive(Clone, Debug)]
 pub struct Playlist {
     // Arrays
@@ -70,4 +235,16 @@ pub struct Playlist {
     // Strings
     id: String,
     name: String
+}
+
+#[derive(Clone, Debug)]
+pub struct Root {
+    enabled: bool,
+    path: String
+}
+
+#[derive(Clone, Debug)]
+pub struct Station {
+    name: String,
+    url: String
 }
\ No newline at end of file
@@ -0,0 +1,698 @@
+use std::{
+    error::Error,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::Duration
+};
+
+const AHEAD_LIMIT: usize = 8 * 1024 * 1024;
+const BEHIND_LIMIT: usize = 2 * 1024 * 1024;
+const CHUNK_SIZE: usize = 16 * 1024;
+const RETRIES: u64 = 5;
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamStatus { Idle, Buffering, Connecting, Failed(String), Reconnecting, Streaming }
+
+impl StreamStatus {
+    pub fn get_label(&self) -> Option<String> {
+        match self {
+            StreamStatus::Buffering => Some("Buffering...".to_string()),
+            StreamStatus::Connecting => Some("Connecting...".to_string()),
+            StreamStatus::Failed(error) => Some(format!("Stream error: {}", error)),
+            StreamStatus::Reconnecting => Some("Reconnecting...".to_string()),
+            StreamStatus::Idle | StreamStatus::Streaming => None
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(self, StreamStatus::Buffering | StreamStatus::Connecting
+            | StreamStatus::Reconnecting)
+    }
+}
+
+impl StreamMetadata {
+    //** Getters **//
+    pub fn get_name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub fn get_title(&self) -> Option<String> {
+        self.title.clone()
+    }
+
+    //** Misc. **//
+    pub fn clear(&mut self) {
+        self.name = None;
+        self.title = None;
+    }
+}
+
+impl<R: Read> IcyReader<R> {
+    pub fn new(inner: R, interval: usize, metadata: Arc<Mutex<StreamMetadata>>) -> Self {
+        Self {
+            inner,
+            interval,
+            metadata,
+            remaining: interval
+        }
+    }
+
+    fn read_metadata(&mut self) -> io::Result<()> {
+        let mut length = [0u8; 1];
+
+        self.inner.read_exact(&mut length)?;
+
+        // The length byte counts 16-byte blocks; zero means the title has not changed
+        let mut block = vec![0u8; length[0] as usize * 16];
+
+        self.inner.read_exact(&mut block)?;
+
+        let text = String::from_utf8_lossy(&block).trim_end_matches('\0').to_string();
+
+        if let Some(title) = parse_title(&text) {
+            if let Ok(mut metadata) = self.metadata.lock() {
+                metadata.title = if title.is_empty() { None } else { Some(title) };
+            };
+        };
+
+        Ok(())
+    }
+}
+
+impl HttpStream {
+    //** Getters **//
+    pub fn get_content_type(&self) -> Option<String> {
+        self.content_type.clone()
+    }
+
+    pub fn get_hint(&mut self) -> Option<String> {
+        let from_type = self.content_type.as_deref().and_then(hint_for_type);
+
+        let from_path = Path::new(self.url.split(['?', '#']).next().unwrap_or_default())
+            .extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_lowercase());
+
+        // Servers often send application/octet-stream, so fall back to the bytes themselves
+        from_type.map(str::to_string).or(from_path).or_else(|| {
+            let header = self.peek(12);
+
+            sniff(&header).map(str::to_string)
+        })
+    }
+
+    pub fn get_length(&self) -> Option<u64> {
+        self.length
+    }
+
+    pub fn is_seekable(&self) -> bool {
+        self.ranged && self.length.is_some()
+    }
+
+    //** Misc. **//
+    pub fn open(url: &str, status: Arc<Mutex<StreamStatus>>,
+        metadata: Arc<Mutex<StreamMetadata>>) -> Result<Self, Box<dyn Error>> {
+        set_status(&status, StreamStatus::Connecting);
+
+        let response = request(url, 0)?;
+
+        let ranged = response.status() == 206
+            || response.header("Accept-Ranges").is_some_and(|value| value == "bytes");
+
+        // A ranged reply reports the full size after the slash in Content-Range
+        let length = response.header("Content-Range")
+            .and_then(|range| range.rsplit('/').next()?.parse::<u64>().ok())
+            .or(response.header("Content-Length").and_then(|value| value.parse::<u64>().ok()));
+
+        let content_type = response.header("Content-Type")
+            .map(|value| value.split(';').next().unwrap_or_default().trim().to_lowercase());
+
+        // Only radio servers get reconnected; a plain download that closes has simply ended
+        let live = response.header("icy-metaint").is_some() || response.header("icy-name").is_some()
+            || response.header("Server").is_some_and(|server| server.contains("Icecast"));
+
+        let stream = Self {
+            content_type,
+            length,
+            live,
+            metadata,
+            position: 0,
+            ranged,
+            shared: Arc::new((Mutex::new(Buffer::default()), Condvar::new())),
+            status,
+            url: url.to_string()
+        };
+
+        let reader = wrap(response, &stream.metadata);
+
+        stream.spawn(reader, 0);
+
+        Ok(stream)
+    }
+
+    fn peek(&mut self, count: usize) -> Vec<u8> {
+        let mut header = vec![0u8; count];
+        let start = self.position;
+        let read = self.read(&mut header).unwrap_or(0);
+
+        self.position = start;
+
+        header.truncate(read);
+        header
+    }
+
+    fn restart(&mut self, offset: u64) -> io::Result<()> {
+        let generation = {
+            let (lock, condvar) = &*self.shared;
+            let mut buffer = lock.lock().map_err(|_| io::Error::other("Stream buffer poisoned."))?;
+
+            buffer.generation += 1;
+            buffer.data.clear();
+            buffer.start = offset;
+            buffer.ended = false;
+            buffer.error = None;
+
+            condvar.notify_all();
+
+            buffer.generation
+        };
+
+        let response = request(&self.url, offset)
+            .map_err(|error| io::Error::other(error.to_string()))?;
+
+        self.spawn(wrap(response, &self.metadata), generation);
+
+        Ok(())
+    }
+
+    fn spawn(&self, mut reader: Box<dyn Read + Send + Sync>, generation: u64) {
+        let shared = self.shared.clone();
+        let metadata = self.metadata.clone();
+        let status = self.status.clone();
+        let url = self.url.clone();
+
+        // Radio never ends, so a closed connection is only a dropout
+        let live = self.live;
+
+        thread::spawn(move || {
+            let (lock, condvar) = &*shared;
+            let mut chunk = vec![0u8; CHUNK_SIZE];
+
+            loop {
+                // Read-ahead is capped so long files do not end up entirely in memory
+                if let Ok(buffer) = lock.lock() {
+                    let buffer = condvar.wait_while(buffer, |buffer| {
+                        buffer.generation == generation && buffer.ahead() > AHEAD_LIMIT
+                    });
+
+                    match buffer {
+                        Ok(buffer) if buffer.generation == generation => {},
+                        _ => return
+                    };
+                };
+
+                let result = reader.read(&mut chunk);
+
+                let dropped = match &result {
+                    Ok(read) => *read == 0,
+                    Err(error) => error.kind() != io::ErrorKind::Interrupted
+                };
+
+                if live && dropped {
+                    if let Some(next) = reconnect(&shared, generation, &url, &metadata, &status) {
+                        reader = next;
+
+                        continue;
+                    };
+                };
+
+                let mut buffer = match lock.lock() {
+                    Ok(buffer) => buffer,
+                    Err(_) => return
+                };
+
+                if buffer.generation != generation { return; }
+
+                match result {
+                    Ok(0) => buffer.ended = true,
+                    Ok(read) => buffer.data.extend_from_slice(&chunk[..read]),
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(error) => buffer.error = Some(error.to_string())
+                };
+
+                let finished = buffer.ended || buffer.error.is_some();
+
+                condvar.notify_all();
+
+                if finished { return; }
+            };
+        });
+    }
+}
+
+impl Buffer {
+    fn ahead(&self) -> usize {
+        (self.start + self.data.len() as u64).saturating_sub(self.reader) as usize
+    }
+}
+
+impl Drop for HttpStream {
+    fn drop(&mut self) {
+        // Moving the generation on releases a reader thread parked on a full buffer
+        if let Ok(mut buffer) = self.shared.0.lock() { buffer.generation += 1; };
+
+        self.shared.1.notify_all();
+    }
+}
+
+impl Read for HttpStream {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let (lock, condvar) = &*self.shared;
+        let mut buffer = lock.lock().map_err(|_| io::Error::other("Stream buffer poisoned."))?;
+        let mut waited = false;
+
+        loop {
+            let end = buffer.start + buffer.data.len() as u64;
+
+            if self.position >= buffer.start && self.position < end {
+                let offset = (self.position - buffer.start) as usize;
+                let count = out.len().min(buffer.data.len() - offset);
+
+                out[..count].copy_from_slice(&buffer.data[offset..offset + count]);
+
+                self.position += count as u64;
+                buffer.reader = self.position;
+
+                // Keep a little history for decoders that step back while probing
+                let behind = (self.position - buffer.start) as usize;
+
+                if behind > BEHIND_LIMIT * 2 {
+                    let drop = behind - BEHIND_LIMIT;
+
+                    buffer.data.drain(..drop);
+                    buffer.start += drop as u64;
+                };
+
+                condvar.notify_all();
+
+                if waited { set_status(&self.status, StreamStatus::Streaming); };
+
+                return Ok(count);
+            };
+
+            if let Some(error) = &buffer.error {
+                set_status(&self.status, StreamStatus::Failed(error.clone()));
+
+                return Err(io::Error::other(error.clone()));
+            };
+
+            if buffer.ended { return Ok(0); }
+
+            set_status(&self.status, StreamStatus::Buffering);
+
+            waited = true;
+
+            let (next, timeout) = condvar.wait_timeout(buffer, TIMEOUT)
+                .map_err(|_| io::Error::other("Stream buffer poisoned."))?;
+
+            buffer = next;
+
+            if timeout.timed_out() {
+                set_status(&self.status, StreamStatus::Failed("Timed out.".to_string()));
+
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "Stream timed out."));
+            };
+        };
+    }
+}
+
+impl<R: Read> Read for IcyReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        // Every interval bytes of audio are followed by a metadata block that must be cut out
+        if self.remaining == 0 {
+            self.read_metadata()?;
+            self.remaining = self.interval;
+        };
+
+        let limit = out.len().min(self.remaining);
+        let read = self.inner.read(&mut out[..limit])?;
+
+        self.remaining -= read;
+
+        Ok(read)
+    }
+}
+
+impl Seek for HttpStream {
+    fn seek(&mut self, from: SeekFrom) -> io::Result<u64> {
+        let target = match from {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+
+            SeekFrom::End(offset) => match self.length {
+                Some(length) => length as i64 + offset,
+                None => return Err(io::Error::new(io::ErrorKind::Unsupported, "Unknown length."))
+            }
+        };
+
+        if target < 0 { return Err(io::Error::new(io::ErrorKind::InvalidInput, "Before start.")); }
+
+        let target = target as u64;
+
+        let buffered = match self.shared.0.lock() {
+            Ok(buffer) => target >= buffer.start
+                && target <= buffer.start + buffer.data.len() as u64 + CHUNK_SIZE as u64,
+            Err(_) => false
+        };
+
+        // Nearby targets are served from the buffer; anything else needs a Range request
+        if !buffered {
+            if !self.ranged {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "Server refuses ranges."));
+            };
+
+            self.restart(target)?;
+        };
+
+        self.position = target;
+
+        if let Ok(mut buffer) = self.shared.0.lock() { buffer.reader = target; };
+
+        self.shared.1.notify_all();
+
+        Ok(target)
+    }
+}
+
+fn hint_for_type(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "audio/mpeg" | "audio/mp3" => Some("mp3"),
+        "audio/aac" | "audio/aacp" => Some("aac"),
+        "audio/flac" | "audio/x-flac" => Some("flac"),
+        "audio/mp4" | "audio/x-m4a" | "video/mp4" => Some("mp4"),
+        "audio/ogg" | "audio/vorbis" | "application/ogg" => Some("ogg"),
+        "audio/wav" | "audio/x-wav" | "audio/wave" => Some("wav"),
+        _ => None
+    }
+}
+
+fn parse_title(text: &str) -> Option<String> {
+    let start = text.find("StreamTitle='")? + "StreamTitle='".len();
+    let rest = &text[start..];
+
+    // Titles may contain apostrophes, so the field ends at the quote before the next semicolon
+    let end = rest.find("';").or(rest.rfind('\''))?;
+
+    Some(rest[..end].trim().to_string())
+}
+
+fn reconnect(shared: &Arc<(Mutex<Buffer>, Condvar)>, generation: u64, url: &str,
+    metadata: &Arc<Mutex<StreamMetadata>>, status: &Arc<Mutex<StreamStatus>>)
+    -> Option<Box<dyn Read + Send + Sync>> {
+    let (lock, condvar) = &**shared;
+
+    for attempt in 1..=RETRIES {
+        if lock.lock().map(|buffer| buffer.generation != generation).unwrap_or(true) {
+            return None;
+        };
+
+        set_status(status, StreamStatus::Reconnecting);
+
+        // Waking readers keeps their timeout from expiring while the retries run
+        condvar.notify_all();
+
+        thread::sleep(Duration::from_secs(attempt));
+
+        if let Ok(response) = request(url, 0) {
+            set_status(status, StreamStatus::Streaming);
+
+            return Some(wrap(response, metadata));
+        };
+    };
+
+    None
+}
+
+fn request(url: &str, offset: u64) -> Result<ureq::Response, Box<dyn Error>> {
+    let agent = ureq::AgentBuilder::new().timeout_connect(TIMEOUT).timeout_read(TIMEOUT).build();
+
+    // Shoutcast and Icecast only interleave titles when the client asks for them
+    Ok(agent.get(url).set("Range", &format!("bytes={}-", offset)).set("Icy-MetaData", "1")
+        .call()?)
+}
+
+fn set_status(status: &Arc<Mutex<StreamStatus>>, next: StreamStatus) {
+    if let Ok(mut status) = status.lock() {
+        if *status != next { *status = next; };
+    };
+}
+
+fn sniff(header: &[u8]) -> Option<&'static str> {
+    match header {
+        [b'I', b'D', b'3', ..] => Some("mp3"),
+        [0xFF, sync, ..] if sync & 0xE0 == 0xE0 => Some("mp3"),
+        [b'f', b'L', b'a', b'C', ..] => Some("flac"),
+        [b'O', b'g', b'g', b'S', ..] => Some("ogg"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some("wav"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("mp4"),
+        _ => None
+    }
+}
+
+fn wrap(response: ureq::Response, metadata: &Arc<Mutex<StreamMetadata>>)
+    -> Box<dyn Read + Send + Sync> {
+    let interval = response.header("icy-metaint").and_then(|value| value.trim().parse().ok());
+
+    if let Some(name) = response.header("icy-name").filter(|name| !name.trim().is_empty()) {
+        if let Ok(mut metadata) = metadata.lock() {
+            metadata.name = Some(name.trim().to_string());
+        };
+    };
+
+    match interval {
+        Some(interval) if interval > 0 => {
+            Box::new(IcyReader::new(response.into_reader(), interval, metadata.clone()))
+        },
+
+        _ => response.into_reader()
+    }
+}
+
+#[derive(Default)]
+struct Buffer {
+    data: Vec<u8>,
+    ended: bool,
+    error: Option<String>,
+    generation: u64,
+    reader: u64,
+    start: u64
+}
+
+pub struct HttpStream {
+    content_type: Option<String>,
+    length: Option<u64>,
+    live: bool,
+    metadata: Arc<Mutex<StreamMetadata>>,
+    position: u64,
+    ranged: bool,
+    shared: Arc<(Mutex<Buffer>, Condvar)>,
+    status: Arc<Mutex<StreamStatus>>,
+    url: String
+}
+
+pub struct IcyReader<R: Read> {
+    inner: R,
+    interval: usize,
+    metadata: Arc<Mutex<StreamMetadata>>,
+    remaining: usize
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StreamMetadata {
+    name: Option<String>,
+    title: Option<String>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener
+    };
+
+    // Serves body over HTTP, honouring Range when ranged, and records each requested offset
+    fn serve(body: Vec<u8>, ranged: bool, extra: &'static str) -> (String, Arc<Mutex<Vec<u64>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/track", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let body = Arc::new(body);
+
+        thread::spawn(move || {
+            for connection in listener.incoming().flatten() {
+                let body = body.clone();
+                let recorded = recorded.clone();
+
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(connection.try_clone().unwrap());
+                    let mut offset = 0;
+                    let mut line = String::new();
+
+                    while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+                        let header = line.trim().to_lowercase();
+
+                        if let Some(range) = header.strip_prefix("range: bytes=") {
+                            offset = range.trim_end_matches('-').parse::<u64>().unwrap_or(0);
+                        };
+
+                        line.clear();
+                    };
+
+                    recorded.lock().unwrap().push(offset);
+
+                    let mut connection = connection;
+                    let length = body.len() as u64;
+
+                    let (head, start) = match ranged {
+                        true => (format!("HTTP/1.1 206 Partial Content\r\nAccept-Ranges: bytes\r\n\
+                            Content-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\n{}\
+                            Connection: close\r\n\r\n", offset, length - 1, length,
+                            length - offset, extra), offset),
+
+                        // Without a length the body runs until the connection closes
+                        false => (format!("HTTP/1.1 200 OK\r\n{}Connection: close\r\n\r\n",
+                            extra), 0)
+                    };
+
+                    let _ = connection.write_all(head.as_bytes());
+                    let _ = connection.write_all(&body[start as usize..]);
+                });
+            };
+        });
+
+        (url, requests)
+    }
+
+    fn open(url: &str) -> HttpStream {
+        let status = Arc::new(Mutex::new(StreamStatus::Idle));
+        let metadata = Arc::new(Mutex::new(StreamMetadata::default()));
+
+        HttpStream::open(url, status, metadata).unwrap()
+    }
+
+    fn pattern(length: usize) -> Vec<u8> {
+        (0..length).map(|index| (index % 251) as u8).collect()
+    }
+
+    #[test]
+    fn hints_follow_content_type() {
+        assert_eq!(hint_for_type("audio/mpeg"), Some("mp3"));
+        assert_eq!(hint_for_type("audio/x-flac"), Some("flac"));
+        assert_eq!(hint_for_type("application/ogg"), Some("ogg"));
+        assert_eq!(hint_for_type("video/mp4"), Some("mp4"));
+        assert_eq!(hint_for_type("application/octet-stream"), None);
+    }
+
+    #[test]
+    fn sniffs_magic_bytes() {
+        assert_eq!(sniff(b"ID3\x04\x00"), Some("mp3"));
+        assert_eq!(sniff(&[0xFF, 0xFB, 0x90, 0x00]), Some("mp3"));
+        assert_eq!(sniff(b"fLaC\x00\x00"), Some("flac"));
+        assert_eq!(sniff(b"OggS\x00\x02"), Some("ogg"));
+        assert_eq!(sniff(b"RIFF\x24\x00\x00\x00WAVEfmt "), Some("wav"));
+        assert_eq!(sniff(b"\x00\x00\x00\x20ftypM4A "), Some("mp4"));
+        assert_eq!(sniff(b"<html>"), None);
+        assert_eq!(sniff(&[]), None);
+    }
+
+    #[test]
+    fn reconnects_radio_on_a_closed_connection() {
+        let (url, requests) = serve(pattern(1000), false, "icy-name: Test Radio\r\n");
+        let mut stream = open(&url);
+        let mut chunk = vec![0u8; 1000];
+
+        stream.read_exact(&mut chunk).unwrap();
+
+        // The first retry waits a second before connecting again
+        for _ in 0..50 {
+            if requests.lock().unwrap().len() > 1 { break; }
+
+            thread::sleep(Duration::from_millis(100));
+        };
+
+        assert!(requests.lock().unwrap().len() > 1);
+    }
+
+    #[test]
+    fn ends_plain_downloads_on_a_closed_connection() {
+        let body = pattern(1000);
+        let (url, requests) = serve(body.clone(), false, "");
+        let mut stream = open(&url);
+        let mut read: Vec<u8> = Vec::new();
+
+        stream.read_to_end(&mut read).unwrap();
+
+        assert_eq!(read, body);
+        assert_eq!(*requests.lock().unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn reads_the_whole_body() {
+        let body = pattern(100_000);
+        let (url, _requests) = serve(body.clone(), true, "");
+        let mut stream = open(&url);
+        let mut read: Vec<u8> = Vec::new();
+
+        stream.read_to_end(&mut read).unwrap();
+
+        assert_eq!(read, body);
+        assert_eq!(stream.get_length(), Some(body.len() as u64));
+        assert!(stream.is_seekable());
+    }
+
+    #[test]
+    fn seeks_back_within_the_buffer() {
+        let body = pattern(100_000);
+        let (url, requests) = serve(body.clone(), true, "");
+        let mut stream = open(&url);
+        let mut chunk = vec![0u8; 4096];
+
+        stream.read_exact(&mut chunk).unwrap();
+        stream.seek(SeekFrom::Start(10)).unwrap();
+        stream.read_exact(&mut chunk[..100]).unwrap();
+
+        assert_eq!(&chunk[..100], &body[10..110]);
+        assert_eq!(*requests.lock().unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn seeks_past_the_buffer_with_a_range_request() {
+        // Larger than the read-ahead limit, so the tail can't be buffered yet
+        let body = pattern(AHEAD_LIMIT + 1024 * 1024);
+        let target = body.len() as u64 - 1000;
+        let (url, requests) = serve(body.clone(), true, "");
+        let mut stream = open(&url);
+        let mut read: Vec<u8> = Vec::new();
+
+        assert_eq!(stream.seek(SeekFrom::End(-1000)).unwrap(), target);
+
+        stream.read_to_end(&mut read).unwrap();
+
+        assert_eq!(read, &body[target as usize..]);
+        assert!(requests.lock().unwrap().contains(&target));
+    }
+
+    #[test]
+    fn refuses_far_seeks_without_ranges() {
+        let body = pattern(AHEAD_LIMIT + 1024 * 1024);
+        let target = body.len() as u64 - 1000;
+        let (url, _requests) = serve(body, false, "");
+        let mut stream = open(&url);
+
+        assert!(!stream.is_seekable());
+        assert!(stream.seek(SeekFrom::Start(target)).is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,383 @@
+use ffmpeg_next::{
+    self as ffmpeg,
+    Packet,
+    Subtitle as Decoded,
+    codec::subtitle::Rect,
+    format,
+    media::Type
+};
+
+use std::{
+    fs,
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration
+};
+
+const SIDECAR_EXTENSIONS: [&str; 4] = ["ass", "srt", "ssa", "vtt"];
+
+impl Subtitles {
+    //** Getters **//
+    pub fn get_names(&self) -> Vec<String> {
+        self.tracks.iter().map(|track| track.name.clone()).collect()
+    }
+
+    pub fn get_selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn get_text(&self, time: Duration, offset: i64) -> Option<String> {
+        let track = self.tracks.get(self.selected?)?;
+        let time = time.as_millis() as i64 - offset;
+
+        let lines = track.cues.iter()
+            .filter(|cue| cue.start.as_millis() as i64 <= time && time < cue.end.as_millis() as i64)
+            .map(|cue| cue.text.clone()).collect::<Vec<_>>();
+
+        if lines.is_empty() { None } else { Some(lines.join("\n")) }
+    }
+
+    //** Misc. **//
+    pub fn load(source: &str) -> Self {
+        let (sender, receiver) = mpsc::channel::<Vec<Track>>();
+        let path = source.to_string();
+
+        // Embedded streams need the whole container demuxed, which is too slow for the UI
+        thread::spawn(move || { let _ = sender.send(read_embedded(&path)); });
+
+        let tracks = read_sidecars(source);
+
+        Self {
+            embedded: Some(receiver),
+            selected: if tracks.is_empty() { None } else { Some(0) },
+            tracks
+        }
+    }
+
+    pub fn poll(&mut self) {
+        let tracks = match &self.embedded {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(tracks) => tracks,
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => Vec::new()
+            },
+
+            None => return
+        };
+
+        self.embedded = None;
+
+        if self.tracks.is_empty() && !tracks.is_empty() { self.selected = Some(0); };
+
+        self.tracks.extend(tracks);
+    }
+
+    //** Setters **//
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        self.selected = selected.filter(|index| *index < self.tracks.len());
+    }
+}
+
+fn clean_ass(text: &str) -> String {
+    let mut clean = String::new();
+    let mut depth = 0;
+
+    // Override blocks like {\i1} or {\pos(10,20)} carry styling this renderer ignores
+    for c in text.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = (depth - 1).max(0),
+            _ if depth == 0 => clean.push(c),
+            _ => {}
+        };
+    };
+
+    clean.replace("\\N", "\n").replace("\\n", "\n").replace("\\h", " ").trim().to_string()
+}
+
+fn clean_markup(text: &str) -> String {
+    let mut clean = String::new();
+    let mut in_tag = false;
+
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => clean.push(c),
+            _ => {}
+        };
+    };
+
+    clean.replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">")
+        .replace("&nbsp;", " ").trim().to_string()
+}
+
+fn parse_ass(text: &str) -> Vec<Cue> {
+    let mut cues: Vec<Cue> = Vec::new();
+    let mut columns = (1, 2, 9);
+    let mut in_events = false;
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_events = line.eq_ignore_ascii_case("[events]");
+
+            continue;
+        };
+
+        if !in_events { continue; }
+
+        if let Some(format) = line.strip_prefix("Format:") {
+            let names = format.split(',').map(|name| name.trim().to_lowercase())
+                .collect::<Vec<_>>();
+
+            let find = |key: &str, default: usize| names.iter().position(|name| name == key)
+                .unwrap_or(default);
+
+            columns = (find("start", 1), find("end", 2), find("text", names.len() - 1));
+        } else if let Some(dialogue) = line.strip_prefix("Dialogue:") {
+            // Text is always last and may itself contain commas
+            let fields = dialogue.splitn(columns.2 + 1, ',').collect::<Vec<_>>();
+
+            let start = fields.get(columns.0).and_then(|time| parse_time(time));
+            let end = fields.get(columns.1).and_then(|time| parse_time(time));
+
+            if let (Some(start), Some(end), Some(text)) = (start, end, fields.get(columns.2)) {
+                cues.push(Cue { end, start, text: clean_ass(text) });
+            };
+        };
+    };
+
+    cues.sort_by_key(|cue| cue.start);
+
+    cues
+}
+
+fn parse_srt(text: &str) -> Vec<Cue> {
+    let mut cues: Vec<Cue> = Vec::new();
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+
+    // SRT and WebVTT share the same blank-line separated block layout
+    for block in text.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+
+        let timing = match lines.next() {
+            Some(timing) => timing,
+            None => continue
+        };
+
+        let (start, rest) = match timing.split_once("-->") {
+            Some(parts) => parts,
+            None => continue
+        };
+
+        let end = rest.split_whitespace().next().unwrap_or_default();
+
+        if let (Some(start), Some(end)) = (parse_time(start), parse_time(end)) {
+            let text = lines.map(clean_markup).collect::<Vec<_>>().join("\n");
+
+            if !text.trim().is_empty() { cues.push(Cue { end, start, text }); };
+        };
+    };
+
+    cues.sort_by_key(|cue| cue.start);
+
+    cues
+}
+
+fn parse_time(text: &str) -> Option<Duration> {
+    let text = text.trim().replace(',', ".");
+    let parts = text.split(':').collect::<Vec<_>>();
+
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [hours, minutes, seconds] => (hours.parse::<u64>().ok()?, minutes.parse::<u64>().ok()?,
+            seconds.parse::<f64>().ok()?),
+        [minutes, seconds] => (0, minutes.parse::<u64>().ok()?, seconds.parse::<f64>().ok()?),
+        _ => return None
+    };
+
+    // Shifted files can hold negative or garbled times, which must not reach Duration
+    if !(0.0..60.0).contains(&seconds) { return None; }
+
+    let whole = hours.checked_mul(3600)?.checked_add(minutes.checked_mul(60)?)?;
+
+    Duration::from_secs(whole).checked_add(Duration::from_secs_f64(seconds))
+}
+
+fn read_embedded(source: &str) -> Vec<Track> {
+    let mut tracks: Vec<(usize, ffmpeg::codec::decoder::Subtitle, f64, Track)> = Vec::new();
+
+    if ffmpeg::init().is_err() { return Vec::new(); }
+
+    let mut input = match format::input(&source) {
+        Ok(input) => input,
+        Err(_) => return Vec::new()
+    };
+
+    for stream in input.streams().filter(|stream| stream.parameters().medium() == Type::Subtitle) {
+        let decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+            .and_then(|context| context.decoder().subtitle());
+
+        let language = stream.metadata().get("language").unwrap_or("und").to_string();
+        let title = stream.metadata().get("title").map(|title| format!(" - {}", title));
+
+        if let Ok(decoder) = decoder {
+            tracks.push((stream.index(), decoder, f64::from(stream.time_base()), Track {
+                cues: Vec::new(),
+                name: format!("Embedded #{} ({}){}", stream.index(), language,
+                    title.unwrap_or_default())
+            }));
+        };
+    };
+
+    if tracks.is_empty() { return Vec::new(); }
+
+    let mut packet = Packet::empty();
+
+    loop {
+        match packet.read(&mut input) {
+            Ok(()) => {},
+            Err(ffmpeg::Error::Other { errno: ffmpeg::error::EAGAIN }) => continue,
+            Err(_) => break
+        };
+
+        let (decoder, time_base, track) = match tracks.iter_mut()
+            .find(|(index, ..)| *index == packet.stream()) {
+            Some((_index, decoder, time_base, track)) => (decoder, *time_base, track),
+            None => continue
+        };
+
+        let mut decoded = Decoded::new();
+
+        if !decoder.decode(&packet, &mut decoded).unwrap_or(false) { continue; }
+
+        let start = packet.pts().unwrap_or(0).max(0) as f64 * time_base;
+        let length = packet.duration().max(0) as f64 * time_base;
+
+        let end = if length > 0.0 { start + length } else { start + decoded.end() as f64 / 1000.0 };
+
+        // Bitmap subtitles (PGS, VobSub) have no text and are skipped
+        let text = decoded.rects().filter_map(|rect| match rect {
+            Rect::Text(text) => Some(clean_markup(text.get())),
+            Rect::Ass(ass) => ass.get().splitn(9, ',').last().map(clean_ass),
+            _ => None
+        }).collect::<Vec<_>>().join("\n");
+
+        if !text.trim().is_empty() {
+            track.cues.push(Cue {
+                end: Duration::from_secs_f64(end.max(start)),
+                start: Duration::from_secs_f64(start),
+                text
+            });
+        };
+    };
+
+    tracks.into_iter().map(|(_index, _decoder, _time_base, track)| track)
+        .filter(|track| !track.cues.is_empty()).collect()
+}
+
+fn read_sidecars(source: &str) -> Vec<Track> {
+    let path = Path::new(source);
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default().to_string();
+    let prefix = stem + ".";
+
+    let mut entries = match path.parent().and_then(|dir| fs::read_dir(dir).ok()) {
+        Some(entries) => entries.flatten().map(|entry| entry.path()).collect::<Vec<_>>(),
+        None => return Vec::new()
+    };
+
+    entries.sort();
+
+    let mut tracks: Vec<Track> = Vec::new();
+
+    // "talk.srt" and "talk.en.srt" both belong to "talk.mp4", but "talkshow.srt" doesn't
+    for entry in entries {
+        let name = entry.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let ext = entry.extension().and_then(|ext| ext.to_str()).unwrap_or_default()
+            .to_lowercase();
+
+        if !SIDECAR_EXTENSIONS.contains(&ext.as_str()) || !name.starts_with(&prefix) { continue; }
+
+        let text = match fs::read(&entry) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+            Err(_) => continue
+        };
+
+        let cues = match ext.as_str() {
+            "ass" | "ssa" => parse_ass(&text),
+            _ => parse_srt(&text)
+        };
+
+        if !cues.is_empty() { tracks.push(Track { cues, name: name.to_string() }); };
+    };
+
+    tracks
+}
+
+#[derive(Clone, Debug)]
+pub struct Cue {
+    end: Duration,
+    start: Duration,
+    text: String
+}
+
+pub struct Subtitles {
+    embedded: Option<Receiver<Vec<Track>>>,
+    selected: Option<usize>,
+    tracks: Vec<Track>
+}
+
+#[derive(Clone, Debug)]
+pub struct Track {
+    cues: Vec<Cue>,
+    name: String
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn times(cues: &[Cue]) -> Vec<(u128, u128, &str)> {
+        cues.iter().map(|cue| (cue.start.as_millis(), cue.end.as_millis(), cue.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("01:02:03,450"), Some(Duration::from_millis(3_723_450)));
+        assert_eq!(parse_time("02:03.450"), Some(Duration::from_millis(123_450)));
+
+        for bad in ["-00:00:00.500", "00:00:-0.500", "00:00:NaN", "00:00:inf", "00:00:60.000",
+            "99999999999999999:00:00.000", "1:2:3:4", "abc"] {
+            assert_eq!(parse_time(bad), None, "{}", bad);
+        };
+    }
+
+    #[test]
+    fn parses_srt_and_vtt() {
+        let srt = concat!("\u{feff}1\r\n00:00:01,000 --> 00:00:02,500\r\n",
+            "<i>Hello</i> &amp; bye\r\n\r\n",
+            "2\r\n00:00:-1,000 --> 00:00:03,000\r\nShifted too far\r\n\r\n",
+            "3\r\n00:00:04,000 --> 00:00:05,000\r\nTwo\r\nlines\r\n");
+
+        assert_eq!(times(&parse_srt(srt)), vec![(1000, 2500, "Hello & bye"),
+            (4000, 5000, "Two\nlines")]);
+
+        let vtt = "WEBVTT\n\n00:10.000 --> 00:11.000 align:start\nCue\n\nNOTE no timing here\n";
+
+        assert_eq!(times(&parse_srt(vtt)), vec![(10_000, 11_000, "Cue")]);
+    }
+
+    #[test]
+    fn parses_ass() {
+        let ass = concat!("[Script Info]\nTitle: Test\n\n[Events]\n",
+            "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+            "Dialogue: 0,0:00:03.00,0:00:04.00,Default,,0,0,0,,Second, with a comma\n",
+            "Dialogue: 0,0:00:01.00,0:00:02.50,Default,,0,0,0,,{\\i1}First{\\i0}\\Nline\n",
+            "Dialogue: 0,0:00:NaN,0:00:05.00,Default,,0,0,0,,Broken\n",
+            "Dialogue: 0,0:00:06.00\n");
+
+        assert_eq!(times(&parse_ass(ass)), vec![(1000, 2500, "First\nline"),
+            (3000, 4000, "Second, with a comma")]);
+    }
+}
\ No newline at end of file
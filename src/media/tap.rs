@@ -0,0 +1,182 @@
+use rodio::{ChannelCount, Sample, SampleRate, Source, source::SeekError};
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, mpsc::{Receiver, SyncSender, TrySendError, sync_channel}},
+    time::Duration
+};
+
+const CAPACITY: usize = 16384;
+const CHUNK: usize = 256;
+const QUEUE_SIZE: usize = 1024;
+
+impl Chunk {
+    //** Getters **//
+    pub fn get_channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn get_sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn get_samples(&self) -> &[f32] {
+        &self.samples
+    }
+}
+
+impl Outputs {
+    //** Misc. **//
+    pub fn new() -> Self {
+        Self {
+            senders: Vec::new()
+        }
+    }
+
+    fn send(&mut self, chunk: Chunk) {
+        // Listeners that fall behind lose chunks instead of stalling playback
+        self.senders.retain(|sender| {
+            !matches!(sender.try_send(chunk.clone()), Err(TrySendError::Disconnected(_)))
+        });
+    }
+
+    pub fn subscribe(&mut self) -> Receiver<Chunk> {
+        let (sender, receiver) = sync_channel(QUEUE_SIZE);
+
+        self.senders.push(sender);
+
+        receiver
+    }
+}
+
+impl Samples {
+    //** Getters **//
+    pub fn get_channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn get_latest(&self, count: usize) -> Vec<f32> {
+        let skip = self.buffer.len().saturating_sub(count);
+
+        self.buffer.iter().skip(skip).copied().collect()
+    }
+
+    pub fn get_sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    //** Misc. **//
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    pub fn new() -> Self {
+        Self {
+            buffer: VecDeque::with_capacity(CAPACITY),
+            channels: 2,
+            sample_rate: 44100
+        }
+    }
+
+    fn push(&mut self, samples: &[f32], channels: u16, sample_rate: u32) {
+        self.channels = channels;
+        self.sample_rate = sample_rate;
+
+        for sample in samples {
+            if self.buffer.len() == CAPACITY { self.buffer.pop_front(); };
+
+            self.buffer.push_back(*sample);
+        };
+    }
+}
+
+impl<S: Source> Tap<S> {
+    pub fn new(source: S, samples: Arc<Mutex<Samples>>, outputs: Arc<Mutex<Outputs>>) -> Self {
+        Self {
+            outputs,
+            pending: Vec::with_capacity(CHUNK),
+            samples,
+            source
+        }
+    }
+
+    fn flush(&mut self) {
+        let channels: u16 = self.source.channels().into();
+        let sample_rate: u32 = self.source.sample_rate().into();
+
+        // The audio thread never waits on the UI, so a busy lock just drops this chunk
+        if let Ok(mut samples) = self.samples.try_lock() {
+            samples.push(&self.pending, channels, sample_rate);
+        };
+
+        if let Ok(mut outputs) = self.outputs.try_lock() {
+            if !outputs.senders.is_empty() {
+                outputs.send(Chunk { channels, sample_rate, samples: self.pending.clone() });
+            };
+        };
+
+        self.pending.clear();
+    }
+}
+
+impl<S: Source> Iterator for Tap<S> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        let sample = self.source.next()?;
+
+        self.pending.push(sample);
+
+        if self.pending.len() == CHUNK { self.flush(); };
+
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for Tap<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), SeekError> {
+        self.pending.clear();
+
+        self.source.try_seek(position)
+    }
+}
+
+#[derive(Clone)]
+pub struct Chunk {
+    channels: u16,
+    sample_rate: u32,
+    samples: Vec<f32>
+}
+
+pub struct Outputs {
+    senders: Vec<SyncSender<Chunk>>
+}
+
+pub struct Samples {
+    buffer: VecDeque<f32>,
+    channels: u16,
+    sample_rate: u32
+}
+
+pub struct Tap<S: Source> {
+    outputs: Arc<Mutex<Outputs>>,
+    pending: Vec<f32>,
+    samples: Arc<Mutex<Samples>>,
+    source: S
+}
\ No newline at end of file
@@ -0,0 +1,222 @@
+use egui::ColorImage;
+
+use ffmpeg_next::{
+    self as ffmpeg,
+    Packet,
+    format::{self, Pixel, context::Input, stream::Disposition},
+    media::Type,
+    software::scaling::{context::Context as Scaler, flag::Flags},
+    util::frame::video::Video as VideoFrame
+};
+
+use std::{
+    error::Error,
+    sync::mpsc::{self, Receiver, Sender, SyncSender, TryRecvError},
+    thread,
+    time::Duration
+};
+
+const MAX_WIDTH: u32 = 1280;
+const QUEUE_SIZE: usize = 8;
+
+// Frames further than this from the audio clock mean playback jumped, so the decoder seeks
+const DRIFT_AHEAD: f64 = 2.0;
+const DRIFT_BEHIND: f64 = 0.5;
+
+enum Command {
+    Seek(u64, Duration)
+}
+
+impl Video {
+    //** Getters **//
+    pub fn get_frame(&mut self, clock: Duration) -> Option<ColorImage> {
+        let clock = clock.as_secs_f64();
+
+        if self.position.is_some_and(|position| {
+            clock < position - DRIFT_BEHIND || clock > position + DRIFT_AHEAD
+        }) {
+            self.seek(Duration::from_secs_f64(clock));
+        };
+
+        let mut latest = None;
+
+        loop {
+            let frame = match self.pending.take() {
+                Some(frame) => frame,
+
+                None => match self.frames.try_recv() {
+                    Ok(frame) => frame,
+                    Err(TryRecvError::Empty) => break,
+
+                    Err(TryRecvError::Disconnected) => {
+                        self.finished = true;
+
+                        break;
+                    }
+                }
+            };
+
+            if frame.generation != self.generation { continue; }
+
+            if frame.pts > clock {
+                self.pending = Some(frame);
+
+                break;
+            };
+
+            // Late frames are dropped; only the newest one due is shown
+            self.position = Some(frame.pts);
+            latest = Some(frame.image);
+        };
+
+        latest
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    //** Misc. **//
+    pub fn open(source: &str) -> Option<Self> {
+        ffmpeg::init().ok()?;
+
+        // Cover art embedded in audio files shows up as a single-frame video stream
+        let input = format::input(&source).ok()?;
+        let stream = input.streams().best(Type::Video)?;
+
+        if stream.disposition().contains(Disposition::ATTACHED_PIC) { return None; }
+
+        let (frame_sender, frames) = mpsc::sync_channel::<Frame>(QUEUE_SIZE);
+        let (commands, command_receiver) = mpsc::channel::<Command>();
+        let path = source.to_string();
+
+        thread::spawn(move || {
+            let _ = decode(&path, frame_sender, command_receiver);
+        });
+
+        Some(Self {
+            commands,
+            finished: false,
+            frames,
+            generation: 0,
+            pending: None,
+            position: None
+        })
+    }
+
+    pub fn seek(&mut self, position: Duration) {
+        self.generation += 1;
+        self.pending = None;
+        self.position = None;
+
+        let _ = self.commands.send(Command::Seek(self.generation, position));
+    }
+}
+
+fn decode(path: &str, frames: SyncSender<Frame>, commands: Receiver<Command>)
+    -> Result<(), Box<dyn Error>> {
+    let mut input = format::input(&path)?;
+    let stream = input.streams().best(Type::Video).ok_or("No video stream.")?;
+    let index = stream.index();
+    let time_base = f64::from(stream.time_base());
+
+    // Timestamps are shifted so the first frame lines up with the audio clock at zero
+    let start = match stream.start_time() {
+        ffmpeg::ffi::AV_NOPTS_VALUE => 0.0,
+        start => start as f64 * time_base
+    };
+
+    let context = ffmpeg::codec::context::Context::from_parameters(stream.parameters())?;
+    let mut decoder = context.decoder().video()?;
+
+    let width = decoder.width().min(MAX_WIDTH);
+    let height = (decoder.height() as u64 * width as u64 / decoder.width().max(1) as u64) as u32;
+
+    let mut scaler = Scaler::get(decoder.format(), decoder.width(), decoder.height(),
+        Pixel::RGBA, width, height, Flags::BILINEAR)?;
+
+    let mut generation = 0;
+    let mut ended = false;
+
+    loop {
+        // At the end of the file there is nothing to do until playback seeks back
+        let command = if ended { commands.recv().ok() } else { commands.try_recv().ok() };
+
+        if ended && command.is_none() { return Ok(()); }
+
+        if let Some(Command::Seek(next, position)) = command {
+            generation = next;
+            ended = false;
+
+            seek(&mut input, position.as_secs_f64() + start)?;
+            decoder.flush();
+
+            continue;
+        };
+
+        let mut packet = Packet::empty();
+
+        match packet.read(&mut input) {
+            Ok(()) => {},
+            Err(ffmpeg::Error::Eof) => { ended = true; continue; },
+            Err(ffmpeg::Error::Other { errno: ffmpeg::error::EAGAIN }) => continue,
+            Err(error) => return Err(error.into())
+        };
+
+        if packet.stream() != index || decoder.send_packet(&packet).is_err() { continue; }
+
+        let mut decoded = VideoFrame::empty();
+
+        while decoder.receive_frame(&mut decoded).is_ok() {
+            let mut rgba = VideoFrame::empty();
+
+            if scaler.run(&decoded, &mut rgba).is_err() { continue; };
+
+            let pts = decoded.timestamp().or(decoded.pts()).unwrap_or(0) as f64 * time_base - start;
+
+            let frame = Frame {
+                generation,
+                image: to_image(&rgba, width, height),
+                pts: pts.max(0.0)
+            };
+
+            // The UI dropping the receiver means the track changed
+            if frames.send(frame).is_err() { return Ok(()); }
+        };
+    };
+}
+
+fn seek(input: &mut Input, position: f64) -> Result<(), ffmpeg::Error> {
+    let timestamp = (position * ffmpeg::ffi::AV_TIME_BASE as f64) as i64;
+
+    input.seek(timestamp, ..timestamp)
+}
+
+fn to_image(frame: &VideoFrame, width: u32, height: u32) -> ColorImage {
+    let stride = frame.stride(0);
+    let row = width as usize * 4;
+    let data = frame.data(0);
+    let mut pixels: Vec<u8> = Vec::with_capacity(row * height as usize);
+
+    // Rows are padded to the stride, so copy them one at a time
+    for line in 0..height as usize {
+        pixels.extend_from_slice(&data[line * stride..line * stride + row]);
+    };
+
+    ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &pixels)
+}
+
+struct Frame {
+    generation: u64,
+    image: ColorImage,
+    pts: f64
+}
+
+pub struct Video {
+    commands: Sender<Command>,
+    finished: bool,
+    frames: Receiver<Frame>,
+    generation: u64,
+    pending: Option<Frame>,
+    position: Option<f64>
+}
\ No newline at end of file
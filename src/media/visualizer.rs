@@ -0,0 +1,250 @@
+use std::{
+    f32::consts::PI,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant}
+};
+
+use crate::media::tap::Samples;
+
+const BANDS: usize = 48;
+const FFT_SIZE: usize = 2048;
+const FLOOR_DB: f32 = -70.0;
+const MAX_FREQ: f32 = 16000.0;
+const MIN_FREQ: f32 = 30.0;
+const SCOPE_SIZE: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VisualMode { Off, Meters, Oscilloscope, Spectrum }
+
+impl VisualMode {
+    pub const ALL: [VisualMode; 4] = [
+        VisualMode::Off, VisualMode::Spectrum, VisualMode::Oscilloscope, VisualMode::Meters
+    ];
+
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            VisualMode::Off => "Artwork",
+            VisualMode::Meters => "Meters",
+            VisualMode::Oscilloscope => "Scope",
+            VisualMode::Spectrum => "Spectrum"
+        }
+    }
+}
+
+impl Visualizer {
+    //** Getters **//
+    pub fn get_bands(&self) -> Vec<f32> {
+        self.bands.clone()
+    }
+
+    pub fn get_fps(&self) -> u32 {
+        self.fps
+    }
+
+    pub fn get_interval(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / self.fps.max(1) as f32)
+    }
+
+    pub fn get_levels(&self) -> Vec<f32> {
+        self.levels.clone()
+    }
+
+    pub fn get_mode(&self) -> VisualMode {
+        self.mode
+    }
+
+    pub fn get_peaks(&self) -> Vec<f32> {
+        self.peaks.clone()
+    }
+
+    pub fn get_wave(&self) -> Vec<f32> {
+        self.wave.clone()
+    }
+
+    //** Misc. **//
+    fn analyze_levels(&mut self, samples: &[f32], channels: usize) {
+        let mut levels = vec![0.0f32; channels];
+        let frames = (samples.len() / channels).max(1);
+
+        for frame in samples.chunks_exact(channels) {
+            for (channel, sample) in frame.iter().enumerate() {
+                levels[channel] += sample * sample;
+            };
+        };
+
+        if self.peaks.len() != channels { self.peaks = vec![0.0; channels]; };
+
+        self.levels = levels.iter().map(|sum| to_level((sum / frames as f32).sqrt())).collect();
+
+        // Peak markers hold the loudest level and fall back slowly
+        for (peak, level) in self.peaks.iter_mut().zip(&self.levels) {
+            *peak = level.max(*peak - 0.01);
+        };
+    }
+
+    fn analyze_spectrum(&mut self, mono: &[f32], sample_rate: u32) {
+        let mut real = vec![0.0f32; FFT_SIZE];
+        let mut imag = vec![0.0f32; FFT_SIZE];
+        let offset = FFT_SIZE - mono.len().min(FFT_SIZE);
+
+        for (index, sample) in mono.iter().rev().take(FFT_SIZE).rev().enumerate() {
+            let window = 0.5 - 0.5 * (2.0 * PI * (index + offset) as f32 / FFT_SIZE as f32).cos();
+
+            real[index + offset] = sample * window;
+        };
+
+        fft(&mut real, &mut imag);
+
+        let nyquist = sample_rate as f32 / 2.0;
+        let top = MAX_FREQ.min(nyquist);
+        let bin_width = sample_rate as f32 / FFT_SIZE as f32;
+
+        if self.bands.len() != BANDS { self.bands = vec![0.0; BANDS]; };
+
+        for band in 0..BANDS {
+            // Bands are spaced logarithmically so low notes are not crammed into one bar
+            let low = MIN_FREQ * (top / MIN_FREQ).powf(band as f32 / BANDS as f32);
+            let high = MIN_FREQ * (top / MIN_FREQ).powf((band + 1) as f32 / BANDS as f32);
+
+            let first = ((low / bin_width) as usize).clamp(1, FFT_SIZE / 2 - 1);
+            let last = ((high / bin_width) as usize).clamp(first + 1, FFT_SIZE / 2);
+
+            let magnitude = (first..last)
+                .map(|bin| (real[bin] * real[bin] + imag[bin] * imag[bin]).sqrt())
+                .fold(0.0f32, f32::max) * 4.0 / FFT_SIZE as f32;
+
+            let level = to_level(magnitude);
+
+            self.bands[band] = level.max(self.bands[band] * 0.85);
+        };
+    }
+
+    pub fn new(samples: Arc<Mutex<Samples>>) -> Self {
+        Self {
+            // Output
+            bands: vec![0.0; BANDS],
+            levels: vec![0.0; 2],
+            peaks: vec![0.0; 2],
+            wave: Vec::new(),
+
+            // Settings
+            fps: 30,
+            mode: VisualMode::Off,
+
+            // Misc.
+            samples,
+            updated: None
+        }
+    }
+
+    pub fn update(&mut self) -> bool {
+        if self.mode == VisualMode::Off { return false; }
+
+        if let Some(updated) = self.updated {
+            if updated.elapsed() < self.get_interval() { return false; }
+        };
+
+        self.updated = Some(Instant::now());
+
+        let (latest, channels, sample_rate) = match self.samples.lock() {
+            Ok(samples) => {
+                let channels = samples.get_channels().max(1) as usize;
+
+                (samples.get_latest(FFT_SIZE * channels), channels, samples.get_sample_rate())
+            },
+
+            Err(_) => return false
+        };
+
+        let mono = latest.chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32).collect::<Vec<_>>();
+
+        match self.mode {
+            VisualMode::Meters => self.analyze_levels(&latest, channels),
+            VisualMode::Oscilloscope => {
+                self.wave = mono.iter().rev().take(SCOPE_SIZE).rev().copied().collect();
+            },
+            VisualMode::Spectrum => self.analyze_spectrum(&mono, sample_rate),
+            VisualMode::Off => {}
+        };
+
+        true
+    }
+
+    //** Setters **//
+    pub fn set_fps(&mut self, fps: u32) {
+        self.fps = fps;
+    }
+
+    pub fn set_mode(&mut self, mode: VisualMode) {
+        self.mode = mode;
+        self.updated = None;
+    }
+}
+
+fn fft(real: &mut [f32], imag: &mut [f32]) {
+    let size = real.len();
+    let mut target = 0;
+
+    // Bit-reversal permutation followed by iterative radix-2 butterflies
+    for index in 1..size {
+        let mut bit = size >> 1;
+
+        while target & bit != 0 {
+            target ^= bit;
+            bit >>= 1;
+        };
+
+        target |= bit;
+
+        if index < target {
+            real.swap(index, target);
+            imag.swap(index, target);
+        };
+    };
+
+    let mut length = 2;
+
+    while length <= size {
+        let angle = -2.0 * PI / length as f32;
+
+        for start in (0..size).step_by(length) {
+            for step in 0..length / 2 {
+                let (sin, cos) = (angle * step as f32).sin_cos();
+                let (even, odd) = (start + step, start + step + length / 2);
+
+                let odd_real = real[odd] * cos - imag[odd] * sin;
+                let odd_imag = real[odd] * sin + imag[odd] * cos;
+
+                real[odd] = real[even] - odd_real;
+                imag[odd] = imag[even] - odd_imag;
+                real[even] += odd_real;
+                imag[even] += odd_imag;
+            };
+        };
+
+        length <<= 1;
+    };
+}
+
+fn to_level(amplitude: f32) -> f32 {
+    let decibels = 20.0 * amplitude.max(1e-9).log10();
+
+    ((decibels - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0)
+}
+
+pub struct Visualizer {
+    // Output
+    bands: Vec<f32>,
+    levels: Vec<f32>,
+    peaks: Vec<f32>,
+    wave: Vec<f32>,
+
+    // Settings
+    fps: u32,
+    mode: VisualMode,
+
+    // Misc.
+    samples: Arc<Mutex<Samples>>,
+    updated: Option<Instant>
+}
\ No newline at end of file
@@ -0,0 +1,115 @@
+use notify::{
+    Event,
+    EventKind,
+    RecommendedWatcher,
+    RecursiveMode,
+    Watcher as _,
+    event::{ModifyKind, RenameMode}
+};
+
+use std::{
+    path::Path,
+    sync::mpsc::{self, Receiver}
+};
+
+use crate::utils::filesys;
+
+pub enum Change {
+    Created(String),
+    Modified(String),
+    Playlists,
+    Removed(String),
+    Renamed(String, String)
+}
+
+impl Watcher {
+    //** Getters **//
+    pub fn get_roots(&self) -> Vec<String> {
+        self.roots.clone()
+    }
+
+    //** Misc. **//
+    pub fn new(roots: Vec<String>, playlists: String) -> Option<Self> {
+        let (sender, receiver) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = notify::recommended_watcher(sender).ok()?;
+
+        // Unmounted roots are skipped rather than failing the whole watcher
+        for root in &roots {
+            if Path::new(root).is_dir() {
+                let _ = watcher.watch(Path::new(root), RecursiveMode::Recursive);
+            };
+        };
+
+        let parent = Path::new(&playlists).parent().map(|dir| dir.to_path_buf());
+
+        if let Some(dir) = &parent {
+            let _ = watcher.watch(dir, RecursiveMode::NonRecursive);
+        };
+
+        // The library database and caches can sit inside a root
+        let excluded = vec![filesys::cache_dir(), filesys::data_dir()];
+
+        Some(Self {
+            _watcher: watcher,
+            excluded,
+            playlists,
+            receiver,
+            roots
+        })
+    }
+
+    pub fn poll(&self) -> Vec<Change> {
+        let mut changes: Vec<Change> = Vec::new();
+
+        for event in self.receiver.try_iter().flatten() {
+            let paths = event.paths.iter().map(|path| path.display().to_string())
+                .collect::<Vec<_>>();
+
+            if paths.iter().any(|path| *path == self.playlists) {
+                changes.push(Change::Playlists);
+
+                continue;
+            };
+
+            let in_roots = paths.iter()
+                .any(|path| self.roots.iter().any(|root| Path::new(path).starts_with(root)));
+
+            let excluded = paths.iter()
+                .any(|path| self.excluded.iter().any(|dir| Path::new(path).starts_with(dir)));
+
+            if !in_roots || excluded { continue; };
+
+            match event.kind {
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                    for path in paths { changes.push(Change::Created(path)); };
+                },
+
+                EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                    for path in paths { changes.push(Change::Removed(path)); };
+                },
+
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                    if let (Some(from), Some(to)) = (paths.get(0), paths.get(1)) {
+                        changes.push(Change::Renamed(from.clone(), to.clone()));
+                    };
+                },
+
+                EventKind::Modify(ModifyKind::Data(_)) => {
+                    for path in paths { changes.push(Change::Modified(path)); };
+                },
+
+                _ => {}
+            };
+        };
+
+        changes
+    }
+}
+
+pub struct Watcher {
+    _watcher: RecommendedWatcher,
+    excluded: Vec<String>,
+    playlists: String,
+    receiver: Receiver<notify::Result<Event>>,
+    roots: Vec<String>
+}
\ No newline at end of file
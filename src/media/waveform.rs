@@ -0,0 +1,91 @@
+use rodio::Decoder;
+
+use std::fs::File;
+
+const BUCKETS: usize = 480;
+const WINDOW: usize = 1024;
+
+impl Waveform {
+    //** Getters **//
+    pub fn get_peak(&self, position: f32) -> f32 {
+        self.peaks.get(self.index(position)).copied().unwrap_or(0.0)
+    }
+
+    pub fn get_rms(&self, position: f32) -> f32 {
+        self.rms.get(self.index(position)).copied().unwrap_or(0.0)
+    }
+
+    fn index(&self, position: f32) -> usize {
+        ((position.clamp(0.0, 1.0) * self.peaks.len() as f32) as usize)
+            .min(self.peaks.len().saturating_sub(1))
+    }
+
+    //** Misc. **//
+    pub fn compute(source: &str) -> Option<Self> {
+        let decoded = Decoder::try_from(File::open(source).ok()?).ok()?;
+        let mut windows: Vec<(f32, f32, usize)> = Vec::new();
+        let mut current = (0.0f32, 0.0f32, 0usize);
+
+        // Channels stay interleaved; a combined envelope is all the seek bar needs
+        for sample in decoded {
+            let level = sample.abs();
+
+            current.0 = current.0.max(level);
+            current.1 += level * level;
+            current.2 += 1;
+
+            if current.2 == WINDOW {
+                windows.push(current);
+
+                current = (0.0, 0.0, 0);
+            };
+        };
+
+        if current.2 > 0 { windows.push(current); };
+        if windows.is_empty() { return None; }
+
+        let buckets = BUCKETS.min(windows.len());
+        let mut peaks: Vec<f32> = Vec::with_capacity(buckets);
+        let mut rms: Vec<f32> = Vec::with_capacity(buckets);
+
+        for bucket in 0..buckets {
+            let start = bucket * windows.len() / buckets;
+            let range = &windows[start..(bucket + 1) * windows.len() / buckets];
+
+            let peak = range.iter().fold(0.0f32, |peak, window| peak.max(window.0));
+            let squares = range.iter().map(|window| window.1).sum::<f32>();
+            let count = range.iter().map(|window| window.2).sum::<usize>().max(1);
+
+            peaks.push(peak.min(1.0));
+            rms.push((squares / count as f32).sqrt().min(1.0));
+        };
+
+        Some(Self {
+            peaks,
+            rms
+        })
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.is_empty() || bytes.len() % 2 != 0 { return None; }
+
+        let level = |byte: &u8| *byte as f32 / 255.0;
+
+        Some(Self {
+            peaks: bytes.iter().step_by(2).map(level).collect(),
+            rms: bytes.iter().skip(1).step_by(2).map(level).collect()
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let byte = |level: &f32| (level.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        self.peaks.iter().zip(&self.rms).flat_map(|(peak, rms)| [byte(peak), byte(rms)]).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Waveform {
+    peaks: Vec<f32>,
+    rms: Vec<f32>
+}
\ No newline at end of file
@@ -0,0 +1,194 @@
+use egui::{ColorImage, Context, TextureHandle, TextureOptions};
+use image::{DynamicImage, ImageFormat};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, mpsc::{self, Sender}},
+    thread,
+    time::UNIX_EPOCH
+};
+
+use crate::utils::{data, filesys};
+
+const ART_SIZE: u32 = 512;
+const THUMBNAIL_SIZE: u32 = 48;
+
+impl Artwork {
+    //** Getters **//
+    pub fn get(&mut self, ctx: &Context, source: &str) -> Option<TextureHandle> {
+        self.load(ctx, source, ART_SIZE)
+    }
+
+    pub fn get_path(&self, source: &str) -> Option<String> {
+        // Only art already decoded for the UI has a thumbnail on disk
+        let key = self.keys.get(source)?.as_ref()?;
+        let cached = cache_path(key, ART_SIZE);
+
+        if cached.exists() { Some(cached.to_string_lossy().to_string()) } else { None }
+    }
+
+    pub fn get_thumbnail(&mut self, ctx: &Context, source: &str) -> Option<TextureHandle> {
+        self.load(ctx, source, THUMBNAIL_SIZE)
+    }
+
+    //** Misc. **//
+    fn load(&mut self, ctx: &Context, source: &str, size: u32) -> Option<TextureHandle> {
+        if source.is_empty() { return None; }
+
+        self.upload(ctx);
+
+        match self.keys.get(source) {
+            Some(None) => return None,
+
+            Some(Some(key)) => {
+                if let Some(texture) = self.textures.get(&texture_name(key, size)) {
+                    return Some(texture.clone());
+                };
+            },
+
+            None => {}
+        };
+
+        if self.requested.insert((source.to_string(), size)) {
+            let _ = self.sender.send((source.to_string(), size));
+        };
+
+        None
+    }
+
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<(String, u32)>();
+        let ready: Arc<Mutex<Vec<Decoded>>> = Arc::new(Mutex::new(Vec::new()));
+        let shared = ready.clone();
+
+        thread::spawn(move || {
+            let mut decoded: HashSet<String> = HashSet::new();
+
+            for (source, size) in receiver {
+                let (key, bytes) = match locate(&source) {
+                    Some(found) => found,
+
+                    None => {
+                        if let Ok(mut ready) = shared.lock() { ready.push((source, size, None)); };
+
+                        continue;
+                    }
+                };
+
+                // Tracks from the same album share one texture per size
+                let name = texture_name(&key, size);
+                let image = if decoded.insert(name) { thumbnail(&key, bytes, size) } else { None };
+
+                if let Ok(mut ready) = shared.lock() {
+                    ready.push((source, size, Some((key, image))));
+                };
+            };
+        });
+
+        Self {
+            keys: HashMap::new(),
+            ready,
+            requested: HashSet::new(),
+            sender,
+            textures: HashMap::new()
+        }
+    }
+
+    pub fn refresh(&mut self, path: &str) {
+        // A changed folder cover affects every track beside it
+        let dir = Path::new(path).parent();
+        let cover = !filesys::is_media(path.to_string());
+
+        let stale = self.keys.keys()
+            .filter(|source| *source == path || (cover && Path::new(source).parent() == dir))
+            .cloned().collect::<HashSet<_>>();
+
+        self.keys.retain(|source, _| !stale.contains(source));
+        self.requested.retain(|(source, _)| !stale.contains(source) && source.as_str() != path);
+    }
+
+    fn upload(&mut self, ctx: &Context) {
+        let ready = match self.ready.lock() {
+            Ok(mut ready) => std::mem::take(&mut *ready),
+            Err(_) => return
+        };
+
+        for (source, size, found) in ready {
+            match found {
+                Some((key, image)) => {
+                    if let Some(image) = image {
+                        let name = texture_name(&key, size);
+                        let texture = ctx.load_texture(name.clone(), image, TextureOptions::LINEAR);
+
+                        self.textures.insert(name, texture);
+                    };
+
+                    self.keys.insert(source, Some(key));
+                },
+
+                None => { self.keys.insert(source, None); }
+            };
+        };
+    }
+}
+
+fn cache_path(key: &str, size: u32) -> PathBuf {
+    Path::new(&filesys::cache_dir()).join("art").join(texture_name(key, size) + ".png")
+}
+
+fn locate(source: &str) -> Option<(String, Option<Vec<u8>>)> {
+    if let Some(bytes) = data::read_cover(source) {
+        return Some((data::hash_bytes(&bytes), Some(bytes)));
+    };
+
+    let cover = filesys::find_cover(source.to_string())?;
+
+    // The modified time is part of the key so a replaced cover isn't served from the cache
+    let modified = fs::metadata(&cover).and_then(|meta| meta.modified()).ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|time| time.as_nanos()).unwrap_or_default();
+
+    let key = data::hash_bytes(format!("{}\0{}", cover, modified).as_bytes());
+
+    Some((key, filesys::get_file(cover).ok()))
+}
+
+fn texture_name(key: &str, size: u32) -> String {
+    format!("{}-{}", key, size)
+}
+
+fn thumbnail(key: &str, bytes: Option<Vec<u8>>, size: u32) -> Option<ColorImage> {
+    let cached = cache_path(key, size);
+
+    let image = match image::open(&cached) {
+        Ok(image) => image,
+
+        Err(_) => {
+            let image = image::load_from_memory(&bytes?).ok()?.thumbnail(size, size);
+
+            if let Some(dir) = cached.parent() { let _ = fs::create_dir_all(dir); };
+
+            let _ = DynamicImage::ImageRgba8(image.to_rgba8()).save_with_format(&cached,
+                ImageFormat::Png);
+
+            image
+        }
+    };
+
+    let rgba = image.to_rgba8();
+    let size = [rgba.width() as usize, rgba.height() as usize];
+
+    Some(ColorImage::from_rgba_unmultiplied(size, rgba.as_flat_samples().as_slice()))
+}
+
+type Decoded = (String, u32, Option<(String, Option<ColorImage>)>);
+
+pub struct Artwork {
+    keys: HashMap<String, Option<String>>,
+    ready: Arc<Mutex<Vec<Decoded>>>,
+    requested: HashSet<(String, u32)>,
+    sender: Sender<(String, u32)>,
+    textures: HashMap<String, TextureHandle>
+}
\ No newline at end of file
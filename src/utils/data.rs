@@ -1,4 +1,11 @@
-use audiotags::Tag;
+use lofty::{
+    config::WriteOptions,
+    error::{ErrorKind, LoftyError},
+    file::{AudioFile, FileType, TaggedFileExt},
+    picture::PictureType,
+    probe::Probe,
+    tag::{Accessor, ItemKey, Tag as LoftyTag, TagExt}
+};
 
 use rodio::{
     Decoder,
@@ -9,13 +16,290 @@ use rodio::{
 };
 
 use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
     fs::File,
-    io::BufReader,
+    io::{self, BufReader, Read},
     path::Path,
     time::Duration
 };
 
-use crate::utils::filesys;
+use crate::utils::{filesys, probe};
+
+#[derive(Debug)]
+pub enum MetadataError {
+    Io(io::Error),
+    Missing(String),
+    Unreadable(String)
+}
+
+impl Display for MetadataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataError::Io(error) => write!(f, "Unable to open file: {}", error),
+            MetadataError::Missing(path) => write!(f, "File not found: {}", path),
+            MetadataError::Unreadable(reason) => write!(f, "Unable to read metadata: {}", reason)
+        }
+    }
+}
+
+impl Error for MetadataError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TagField {
+    Title,
+    Artist,
+    AlbumArtist,
+    Album,
+    Track,
+    Disc,
+    Year,
+    Genre,
+    Comment,
+    Composer
+}
+
+impl TagField {
+    pub const ALL: [TagField; 10] = [
+        TagField::Title,
+        TagField::Artist,
+        TagField::AlbumArtist,
+        TagField::Album,
+        TagField::Track,
+        TagField::Disc,
+        TagField::Year,
+        TagField::Genre,
+        TagField::Comment,
+        TagField::Composer
+    ];
+
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            TagField::Title => "Title",
+            TagField::Artist => "Artist",
+            TagField::AlbumArtist => "Album Artist",
+            TagField::Album => "Album",
+            TagField::Track => "Track",
+            TagField::Disc => "Disc",
+            TagField::Year => "Year",
+            TagField::Genre => "Genre",
+            TagField::Comment => "Comment",
+            TagField::Composer => "Composer"
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, TagField::Track | TagField::Disc | TagField::Year)
+    }
+}
+
+impl Tags {
+    //** Getters **//
+    pub fn get(&self, field: TagField) -> String {
+        self.fields[Self::index(field)].clone()
+    }
+
+    pub fn get_mut(&mut self, field: TagField) -> &mut String {
+        &mut self.fields[Self::index(field)]
+    }
+
+    fn get_number(&self, field: TagField) -> Result<Option<u32>, Box<dyn Error>> {
+        let value = self.get(field);
+
+        if value.trim().is_empty() { return Ok(None); }
+
+        match value.trim().parse::<u32>() {
+            Ok(number) => Ok(Some(number)),
+            Err(_) => Err(format!("{} must be a number.", field.get_label()).into())
+        }
+    }
+
+    fn index(field: TagField) -> usize {
+        TagField::ALL.iter().position(|entry| *entry == field).unwrap_or(0)
+    }
+
+    //** Misc. **//
+    pub fn new() -> Self {
+        Self {
+            fields: Default::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for field in TagField::ALL {
+            if field.is_numeric() { self.get_number(field)?; };
+        };
+
+        Ok(())
+    }
+
+    //** Setters **//
+    pub fn set(&mut self, field: TagField, value: String) {
+        self.fields[Self::index(field)] = value;
+    }
+}
+
+impl TrackMetadata {
+    //** Getters **//
+    pub fn get_album(&self) -> Option<String> {
+        self.album.clone()
+    }
+
+    pub fn get_album_artist(&self) -> Option<String> {
+        self.album_artist.clone()
+    }
+
+    pub fn get_artist(&self) -> Option<String> {
+        self.artist.clone()
+    }
+
+    pub fn get_bitrate(&self) -> Option<u32> {
+        self.bitrate
+    }
+
+    pub fn get_channels(&self) -> Option<u8> {
+        self.channels
+    }
+
+    pub fn get_codec(&self) -> Option<String> {
+        self.codec.clone()
+    }
+
+    pub fn get_cover_hash(&self) -> Option<String> {
+        self.cover_hash.clone()
+    }
+
+    pub fn get_disc(&self) -> Option<u32> {
+        self.disc
+    }
+
+    pub fn get_duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    pub fn get_genre(&self) -> Option<String> {
+        self.genre.clone()
+    }
+
+    pub fn get_sample_rate(&self) -> Option<u32> {
+        self.sample_rate
+    }
+
+    pub fn get_title(&self) -> Option<String> {
+        self.title.clone()
+    }
+
+    pub fn get_track(&self) -> Option<u32> {
+        self.track
+    }
+
+    pub fn get_year(&self) -> Option<u32> {
+        self.year
+    }
+
+    //** Misc. **//
+    pub fn decode(line: &str) -> Self {
+        let items = line.split("⁘").collect::<Vec<_>>();
+        let text = |index: usize| items.get(index).filter(|item| !item.is_empty())
+            .map(|item| item.to_string());
+        let number = |index: usize| text(index).and_then(|item| item.parse::<u32>().ok());
+
+        Self {
+            // Tags
+            album: text(0),
+            album_artist: text(1),
+            artist: text(2),
+            disc: number(3),
+            genre: text(4),
+            title: text(5),
+            track: number(6),
+            year: number(7),
+
+            // Properties
+            bitrate: number(8),
+            channels: number(9).map(|channels| channels as u8),
+            codec: text(10),
+            duration: text(11).and_then(|item| item.parse::<u64>().ok()).map(Duration::from_millis),
+            sample_rate: number(12),
+
+            // Misc.
+            cover_hash: text(13)
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let text = |value: &Option<String>| value.clone().unwrap_or_default().replace("⁘", "");
+        let number = |value: Option<u32>| value.map(|n| n.to_string()).unwrap_or_default();
+
+        [
+            text(&self.album),
+            text(&self.album_artist),
+            text(&self.artist),
+            number(self.disc),
+            text(&self.genre),
+            text(&self.title),
+            number(self.track),
+            number(self.year),
+            number(self.bitrate),
+            number(self.channels.map(|channels| channels as u32)),
+            text(&self.codec),
+            self.duration.map(|d| d.as_millis().to_string()).unwrap_or_default(),
+            number(self.sample_rate),
+            text(&self.cover_hash)
+        ].join("⁘")
+    }
+
+    //** Setters **//
+    pub fn set_duration(&mut self, duration: Option<Duration>) {
+        self.duration = duration;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tags {
+    fields: [String; 10]
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrackMetadata {
+    // Tags
+    album: Option<String>,
+    album_artist: Option<String>,
+    artist: Option<String>,
+    disc: Option<u32>,
+    genre: Option<String>,
+    title: Option<String>,
+    track: Option<u32>,
+    year: Option<u32>,
+
+    // Properties
+    bitrate: Option<u32>,
+    channels: Option<u8>,
+    codec: Option<String>,
+    duration: Option<Duration>,
+    sample_rate: Option<u32>,
+
+    // Misc.
+    cover_hash: Option<String>
+}
+
+fn codec_name(file_type: FileType) -> String {
+    match file_type {
+        FileType::Aac => "AAC".to_string(),
+        FileType::Aiff => "AIFF".to_string(),
+        FileType::Ape => "APE".to_string(),
+        FileType::Flac => "FLAC".to_string(),
+        FileType::Mpeg => "MP3".to_string(),
+        FileType::Mp4 => "MP4".to_string(),
+        FileType::Mpc => "Musepack".to_string(),
+        FileType::Opus => "Opus".to_string(),
+        FileType::Speex => "Speex".to_string(),
+        FileType::Vorbis => "Vorbis".to_string(),
+        FileType::Wav => "WAV".to_string(),
+        FileType::WavPack => "WavPack".to_string(),
+        other => format!("{:?}", other)
+    }
+}
 
 pub fn format_duration(duration: Duration) -> String {
     let seconds = duration.as_secs() % 60;
@@ -26,70 +310,302 @@ pub fn format_duration(duration: Duration) -> String {
     else { format!("{minutes:0>2}:{seconds:0>2}") }
 }
 
-pub fn get_album(file_path: Option<String>) -> String {
-    let (album_title, _artist, _title) = get_tags(file_path);
+pub fn get_stream() -> (Sink, OutputStream) {
+    let stream = OutputStreamBuilder::open_default_stream().expect("Unable to open stream.");
+    let sink = Sink::connect_new(&stream.mixer());
 
-    album_title
+    (sink, stream)
 }
 
-pub fn get_artist(file_path: Option<String>) -> String {
-    let (_album_title, artist, _title) = get_tags(file_path);
+pub fn get_total_time(file_path: &str) -> Option<Duration> {
+    if !filesys::exists(file_path.to_string()) { return None; }
+
+    // Container headers first, so VBR, Ogg and MP4 files are never fully decoded
+    if let Some(duration) = probe::duration(file_path) { return Some(duration); };
+
+    let duration = match Path::new(&file_path).extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.to_lowercase() == "mp3" => mp3_duration::from_path(file_path).ok(),
+
+        _ => File::open(&file_path).ok().map(BufReader::new)
+            .and_then(|file| Decoder::new(file).ok())
+            .and_then(|source| Source::total_duration(&source))
+    };
 
-    artist
+    duration.filter(|duration| !duration.is_zero())
 }
 
-pub fn get_stream() -> (Sink, OutputStream) {
-    let stream = OutputStreamBuilder::open_default_stream().expect("Unable to open stream.");
-    let sink = Sink::connect_new(&stream.mixer());
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    // FNV-1a rather than DefaultHasher, whose output may change between Rust releases
+    let hash = bytes.iter().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
 
-    (sink, stream)
+    format!("{:016x}", hash)
 }
 
-fn get_tags(file_path: Option<String>) -> (String, String, String) {
-    let mut album_title: String = "".to_string();
-    let mut artist: String = "".to_string();
-    let mut title: String = "".to_string();
+fn lofty_error(error: LoftyError) -> MetadataError {
+    match error.kind() {
+        ErrorKind::Io(inner) => MetadataError::Io(io::Error::new(inner.kind(), inner.to_string())),
+        _ => MetadataError::Unreadable(error.to_string())
+    }
+}
+
+pub fn read_cover(file_path: &str) -> Option<Vec<u8>> {
+    let tagged = Probe::open(file_path).ok()?.read().ok()?;
 
-    if file_path != Some("".to_string()) {
-        let file: String = file_path.clone().unwrap();
-        let tags = Tag::new().read_from_path(&file).unwrap();
+    for tag in tagged.tags() {
+        let pictures = tag.pictures();
 
-        if tags.album_title().is_some() { album_title = tags.album_title().unwrap().to_string(); };
-        if tags.artist().is_some() { artist = tags.artist().unwrap().to_string(); };
-        if tags.title().is_some() { title = tags.title().unwrap().to_string(); };
+        let picture = pictures.iter().find(|picture| picture.pic_type() == PictureType::CoverFront)
+            .or(pictures.first());
+
+        if let Some(picture) = picture { return Some(picture.data().to_vec()); };
     };
 
-    (album_title, artist, title)
+    None
 }
 
-pub fn get_title(file_path: Option<String>) -> String {
-    let (_album_title, _artist, title) = get_tags(file_path.clone());
+pub fn read_lyrics(file_path: &str) -> Option<String> {
+    // Synchronized ID3v2 lyrics win over unsynchronized USLT/LYRICS text
+    if let Some(lyrics) = read_sylt(file_path) { return Some(lyrics); };
+
+    let tagged = Probe::open(file_path).ok()?.read().ok()?;
 
-    title
+    tagged.tags().iter().find_map(|tag| tag.get_string(&ItemKey::Lyrics).map(str::to_string))
+        .filter(|lyrics| !lyrics.trim().is_empty())
 }
 
-pub fn get_total_time(file_path: &str) -> Duration {
-    let mut duration: Duration = {
-        if filesys::exists(file_path.to_string()) {
-            let file = BufReader::new(File::open(&file_path).unwrap());
+pub fn read_metadata(file_path: &str) -> Result<TrackMetadata, MetadataError> {
+    if !filesys::exists(file_path.to_string()) {
+        return Err(MetadataError::Missing(file_path.to_string()));
+    };
+
+    let tagged = Probe::open(file_path).map_err(lofty_error)?.read().map_err(lofty_error)?;
+
+    let properties = tagged.properties();
+    let duration = properties.duration();
+
+    let mut metadata = TrackMetadata {
+        // Properties
+        bitrate: properties.audio_bitrate().filter(|rate| *rate > 0),
+        channels: properties.channels(),
+        codec: Some(codec_name(tagged.file_type())),
+        duration: if duration.is_zero() { None } else { Some(duration) },
+        sample_rate: properties.sample_rate(),
+
+        ..Default::default()
+    };
+
+    if let Some(tag) = tagged.primary_tag().or(tagged.first_tag()) {
+        let text = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+
+        metadata.album = text(tag.album().map(|value| value.to_string()));
+        metadata.album_artist = text(tag.get_string(&ItemKey::AlbumArtist).map(str::to_string));
+        metadata.artist = text(tag.artist().map(|value| value.to_string()));
+        metadata.disc = tag.disk();
+        metadata.genre = text(tag.genre().map(|value| value.to_string()));
+        metadata.title = text(tag.title().map(|value| value.to_string()));
+        metadata.track = tag.track();
+        metadata.year = tag.year();
+
+        if let Some(picture) = tag.pictures().first() {
+            metadata.cover_hash = Some(hash_bytes(picture.data()));
+        };
+    };
+
+    Ok(metadata)
+}
+
+fn read_sylt(file_path: &str) -> Option<String> {
+    let mut file = File::open(file_path).ok()?;
+    let mut header = [0u8; 10];
+
+    file.read_exact(&mut header).ok()?;
+
+    // ID3v2.2 uses three character frame ids and has no SYLT frame worth supporting
+    if &header[0..3] != b"ID3" || !(3..=4).contains(&header[3]) { return None; }
+
+    let syncsafe = |bytes: &[u8]| bytes.iter().fold(0usize, |n, b| (n << 7) | (*b & 0x7F) as usize);
+    let mut body = vec![0u8; syncsafe(&header[6..10])];
+
+    file.read_exact(&mut body).ok()?;
+
+    // ID3v2.3 unsynchronises the whole tag, ID3v2.4 does it frame by frame
+    if header[3] == 3 && header[5] & 0x80 != 0 { body = resync(&body); };
+
+    let mut offset = 0;
+
+    if header[5] & 0x40 != 0 {
+        offset = match header[3] {
+            3 => 4 + u32::from_be_bytes(body.get(0..4)?.try_into().ok()?) as usize,
+            _ => syncsafe(body.get(0..4)?)
+        };
+    };
+
+    while let Some(frame) = body.get(offset..offset + 10) {
+        let size = match header[3] {
+            3 => u32::from_be_bytes(frame[4..8].try_into().ok()?) as usize,
+            _ => syncsafe(&frame[4..8])
+        };
+
+        if frame[0] == 0 || size == 0 { break; }
+
+        let mut content = body.get(offset + 10..offset + 10 + size)?.to_vec();
+
+        if header[3] == 4 {
+            // A data length indicator comes before the frame content
+            if frame[9] & 0x01 != 0 { content = content.get(4..)?.to_vec(); };
+
+            if frame[9] & 0x02 != 0 || header[5] & 0x80 != 0 { content = resync(&content); };
+        };
+
+        if &frame[0..4] == b"SYLT" && content.get(4) == Some(&2) {
+            let encoding = content[0];
+            let (_descriptor, mut rest) = take_text(content.get(6..)?, encoding)?;
+            let mut lyrics = String::new();
+
+            while let Some((text, after)) = take_text(rest, encoding) {
+                let time = u32::from_be_bytes(after.get(0..4)?.try_into().ok()?) as u64;
+                let hundredths = time / 10;
+
+                lyrics += &format!("[{:02}:{:02}.{:02}]{}\n", hundredths / 6000,
+                    (hundredths / 100) % 60, hundredths % 100, text.trim());
+
+                rest = &after[4..];
+            };
+
+            if !lyrics.is_empty() { return Some(lyrics); };
+        };
+
+        offset += 10 + size;
+    };
+
+    None
+}
+
+pub fn read_tags(file_path: &str) -> Result<Tags, Box<dyn Error>> {
+    let tagged = Probe::open(file_path)?.read()?;
+    let mut tags = Tags::new();
+
+    let tag = match tagged.primary_tag().or(tagged.first_tag()) {
+        Some(tag) => tag,
+        None => return Ok(tags)
+    };
+
+    let text = |key: ItemKey| tag.get_string(&key).unwrap_or_default().to_string();
+    let number = |value: Option<u32>| value.map(|n| n.to_string()).unwrap_or_default();
+
+    tags.set(TagField::Title, tag.title().unwrap_or_default().to_string());
+    tags.set(TagField::Artist, tag.artist().unwrap_or_default().to_string());
+    tags.set(TagField::AlbumArtist, text(ItemKey::AlbumArtist));
+    tags.set(TagField::Album, tag.album().unwrap_or_default().to_string());
+    tags.set(TagField::Track, number(tag.track()));
+    tags.set(TagField::Disc, number(tag.disk()));
+    tags.set(TagField::Year, number(tag.year()));
+    tags.set(TagField::Genre, tag.genre().unwrap_or_default().to_string());
+    tags.set(TagField::Comment, tag.comment().unwrap_or_default().to_string());
+    tags.set(TagField::Composer, text(ItemKey::Composer));
+
+    Ok(tags)
+}
+
+fn resync(bytes: &[u8]) -> Vec<u8> {
+    let mut data: Vec<u8> = Vec::with_capacity(bytes.len());
+
+    // Unsynchronisation inserts a zero after every 0xFF
+    for (index, byte) in bytes.iter().enumerate() {
+        if *byte == 0 && index > 0 && bytes[index - 1] == 0xFF { continue; };
+
+        data.push(*byte);
+    };
+
+    data
+}
+
+fn take_text(bytes: &[u8], encoding: u8) -> Option<(String, &[u8])> {
+    if bytes.is_empty() { return None; }
+
+    match encoding {
+        1 | 2 => {
+            let end = bytes.chunks(2).position(|pair| pair == [0, 0]).map(|i| i * 2)
+                .unwrap_or(bytes.len());
+
+            // Without a byte order mark UTF-16 is big-endian
+            let (little, text) = match &bytes[..end] {
+                [0xFF, 0xFE, rest @ ..] => (true, rest),
+                [0xFE, 0xFF, rest @ ..] => (false, rest),
+                text => (false, text)
+            };
 
-            match Path::new(&file_path).extension().and_then(|ext| ext.to_str()) {
-                Some(ext) => match ext.to_lowercase().as_str() {
-                    "mp3" => mp3_duration::from_path(file_path).unwrap_or(Duration::ZERO),
+            let units = text.chunks_exact(2).map(|pair| match little {
+                true => u16::from_le_bytes([pair[0], pair[1]]),
+                false => u16::from_be_bytes([pair[0], pair[1]])
+            }).collect::<Vec<_>>();
 
-                    _ => {
-                        let source = Decoder::new(file).unwrap();
+            Some((String::from_utf16_lossy(&units), bytes.get(end + 2..).unwrap_or_default()))
+        },
 
-                        Source::total_duration(&source).unwrap_or(Duration::ZERO)
-                    }
-                },
+        _ => {
+            let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
 
-                None => Duration::ZERO
-            }
-        } else { Duration::ZERO }
+            let text = match encoding {
+                3 => String::from_utf8_lossy(&bytes[..end]).to_string(),
+                _ => bytes[..end].iter().map(|byte| *byte as char).collect()
+            };
+
+            Some((text, bytes.get(end + 1..).unwrap_or_default()))
+        }
+    }
+}
+
+pub fn write_tags(file_path: &str, tags: &Tags) -> Result<(), Box<dyn Error>> {
+    let track = tags.get_number(TagField::Track)?;
+    let disc = tags.get_number(TagField::Disc)?;
+    let year = tags.get_number(TagField::Year)?;
+
+    let mut tagged = Probe::open(file_path)?.read()?;
+    let tag_type = tagged.primary_tag_type();
+
+    // read_tags falls back to the first tag, so carry it over rather than starting blank
+    if tagged.primary_tag().is_none() {
+        let mut tag = tagged.first_tag().cloned().unwrap_or_else(|| LoftyTag::new(tag_type));
+
+        tag.re_tag(tag_type);
+        tagged.insert_tag(tag);
+    };
+
+    let tag = match tagged.primary_tag_mut() {
+        Some(tag) => tag,
+        None => return Err("Unable to create tag for file.".into())
     };
 
-    if duration != Duration::ZERO { duration += Duration::from_secs(1); };
+    let text = |field: TagField| {
+        let value = tags.get(field);
+
+        if value.trim().is_empty() { None } else { Some(value) }
+    };
+
+    match text(TagField::Title) { Some(v) => tag.set_title(v), None => tag.remove_title() };
+    match text(TagField::Artist) { Some(v) => tag.set_artist(v), None => tag.remove_artist() };
+    match text(TagField::Album) { Some(v) => tag.set_album(v), None => tag.remove_album() };
+    match text(TagField::Genre) { Some(v) => tag.set_genre(v), None => tag.remove_genre() };
+    match text(TagField::Comment) { Some(v) => tag.set_comment(v), None => tag.remove_comment() };
+
+    match text(TagField::AlbumArtist) {
+        Some(v) => { tag.insert_text(ItemKey::AlbumArtist, v); },
+        None => tag.remove_key(&ItemKey::AlbumArtist)
+    };
+
+    match text(TagField::Composer) {
+        Some(v) => { tag.insert_text(ItemKey::Composer, v); },
+        None => tag.remove_key(&ItemKey::Composer)
+    };
+
+    match track { Some(n) => tag.set_track(n), None => tag.remove_track() };
+    match disc { Some(n) => tag.set_disk(n), None => tag.remove_disk() };
+    match year { Some(n) => tag.set_year(n), None => tag.remove_year() };
+
+    tag.save_to_path(file_path, WriteOptions::default())?;
 
-    duration
+    Ok(())
 }
\ No newline at end of file
@@ -1,33 +1,136 @@
 use std::{
     fs::{
         self,
+        File,
         OpenOptions,
         ReadDir,
         read,
         read_dir as read_from_dir
     },
-    io::{Result, Write},
-    path::Path
+    env,
+    io::{Read, Result, Write},
+    path::{Path, PathBuf}
 };
 
-use crate::media::source::Playlist;
+use crate::media::{
+    encoder::Format,
+    source::{Mount, Playlist, Root, Station}
+};
+
+const APP_NAME: &str = "comrad";
+
+const COVER_NAMES: [&str; 5] = ["cover", "folder", "front", "album", "albumart"];
+const COVER_EXTENSIONS: [&str; 4] = ["jpg", "jpeg", "png", "webp"];
+
+const MEDIA_EXTENSIONS: [&str; 20] = [
+    "aac", "aif", "aiff", "avi", "flac", "m4a", "m4b", "m4v", "mka", "mkv",
+    "mov", "mp3", "mp4", "oga", "ogg", "ogv", "opus", "wav", "webm", "wma"
+];
+
+const VIDEO_EXTENSIONS: [&str; 7] = ["avi", "m4v", "mkv", "mov", "mp4", "ogv", "webm"];
+
+const HOME_VAR: &str = "COMRAD_HOME";
+const PORTABLE_FLAG: &str = "--portable";
 
-fn dir() -> String { "./bin".to_string() }
+enum DirKind { Cache, Config, Data }
+
+fn broadcast_file() -> String { "broadcast.ini".to_string() }
+fn broadcast_path() -> String { (config_dir() + "/" + &broadcast_file()).to_string() }
 fn config_file() -> String { "config.ini".to_string() }
-fn config_path() -> String { (dir() + "/" + &config_file()).to_string() }
+fn config_path() -> String { (config_dir() + "/" + &config_file()).to_string() }
+fn library_file() -> String { "library.db".to_string() }
+fn mpd_file() -> String { "mpd.ini".to_string() }
+fn mpd_path() -> String { (config_dir() + "/" + &mpd_file()).to_string() }
 fn playlists_file() -> String { "playlists.ini".to_string() }
-fn playlists_path() -> String { (dir() + "/" + &playlists_file()).to_string() }
+fn playlists_path() -> String { (data_dir() + "/" + &playlists_file()).to_string() }
+fn podcasts_file() -> String { "podcasts.ini".to_string() }
+fn podcasts_path() -> String { (data_dir() + "/" + &podcasts_file()).to_string() }
+fn remote_file() -> String { "remote.ini".to_string() }
+fn remote_path() -> String { (config_dir() + "/" + &remote_file()).to_string() }
+fn roots_file() -> String { "roots.ini".to_string() }
+fn roots_path() -> String { (config_dir() + "/" + &roots_file()).to_string() }
+fn server_file() -> String { "server.ini".to_string() }
+fn server_path() -> String { (config_dir() + "/" + &server_file()).to_string() }
+fn stations_file() -> String { "stations.ini".to_string() }
+fn stations_path() -> String { (data_dir() + "/" + &stations_file()).to_string() }
+
+fn base_dir(kind: DirKind) -> String {
+    if let Some(home) = env::var_os(HOME_VAR) {
+        let sub = match kind {
+            DirKind::Cache => "cache",
+            DirKind::Config => "config",
+            DirKind::Data => "data"
+        };
+
+        return PathBuf::from(home).join(sub).display().to_string();
+    };
+
+    if is_portable() { return portable_dir(); }
+
+    match platform_dir(kind) {
+        Some(path) => path.join(APP_NAME).display().to_string(),
+        None => portable_dir()
+    }
+}
 
 fn create_dir(path: String) -> Result<()> {
-    if !exists(path.clone()) { fs::create_dir(path)?; };
+    if !exists(path.clone()) { fs::create_dir_all(path)?; };
 
     Ok(())
 }
 
+fn env_path(var: &str) -> Option<PathBuf> {
+    env::var_os(var).filter(|value| !value.is_empty()).map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+fn home_path() -> Option<PathBuf> {
+    if cfg!(windows) { env_path("USERPROFILE") } else { env_path("HOME") }
+}
+
+fn is_portable() -> bool {
+    env::args().any(|arg| arg == PORTABLE_FLAG)
+}
+
+fn platform_dir(kind: DirKind) -> Option<PathBuf> {
+    if cfg!(windows) {
+        match kind {
+            DirKind::Cache => env_path("LOCALAPPDATA"),
+            DirKind::Config | DirKind::Data => env_path("APPDATA")
+        }
+    } else if cfg!(target_os = "macos") {
+        let home = home_path()?;
+
+        match kind {
+            DirKind::Cache => Some(home.join("Library/Caches")),
+            DirKind::Config | DirKind::Data => Some(home.join("Library/Application Support"))
+        }
+    } else {
+        match kind {
+            DirKind::Cache => env_path("XDG_CACHE_HOME").or(home_path().map(|h| h.join(".cache"))),
+            DirKind::Config => env_path("XDG_CONFIG_HOME")
+                .or(home_path().map(|h| h.join(".config"))),
+            DirKind::Data => env_path("XDG_DATA_HOME")
+                .or(home_path().map(|h| h.join(".local/share")))
+        }
+    }
+}
+
+fn portable_dir() -> String {
+    match env::current_exe().ok().and_then(|exe| exe.parent().map(|dir| dir.join("bin"))) {
+        Some(path) => path.display().to_string(),
+        None => "./bin".to_string()
+    }
+}
+
+pub fn cache_dir() -> String { base_dir(DirKind::Cache) }
+pub fn config_dir() -> String { base_dir(DirKind::Config) }
+pub fn data_dir() -> String { base_dir(DirKind::Data) }
+
 pub fn create_config() -> Result<()> {
-    let _ = create_dir(dir());
+    let _ = create_dir(config_dir());
 
-    if exists(dir()) {
+    if exists(config_dir()) {
         if !exists(config_path()) {
             let mut f = OpenOptions::new().create_new(true).write(true).truncate(true)
                 .open(config_path()).unwrap();
@@ -48,7 +151,9 @@ pub fn create_from_path(path: String) -> String {
 }
 
 pub fn create_playlists() -> Result<()> {
-    if exists(dir()) {
+    let _ = create_dir(data_dir());
+
+    if exists(data_dir()) {
         if !exists(playlists_path()) {
             let mut f = OpenOptions::new().create_new(true).write(true).open(playlists_path())
                 .unwrap();
@@ -60,6 +165,36 @@ pub fn create_playlists() -> Result<()> {
     Ok(())
 }
 
+pub fn create_roots() -> Result<()> {
+    if exists(config_dir()) && !exists(roots_path()) {
+        let dir = get_dir();
+        let mut roots: Vec<Root> = Vec::new();
+
+        if dir != "/" && dir != String::new() { roots.push(Root::new(dir)); };
+
+        edit_roots(roots)?;
+    };
+
+    Ok(())
+}
+
+pub fn create_stations() -> Result<()> {
+    let _ = create_dir(data_dir());
+
+    if exists(data_dir()) && !exists(stations_path()) { edit_stations(Vec::new())?; };
+
+    Ok(())
+}
+
+pub fn edit_broadcast(mount: Mount) -> Result<()> {
+    let mut f = open_private(broadcast_path())?;
+
+    writeln!(&mut f, "{:?}⁘{:?}⁘{:?}⁘{:?}⁘{:?}⁘{:?}", mount.get_host(), mount.get_port(),
+        mount.get_mount(), mount.get_user(), mount.get_password(), mount.get_format().get_label())?;
+
+    Ok(())
+}
+
 pub fn edit_config(directory: String, volume: i32) -> Result<()> {
     if exists(config_path()) {
         let mut f = OpenOptions::new().write(true).open(config_path()).unwrap();
@@ -71,6 +206,14 @@ pub fn edit_config(directory: String, volume: i32) -> Result<()> {
     Ok(())
 }
 
+pub fn edit_mpd(enabled: bool, port: u16, password: String) -> Result<()> {
+    let mut f = open_private(mpd_path())?;
+
+    writeln!(&mut f, "{:?}⁘{:?}⁘{:?}", enabled, port, password)?;
+
+    Ok(())
+}
+
 pub fn edit_playlists(playlists: Vec<Playlist>) -> Result<()> {
     if exists(playlists_path()) {
         let mut f = OpenOptions::new().write(true).truncate(true).open(playlists_path()).unwrap();
@@ -93,8 +236,90 @@ pub fn edit_playlists(playlists: Vec<Playlist>) -> Result<()> {
     Ok(())
 }
 
+pub fn edit_remote(enabled: bool, port: u16, token: String) -> Result<()> {
+    let mut f = open_private(remote_path())?;
+
+    writeln!(&mut f, "{:?}⁘{:?}⁘{:?}", enabled, port, token)?;
+
+    Ok(())
+}
+
+pub fn edit_roots(roots: Vec<Root>) -> Result<()> {
+    let mut f = OpenOptions::new().create(true).write(true).truncate(true).open(roots_path())?;
+
+    for root in roots {
+        writeln!(&mut f, "{:?}⁘{:?}", root.get_path().replace("\\", "/"), root.is_enabled())?;
+    };
+
+    Ok(())
+}
+
+pub fn edit_server(port: u16, format: String, enabled: bool) -> Result<()> {
+    let mut f = OpenOptions::new().create(true).write(true).truncate(true).open(server_path())?;
+
+    writeln!(&mut f, "{:?}⁘{:?}⁘{:?}", port, format, enabled)?;
+
+    Ok(())
+}
+
+pub fn edit_stations(stations: Vec<Station>) -> Result<()> {
+    let mut f = OpenOptions::new().create(true).write(true).truncate(true)
+        .open(stations_path())?;
+
+    for station in stations {
+        writeln!(&mut f, "{:?}⁘{:?}", station.get_name(), station.get_url())?;
+    };
+
+    Ok(())
+}
+
 pub fn exists(dir: String) -> bool { Path::new(&dir).exists() }
 
+pub fn find_cover(source: String) -> Option<String> {
+    let dir = Path::new(&source).parent()?;
+    let mut entries = read_from_dir(dir).ok()?.flatten().map(|entry| entry.path())
+        .collect::<Vec<_>>();
+
+    entries.sort();
+
+    for name in COVER_NAMES {
+        for entry in &entries {
+            let stem = entry.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default()
+                .to_lowercase();
+
+            let ext = entry.extension().and_then(|ext| ext.to_str()).unwrap_or_default()
+                .to_lowercase();
+
+            if stem == name && COVER_EXTENSIONS.contains(&ext.as_str()) {
+                return Some(entry.display().to_string());
+            };
+        };
+    };
+
+    None
+}
+
+pub fn get_broadcast() -> Mount {
+    let default = Mount::new("localhost".to_string(), 8000, "/live".to_string(),
+        "source".to_string(), String::new(), Format::Mp3);
+
+    if !exists(broadcast_path()) { return default; }
+
+    let file = read_file(broadcast_path());
+    let items = split_fields(file.trim());
+
+    match items.as_slice() {
+        [host, port, mount, user, password, format] => {
+            let Ok(port) = port.parse::<u16>() else { return default; };
+
+            Mount::new(host.clone(), port, mount.clone(), user.clone(), password.clone(),
+                Format::from_label(format).unwrap_or(Format::Mp3))
+        },
+
+        _ => default
+    }
+}
+
 pub fn get_dir() -> String {
     read_file(config_path()).split("\n").collect::<Vec<_>>().get(0).unwrap().to_string()
 }
@@ -103,6 +328,29 @@ pub fn get_file(file: String) -> Result<Vec<u8>> {
     read(file)
 }
 
+pub fn get_library_path() -> String {
+    let _ = create_dir(data_dir());
+
+    (data_dir() + "/" + &library_file()).to_string()
+}
+
+pub fn get_mpd() -> (bool, u16, String) {
+    let default = (false, 6600, String::new());
+
+    if !exists(mpd_path()) { return default; }
+
+    let items = split_fields(read_file(mpd_path()).trim());
+
+    // Files from before the password was added only hold the first two fields
+    match (items.get(0), items.get(1).and_then(|port| port.parse::<u16>().ok())) {
+        (Some(enabled), Some(port)) => {
+            (enabled.trim() == "true", port, items.get(2).cloned().unwrap_or_default())
+        },
+
+        _ => default
+    }
+}
+
 pub fn get_playlists() -> Vec<Playlist> {
     let file = read_file(playlists_path());
     let lines = file.split("\n").collect::<Vec<_>>();
@@ -135,12 +383,136 @@ pub fn get_playlists() -> Vec<Playlist> {
     playlists
 }
 
+pub fn get_playlists_path() -> String {
+    playlists_path()
+}
+
+pub fn get_podcasts_dir() -> String {
+    (data_dir() + "/podcasts").to_string()
+}
+
+pub fn get_podcasts_path() -> String {
+    podcasts_path()
+}
+
+pub fn get_remote() -> (bool, u16, String) {
+    let default = (false, 8091, String::new());
+
+    if !exists(remote_path()) { return default; }
+
+    let items = split_fields(read_file(remote_path()).trim());
+
+    match (items.get(0), items.get(1).and_then(|port| port.parse::<u16>().ok()), items.get(2)) {
+        (Some(enabled), Some(port), Some(token)) => (enabled == "true", port, token.clone()),
+
+        _ => default
+    }
+}
+
+pub fn get_roots() -> Vec<Root> {
+    let mut roots: Vec<Root> = Vec::new();
+
+    if !exists(roots_path()) { return roots; }
+
+    for line in read_file(roots_path()).split("\n") {
+        let items = split_fields(line);
+
+        if let (Some(path), Some(enabled)) = (items.get(0), items.get(1)) {
+            let mut root = Root::new(path.clone());
+
+            root.set_enabled(enabled.trim() == "true");
+
+            roots.push(root);
+        };
+    };
+
+    roots
+}
+
+pub fn get_server() -> (u16, String, bool) {
+    let default = (8090, "MP3".to_string(), false);
+
+    if !exists(server_path()) { return default; }
+
+    let file = read_file(server_path());
+    let items = file.trim().split("⁘").collect::<Vec<_>>();
+
+    match (items.get(0).and_then(|port| port.parse::<u16>().ok()), items.get(1), items.get(2)) {
+        (Some(port), Some(format), Some(enabled)) => {
+            (port, format.replace(r#"""#, ""), enabled.trim() == "true")
+        },
+
+        _ => default
+    }
+}
+
+pub fn get_stations() -> Vec<Station> {
+    let mut stations: Vec<Station> = Vec::new();
+
+    if !exists(stations_path()) { return stations; }
+
+    for line in read_file(stations_path()).split("\n") {
+        let items = split_fields(line.trim());
+
+        if let (Some(name), Some(url)) = (items.get(0), items.get(1)) {
+            stations.push(Station::new(name.clone(), url.clone()));
+        };
+    };
+
+    stations
+}
+
 pub fn get_volume() -> i32 {
     if !exists(config_path()) { return 100; }
 
     read_file(config_path()).split("\n").collect::<Vec<_>>().get(1).unwrap().parse::<i32>().unwrap()
 }
 
+pub fn is_media(path: String) -> bool {
+    let ext = Path::new(&path).extension().and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase()).unwrap_or_default();
+
+    if MEDIA_EXTENSIONS.contains(&ext.as_str()) { return true; }
+
+    sniff(path)
+}
+
+pub fn is_video(path: String) -> bool {
+    let ext = Path::new(&path).extension().and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase()).unwrap_or_default();
+
+    VIDEO_EXTENSIONS.contains(&ext.as_str())
+}
+
+pub fn is_url(path: String) -> bool {
+    let lower = path.to_lowercase();
+
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+// Settings holding secrets are only readable by the owner
+fn open_private(path: String) -> Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    };
+
+    let f = options.open(path)?;
+
+    // The mode only applies on creation, older files are tightened here
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        f.set_permissions(fs::Permissions::from_mode(0o600))?;
+    };
+
+    Ok(f)
+}
+
 pub fn read_dir(dir: String) -> Result<ReadDir> {
     Ok(read_from_dir(dir)?)
 }
@@ -149,4 +521,109 @@ fn read_file(path: String) -> String {
     if !exists(path.clone()) { return "/".to_string(); }
 
     fs::read_to_string(path).expect("Unable to read contents of file.").to_string()
+}
+
+fn sniff(path: String) -> bool {
+    let mut header = [0u8; 12];
+
+    let read = match File::open(path) {
+        Ok(mut file) => file.read(&mut header).unwrap_or(0),
+        Err(_) => 0
+    };
+
+    if read < 12 { return false; }
+
+    match (&header[0..4], &header[4..8], &header[8..12]) {
+        ([b'I', b'D', b'3', _], _, _) => true,
+        ([0xFF, sync, _, _], _, _) if sync & 0xE0 == 0xE0 => true,
+        (b"fLaC", _, _) | (b"OggS", _, _) | ([0x1A, 0x45, 0xDF, 0xA3], _, _) => true,
+        (b"RIFF", _, b"WAVE") | (b"RIFF", _, b"AVI ") => true,
+        (b"FORM", _, b"AIFF") | (b"FORM", _, b"AIFC") => true,
+        (_, b"ftyp", _) => true,
+        _ => false
+    }
+}
+
+// Splits a settings line, undoing the {:?} escaping of quoted fields
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    loop {
+        let mut field = String::new();
+
+        if chars.peek() == Some(&'"') {
+            chars.next();
+
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => match chars.next() {
+                        Some('n') => field.push('\n'),
+                        Some('r') => field.push('\r'),
+                        Some('t') => field.push('\t'),
+                        Some('0') => field.push('\0'),
+                        Some('u') => {
+                            let code = chars.by_ref().skip(1).take_while(|c| *c != '}')
+                                .collect::<String>();
+
+                            if let Some(c) = u32::from_str_radix(&code, 16).ok()
+                                .and_then(char::from_u32) {
+                                field.push(c);
+                            };
+                        },
+                        Some(c) => field.push(c),
+                        None => break
+                    },
+                    c => field.push(c)
+                };
+            };
+
+            fields.push(field);
+
+            // Skip anything between the closing quote and the separator
+            if !chars.any(|c| c == '⁘') { break; }
+        } else {
+            let mut ended = true;
+
+            for c in chars.by_ref() {
+                if c == '⁘' { ended = false; break; }
+                field.push(c);
+            };
+
+            fields.push(field);
+
+            if ended { break; }
+        };
+    };
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_escaped_fields() {
+        let password = "pa\"ss⁘wo\\rd\n\u{7f}";
+        let line = format!("{:?}⁘{:?}⁘{:?}", "host", 8000, password);
+
+        assert_eq!(split_fields(&line), vec!["host".to_string(), "8000".to_string(),
+            password.to_string()]);
+    }
+
+    #[test]
+    fn keeps_quoted_station_names() {
+        let line = format!("{:?}⁘{:?}", r#"Radio "X" \ Live"#, "http://radio.example/a?b=\"c\"");
+
+        assert_eq!(split_fields(&line), vec![r#"Radio "X" \ Live"#.to_string(),
+            "http://radio.example/a?b=\"c\"".to_string()]);
+    }
+
+    #[test]
+    fn splits_bare_and_empty_fields() {
+        assert_eq!(split_fields("true⁘8091⁘\"\""), vec!["true", "8091", ""]);
+        assert_eq!(split_fields(""), vec![""]);
+    }
 }
\ No newline at end of file
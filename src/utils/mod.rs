@@ -1,4 +1,7 @@
+pub mod artwork;
 pub mod data;
 pub mod filesys;
 pub mod images;
-pub mod styles;
\ No newline at end of file
+pub mod probe;
+pub mod styles;
+pub mod web;
\ No newline at end of file
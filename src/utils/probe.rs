@@ -0,0 +1,328 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+    time::Duration
+};
+
+const MPEG_EXTENSIONS: [&str; 3] = ["mp2", "mp3", "mpa"];
+const OGG_TAIL: u64 = 65536;
+const SYNC_FRAMES: usize = 3;
+const SYNC_WINDOW: usize = 65536;
+
+// Kbps by bitrate index for MPEG-1 layers I-III and MPEG-2/2.5 layers I and II/III
+const BITRATES: [[u32; 16]; 5] = [
+    [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448, 0],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384, 0],
+    [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 0],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256, 0],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160, 0]
+];
+
+const SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+
+fn be_u32(bytes: &[u8]) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(..4)?.try_into().ok()?))
+}
+
+fn be_u64(bytes: &[u8]) -> Option<u64> {
+    Some(u64::from_be_bytes(bytes.get(..8)?.try_into().ok()?))
+}
+
+pub fn duration(file_path: &str) -> Option<Duration> {
+    let mut file = File::open(file_path).ok()?;
+    let mut header = [0u8; 12];
+
+    file.read_exact(&mut header).ok()?;
+
+    let duration = match (&header[0..4], &header[4..8]) {
+        (b"fLaC", _) => flac(&mut file, 0),
+        (b"OggS", _) => ogg(&mut file),
+        (_, b"ftyp") => mp4(&mut file),
+
+        _ => {
+            let start = id3_size(&header);
+
+            let mut marker = [0u8; 4];
+
+            file.seek(SeekFrom::Start(start)).ok()?;
+            file.read_exact(&mut marker).ok()?;
+
+            let ext = Path::new(file_path).extension().and_then(|ext| ext.to_str())
+                .unwrap_or_default().to_lowercase();
+
+            // Frame sync is too weak a signature to trust on arbitrary files
+            match &marker {
+                b"fLaC" => flac(&mut file, start),
+                _ if start > 0 || MPEG_EXTENSIONS.contains(&ext.as_str()) => mpeg(&mut file, start),
+                _ => None
+            }
+        }
+    };
+
+    duration.filter(|duration| !duration.is_zero())
+}
+
+fn find_box(file: &mut File, start: u64, end: u64, kind: &[u8; 4]) -> Option<(u64, u64)> {
+    let mut offset = start;
+
+    while offset + 8 <= end {
+        let mut header = [0u8; 16];
+
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        file.read_exact(&mut header[..8]).ok()?;
+
+        let (size, body) = match be_u32(&header)? {
+            0 => (end - offset, offset + 8),
+
+            1 => {
+                file.read_exact(&mut header[8..]).ok()?;
+
+                (be_u64(&header[8..])?, offset + 16)
+            },
+
+            size => (size as u64, offset + 8)
+        };
+
+        if size < body - offset { return None; }
+
+        if &header[4..8] == kind { return Some((body, offset + size)); };
+
+        offset += size;
+    };
+
+    None
+}
+
+fn flac(file: &mut File, start: u64) -> Option<Duration> {
+    let mut block = [0u8; 38];
+
+    file.seek(SeekFrom::Start(start + 4)).ok()?;
+    file.read_exact(&mut block).ok()?;
+
+    // STREAMINFO is always the first metadata block
+    if block[0] & 0x7F != 0 { return None; }
+
+    streaminfo(&block[4..])
+}
+
+fn id3_size(header: &[u8]) -> u64 {
+    if &header[0..3] != b"ID3" { return 0; }
+
+    // Tag size is stored as a syncsafe integer, excluding the header and optional footer
+    let size = header[6..10].iter().fold(0u64, |size, byte| (size << 7) | (*byte & 0x7F) as u64);
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+
+    size + 10 + footer
+}
+
+fn le_i64(bytes: &[u8]) -> Option<i64> {
+    Some(i64::from_le_bytes(bytes.get(..8)?.try_into().ok()?))
+}
+
+fn mp4(file: &mut File) -> Option<Duration> {
+    let end = file.metadata().ok()?.len();
+    let moov = find_box(file, 0, end, b"moov")?;
+    let mvhd = find_box(file, moov.0, moov.1, b"mvhd")?;
+
+    let mut body = [0u8; 32];
+
+    file.seek(SeekFrom::Start(mvhd.0)).ok()?;
+    file.read_exact(&mut body).ok()?;
+
+    let (timescale, length) = match body[0] {
+        0 => (be_u32(&body[12..])?, be_u32(&body[16..])? as u64),
+        1 => (be_u32(&body[20..])?, be_u64(&body[24..])?),
+        _ => return None
+    };
+
+    if timescale == 0 || length == u32::MAX as u64 || length == u64::MAX { return None; }
+
+    Some(Duration::from_secs_f64(length as f64 / timescale as f64))
+}
+
+fn mpeg(file: &mut File, start: u64) -> Option<Duration> {
+    let length = file.metadata().ok()?.len();
+    let mut window = vec![0u8; SYNC_WINDOW];
+
+    file.seek(SeekFrom::Start(start)).ok()?;
+
+    let read = file.read(&mut window).ok()?;
+    let window = &window[..read];
+
+    let (offset, frame) = (0..window.len().saturating_sub(4))
+        .find_map(|offset| sync(&window[offset..]).map(|frame| (offset, frame)))?;
+
+    // Side information sits between the frame header and any Xing/Info or VBRI header
+    let side = match (frame.mpeg1, frame.mono) {
+        (true, true) => 17,
+        (true, false) => 32,
+        (false, true) => 9,
+        (false, false) => 17
+    };
+
+    let frames = window.get(offset + 4 + side..).and_then(|xing| match xing.get(..4)? {
+        b"Xing" | b"Info" if be_u32(&xing[4..])? & 1 != 0 => be_u32(&xing[8..]),
+        _ => None
+    }).or_else(|| window.get(offset + 36..).and_then(|vbri| match vbri.get(..4)? {
+        b"VBRI" => be_u32(&vbri[14..]),
+        _ => None
+    }));
+
+    if let Some(frames) = frames {
+        let samples = frames as u64 * frame.samples as u64;
+
+        return Some(Duration::from_secs_f64(samples as f64 / frame.sample_rate as f64));
+    };
+
+    // Without a VBR header the stream is assumed to be constant bitrate
+    let mut audio = length.saturating_sub(start + offset as u64);
+    let mut tail = [0u8; 3];
+
+    if length > 128 && file.seek(SeekFrom::Start(length - 128)).is_ok()
+        && file.read_exact(&mut tail).is_ok() && &tail == b"TAG" {
+        audio = audio.saturating_sub(128);
+    };
+
+    Some(Duration::from_secs_f64(audio as f64 * 8.0 / (frame.bitrate as f64 * 1000.0)))
+}
+
+fn ogg(file: &mut File) -> Option<Duration> {
+    let mut page = [0u8; 282];
+
+    file.seek(SeekFrom::Start(0)).ok()?;
+
+    let read = file.read(&mut page).ok()?;
+    let page = &page[..read];
+
+    let serial = page.get(14..18)?;
+    let payload = page.get(27 + *page.get(26)? as usize..)?;
+
+    let (rate, skip) = match payload {
+        [0x01, b'v', b'o', b'r', b'b', b'i', b's', ..] => {
+            (u32::from_le_bytes(payload.get(12..16)?.try_into().ok()?) as u64, 0)
+        },
+
+        [b'O', b'p', b'u', b's', b'H', b'e', b'a', b'd', ..] => {
+            (48000, u16::from_le_bytes(payload.get(10..12)?.try_into().ok()?) as i64)
+        },
+
+        [0x7F, b'F', b'L', b'A', b'C', ..] => {
+            return streaminfo(payload.get(17..)?);
+        },
+
+        _ => return None
+    };
+
+    let length = file.metadata().ok()?.len();
+    let start = length.saturating_sub(OGG_TAIL);
+    let mut tail: Vec<u8> = Vec::new();
+
+    file.seek(SeekFrom::Start(start)).ok()?;
+    file.read_to_end(&mut tail).ok()?;
+
+    // The last page of the logical stream carries the total sample count as its granule
+    let granule = (0..tail.len().saturating_sub(27)).rev()
+        .filter(|offset| &tail[*offset..*offset + 4] == b"OggS")
+        .filter(|offset| &tail[*offset + 14..*offset + 18] == serial)
+        .filter_map(|offset| le_i64(&tail[offset + 6..]))
+        .find(|granule| *granule >= 0)?;
+
+    if rate == 0 { return None; }
+
+    Some(Duration::from_secs_f64((granule - skip).max(0) as f64 / rate as f64))
+}
+
+fn parse_frame(bytes: &[u8]) -> Option<Frame> {
+    let (sync, info, rates, mode) = (*bytes.first()?, *bytes.get(1)?, *bytes.get(2)?,
+        *bytes.get(3)?);
+
+    if sync != 0xFF || info & 0xE0 != 0xE0 { return None; }
+
+    let version = (info >> 3) & 0x03;
+    let layer = (info >> 1) & 0x03;
+    let bitrate_index = (rates >> 4) as usize;
+    let rate_index = ((rates >> 2) & 0x03) as usize;
+    let padding = ((rates >> 1) & 0x01) as usize;
+
+    if version == 1 || layer == 0 || bitrate_index == 0 || bitrate_index == 15 || rate_index == 3 {
+        return None;
+    };
+
+    let mpeg1 = version == 3;
+
+    let table = match (mpeg1, layer) {
+        (true, 3) => 0,
+        (true, 2) => 1,
+        (true, _) => 2,
+        (false, 3) => 3,
+        (false, _) => 4
+    };
+
+    let samples = match (layer, mpeg1) {
+        (3, _) => 384,
+        (2, _) | (1, true) => 1152,
+        _ => 576
+    };
+
+    let divisor = match version {
+        3 => 1,
+        2 => 2,
+        _ => 4
+    };
+
+    let bitrate = BITRATES[table][bitrate_index];
+    let sample_rate = SAMPLE_RATES[rate_index] / divisor;
+
+    // Layer I counts in four byte slots, the others in single bytes
+    let length = match layer {
+        3 => (12 * bitrate * 1000 / sample_rate) as usize * 4 + padding * 4,
+        _ => (samples / 8 * bitrate * 1000 / sample_rate) as usize + padding
+    };
+
+    Some(Frame {
+        bitrate,
+        length,
+        mono: mode >> 6 == 3,
+        mpeg1,
+        sample_rate,
+        samples
+    })
+}
+
+fn sync(bytes: &[u8]) -> Option<Frame> {
+    let frame = parse_frame(bytes)?;
+    let mut offset = frame.length;
+
+    // A single sync word turns up often enough in other data, so the next frames must follow
+    for _ in 1..SYNC_FRAMES {
+        let next = parse_frame(bytes.get(offset..)?)?;
+
+        if next.mpeg1 != frame.mpeg1 || next.sample_rate != frame.sample_rate { return None; }
+
+        offset += next.length;
+    };
+
+    Some(frame)
+}
+
+fn streaminfo(block: &[u8]) -> Option<Duration> {
+    let block = block.get(..18)?;
+
+    let rate = ((block[10] as u64) << 12) | ((block[11] as u64) << 4) | (block[12] as u64 >> 4);
+    let samples = ((block[13] as u64 & 0x0F) << 32) | be_u32(&block[14..])? as u64;
+
+    // A zero sample count means the encoder did not know the length up front
+    if rate == 0 || samples == 0 { return None; }
+
+    Some(Duration::from_secs_f64(samples as f64 / rate as f64))
+}
+
+struct Frame {
+    bitrate: u32,
+    length: usize,
+    mono: bool,
+    mpeg1: bool,
+    sample_rate: u32,
+    samples: u32
+}
\ No newline at end of file
@@ -10,11 +10,21 @@ use egui::{
 use std::collections::BTreeMap;
 
 const BUTTON_BG_COLOR: Color32 =  Color32::from_rgb(60, 80, 130);
+const LYRIC_COLOR: Color32 = Color32::from_rgb(120, 170, 255);
+const MISSING_COLOR: Color32 = Color32::from_rgb(200, 80, 80);
 
 pub fn get_button_fill() -> Color32 {
     BUTTON_BG_COLOR
 }
 
+pub fn get_lyric_color() -> Color32 {
+    LYRIC_COLOR
+}
+
+pub fn get_missing_color() -> Color32 {
+    MISSING_COLOR
+}
+
 pub fn set_styles(ctx: &Context) {
     let text_styles: BTreeMap<TextStyle, FontId> = [
         (TextStyle::Body, FontId::new(20.0, Proportional)),
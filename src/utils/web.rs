@@ -0,0 +1,419 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::{self, BufRead, BufReader, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering}
+    },
+    thread::{self, JoinHandle},
+    time::Duration
+};
+
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+
+// Clients only send control frames and short texts
+const MAX_CLIENT_FRAME: u64 = 64 * 1024;
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+impl Request {
+    //** Getters **//
+    pub fn get_header(&self, name: &str) -> Option<String> {
+        self.headers.get(&name.to_lowercase()).cloned()
+    }
+
+    pub fn get_method(&self) -> String {
+        self.method.clone()
+    }
+
+    pub fn get_path(&self) -> String {
+        self.path.clone()
+    }
+
+    pub fn get_query(&self, key: &str) -> Option<String> {
+        self.query.get(key).cloned()
+    }
+
+    pub fn is_websocket(&self) -> bool {
+        self.get_header("upgrade").is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+    }
+
+    //** Misc. **//
+    pub fn read(stream: &TcpStream) -> io::Result<Self> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+
+        reader.read_line(&mut line)?;
+
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let target = parts.next().unwrap_or("/");
+
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, query),
+            None => (target, "")
+        };
+
+        let query = query.split('&').filter_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+
+            if key.is_empty() { None } else { Some((decode(key), decode(value))) }
+        }).collect::<HashMap<_, _>>();
+
+        let mut headers = HashMap::new();
+
+        loop {
+            let mut header = String::new();
+
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() { break; }
+
+            if let Some((name, value)) = header.split_once(':') {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            };
+        };
+
+        Ok(Self {
+            headers,
+            method,
+            path: decode(path),
+            query
+        })
+    }
+}
+
+pub fn accept_websocket(stream: &mut TcpStream, request: &Request) -> io::Result<()> {
+    let key = request.get_header("sec-websocket-key").unwrap_or_default();
+    let accept = base64(&sha1((key + WEBSOCKET_GUID).as_bytes()));
+
+    write!(stream, concat!("HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n",
+        "Connection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n"), accept)
+}
+
+pub fn base64(input: &[u8]) -> String {
+    let mut output = String::new();
+
+    for chunk in input.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let packed = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                output.push(BASE64[(packed >> (18 - index * 6)) as usize & 0x3F] as char);
+            } else { output.push('='); };
+        };
+    };
+
+    output
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let hex = bytes.get(index + 1..index + 3).and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            },
+
+            (b'+', _) => {
+                decoded.push(b' ');
+                index += 1;
+            },
+
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        };
+    };
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// File urls keep '+' as it is, so only the escapes are undone
+pub fn decode_path(text: &str) -> String {
+    decode(&text.replace('+', "%2B"))
+}
+
+pub fn encode_path(path: &str) -> String {
+    path.bytes().map(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+            (byte as char).to_string()
+        },
+
+        byte => format!("%{:02X}", byte)
+    }).collect()
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", character as u32));
+            },
+            character => escaped.push(character)
+        };
+    };
+
+    escaped.push('"');
+
+    escaped
+}
+
+// Each connection gets its own thread, the loop ends once running is cleared.
+// The accept loop owns the socket, so joining the handle frees the port for a rebind
+pub fn listen(host: &str, port: u16, running: Arc<AtomicBool>,
+    handler: impl Fn(TcpStream) + Send + Sync + 'static) -> io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind((host, port))?;
+
+    listener.set_nonblocking(true)?;
+
+    let handler = Arc::new(handler);
+
+    Ok(thread::spawn(move || {
+        while running.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let handler = handler.clone();
+
+                    thread::spawn(move || handler(stream));
+                },
+
+                Err(_) => thread::sleep(ACCEPT_INTERVAL)
+            };
+        };
+    }))
+}
+
+pub fn read_websocket_frame(reader: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 2];
+
+    reader.read_exact(&mut header)?;
+
+    let length = match header[1] & 0x7F {
+        126 => {
+            let mut length = [0u8; 2];
+            reader.read_exact(&mut length)?;
+
+            u16::from_be_bytes(length) as u64
+        },
+
+        127 => {
+            let mut length = [0u8; 8];
+            reader.read_exact(&mut length)?;
+
+            u64::from_be_bytes(length)
+        },
+
+        length => length as u64
+    };
+
+    if length > MAX_CLIENT_FRAME {
+        return Err(io::Error::new(ErrorKind::InvalidData, "WebSocket frame too large"));
+    };
+
+    let mut mask = [0u8; 4];
+
+    // Client frames are always masked, but an unmasked one reads the same with a zero mask
+    if header[1] & 0x80 != 0 { reader.read_exact(&mut mask)?; };
+
+    let mut payload = vec![0u8; length as usize];
+
+    reader.read_exact(&mut payload)?;
+
+    for (index, byte) in payload.iter_mut().enumerate() { *byte ^= mask[index % 4]; };
+
+    Ok((header[0] & 0x0F, payload))
+}
+
+fn sha1(input: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = input.to_vec();
+
+    message.push(0x80);
+
+    while message.len() % 64 != 56 { message.push(0); };
+
+    message.extend_from_slice(&(input.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+
+        for (index, word) in block.chunks(4).enumerate() {
+            words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        };
+
+        for index in 16..80 {
+            words[index] = (words[index - 3] ^ words[index - 8] ^ words[index - 14]
+                ^ words[index - 16]).rotate_left(1);
+        };
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+
+        for (index, word) in words.iter().enumerate() {
+            let (mix, constant) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6)
+            };
+
+            let next = a.rotate_left(5).wrapping_add(mix).wrapping_add(e).wrapping_add(constant)
+                .wrapping_add(*word);
+
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = next;
+        };
+
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(add);
+        };
+    };
+
+    let mut digest = [0u8; 20];
+
+    for (index, value) in state.iter().enumerate() {
+        digest[index * 4..index * 4 + 4].copy_from_slice(&value.to_be_bytes());
+    };
+
+    digest
+}
+
+fn websocket(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+
+    // Server frames are never masked, only the length encoding varies
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        },
+
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    };
+
+    frame.extend_from_slice(payload);
+
+    frame
+}
+
+pub fn websocket_control(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    websocket(opcode, payload)
+}
+
+pub fn websocket_frame(text: &str) -> Vec<u8> {
+    websocket(0x1, text.as_bytes())
+}
+
+pub fn write_response(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8])
+    -> Result<(), Box<dyn Error>> {
+    write!(stream, concat!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n",
+        "Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n"), status, content_type,
+        body.len())?;
+
+    stream.write_all(body)?;
+
+    Ok(())
+}
+
+pub struct Request {
+    headers: HashMap<String, String>,
+    method: String,
+    path: String,
+    query: HashMap<String, String>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn hashes_rfc_3174_vectors() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hex(&sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+
+        assert_eq!(hex(&sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+
+        assert_eq!(hex(&sha1(&[b'a'; 1_000_000])), "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+    }
+
+    #[test]
+    fn encodes_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn accepts_the_rfc_6455_key() {
+        let key = "dGhlIHNhbXBsZSBub25jZQ==".to_string() + WEBSOCKET_GUID;
+
+        assert_eq!(base64(&sha1(key.as_bytes())), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn reads_masked_client_frames() {
+        // The masked "Hello" example from RFC 6455
+        let frame = [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+
+        assert_eq!(read_websocket_frame(&mut &frame[..]).unwrap(), (0x1, b"Hello".to_vec()));
+
+        let close = [0x88, 0x82, 1, 2, 3, 4, 0x03 ^ 1, 0xe8 ^ 2];
+
+        assert_eq!(read_websocket_frame(&mut &close[..]).unwrap(), (0x8, vec![0x03, 0xe8]));
+        assert!(read_websocket_frame(&mut &frame[..4]).is_err());
+    }
+
+    #[test]
+    fn writes_server_frames() {
+        assert_eq!(websocket_frame("Hi"), vec![0x81, 2, b'H', b'i']);
+        assert_eq!(websocket_control(0x9, &[]), vec![0x89, 0]);
+        assert_eq!(&websocket_frame(&"a".repeat(200))[..4], &[0x81, 126, 0, 200]);
+    }
+
+    #[test]
+    fn decodes_queries() {
+        assert_eq!(decode("a%20b+c%2Fd"), "a b c/d");
+        assert_eq!(decode("100%"), "100%");
+        assert_eq!(decode_path("/a%20b+c"), "/a b+c");
+    }
+
+    #[test]
+    fn encodes_paths() {
+        assert_eq!(encode_path("/music/Sigur Rós/a+b #1.flac"),
+            "/music/Sigur%20R%C3%B3s/a%2Bb%20%231.flac");
+
+        assert_eq!(decode_path(&encode_path("/a b/ü+%.mp3")), "/a b/ü+%.mp3");
+    }
+}
\ No newline at end of file